@@ -14,7 +14,7 @@ fn main() {
     let params = setup(K_RANGE, &mut rng);
 
     let vk = keygen_vk(&params[&K_RANGE.start], &StandardPlonk::default()).unwrap();
-    let generator = SolidityGenerator::new(&params[&K_RANGE.start], &vk, Bdfg21, 0);
+    let generator = SolidityGenerator::new(&params[&K_RANGE.start], &vk, Bdfg21, vec![0]);
     let (verifier_solidity, _) = generator.render_separately().unwrap();
     save_solidity("Halo2Verifier.sol", &verifier_solidity);
 
@@ -33,7 +33,8 @@ fn main() {
 
         let vk = keygen_vk(&params[&k], &circuit).unwrap();
         let pk = keygen_pk(&params[&k], vk, &circuit).unwrap();
-        let generator = SolidityGenerator::new(&params[&k], pk.get_vk(), Bdfg21, num_instances);
+        let generator =
+            SolidityGenerator::new(&params[&k], pk.get_vk(), Bdfg21, vec![num_instances]);
         let (verifier_solidity, vk_solidity) = generator.render_separately().unwrap();
         save_solidity(format!("Halo2VerifyingKey-{k}.sol"), &vk_solidity);
 
@@ -45,7 +46,7 @@ fn main() {
         let calldata = {
             let instances = circuit.instances();
             let proof = create_proof_checked(&params[&k], &pk, circuit, &instances, &mut rng);
-            encode_calldata(Some(vk_address.into()), &proof, &instances)
+            encode_calldata(Some(vk_address.into()), &proof, &[instances])
         };
         let (gas_cost, output) = evm.call(verifier_address, calldata);
         assert_eq!(output, [vec![0; 31], vec![1]].concat());
@@ -1,26 +1,55 @@
-use crate::codegen::{
-    evaluator::Evaluator,
-    pcs::{
-        bdfg21_computations, queries, rotation_sets,
-        BatchOpenScheme::{Bdfg21, Gwc19},
+use crate::{
+    codegen::{
+        abi::{self, CustomError, CUSTOM_ERRORS},
+        code_size::{estimate_code_size, estimate_yul_code_size, fits_in, MAX_CODE_SIZE},
+        evaluator::{num_shared_slots, Evaluator},
+        pcs::{
+            bdfg21_computations, gwc19_computations, queries, rotation_sets,
+            BatchOpenScheme::{Bdfg21, Gwc19},
+        },
+        template::{
+            Halo2MultiVerifier, Halo2Verifier, Halo2VerifierHelper, Halo2VerifyingKey, HelperKind,
+            IHalo2Verifier,
+        },
+        util::{
+            fr_to_u256, g1_to_u256s, g2_to_u256s, ConstraintSystemMeta, Data, ProofEncoding, Ptr,
+        },
     },
-    template::{Halo2Verifier, Halo2VerifyingKey},
-    util::{fr_to_u256, g1_to_u256s, g2_to_u256s, ConstraintSystemMeta, Data, Ptr},
+    transcript::{EvmTranscriptSpec, Keccak256Spec},
 };
 use halo2_proofs::{
-    halo2curves::{bn256, ff::Field},
+    halo2curves::{
+        bn256,
+        ff::{Field, PrimeField},
+    },
     plonk::VerifyingKey,
-    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG, Rotation},
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::commitment::ParamsKZG,
+    },
 };
 use itertools::{chain, Itertools};
 use ruint::aliases::U256;
-use std::fmt::{self, Debug};
+use sha3::{Digest, Keccak256};
+use std::{
+    fmt::{self, Debug},
+    mem,
+};
 
-mod evaluator;
+pub(crate) mod abi;
+pub(crate) mod code_size;
+mod diagnostic;
+pub(crate) mod evaluator;
+mod gas;
+mod layout;
 mod pcs;
 mod template;
 pub(crate) mod util;
 
+pub use code_size::{CodeSizeReport, CodeSizeSections, CodeSizeWarning, ContractCodeSize};
+pub use diagnostic::FailedCheck;
+pub use gas::GasEstimate;
+pub use layout::{DecodedProof, ProofField, ProofLayout};
 pub use pcs::BatchOpenScheme;
 
 /// Solidity verifier generator for [`halo2`] proof with KZG polynomial commitment scheme on BN254.
@@ -29,15 +58,29 @@ pub struct SolidityGenerator<'a> {
     params: &'a ParamsKZG<bn256::Bn256>,
     vk: &'a VerifyingKey<bn256::G1Affine>,
     scheme: BatchOpenScheme,
-    num_instances: usize,
+    num_instances: Vec<usize>,
     acc_encoding: Option<AccumulatorEncoding>,
+    public_input_hash: Option<PublicInputHash>,
+    custom_errors: bool,
+    non_reverting: bool,
+    compressed_points: bool,
+    transcript: Transcript,
+    max_code_size: usize,
     meta: ConstraintSystemMeta,
 }
 
+/// Solidity verifier generator for proofs of multiple [`halo2`] circuits sharing the same KZG
+/// parameters, which verifies all of them with a single pairing.
+#[derive(Debug)]
+pub struct SolidityMultiGenerator<'a> {
+    generators: Vec<SolidityGenerator<'a>>,
+}
+
 /// KZG accumulator encoding information.
 /// Limbs of each field element are assumed to be least significant limb first.
 ///
-/// Given instances and `AccumulatorEncoding`, the accumulator will be interpreted as below:
+/// Given instances (all instance columns concatenated in order) and `AccumulatorEncoding`, the
+/// accumulator will be interpreted as below:
 /// ```rust
 /// use halo2_proofs::halo2curves::{bn256, ff::{Field, PrimeField}, CurveAffine};
 ///
@@ -88,46 +131,536 @@ impl AccumulatorEncoding {
     }
 }
 
+/// Public input hash encoding information.
+/// Limbs of the hash are assumed to be least significant limb first.
+///
+/// Given instances (all instance columns concatenated in order) and `PublicInputHash`, the
+/// verifier takes public data as an extra `bytes publicData` argument, and checks if the
+/// keccak256 of it interpreted as big-endian integer matches the limbs as below:
+/// ```rust
+/// use halo2_proofs::halo2curves::{bn256, ff::PrimeField};
+/// use ruint::aliases::U256;
+/// use sha3::{Digest, Keccak256};
+///
+/// fn is_public_input_hash_valid(
+///     instances: &[bn256::Fr],
+///     public_data: &[u8],
+///     offset: usize,
+///     num_limbs: usize,
+///     num_limb_bits: usize,
+/// ) -> bool {
+///     let hash = U256::from_be_bytes(<[u8; 32]>::from(Keccak256::digest(public_data)));
+///     let mask = (U256::from(1) << num_limb_bits) - U256::from(1);
+///     (0..num_limbs).all(|idx| {
+///         let limb = (hash >> (idx * num_limb_bits)) & mask;
+///         instances[offset + idx].to_repr() == limb.to_le_bytes()
+///     })
+/// }
+/// ```
+///
+/// So the circuit only needs to expose the limbs of the hash instead of all public inputs, and
+/// the limbs can be computed by [`PublicInputHash::limbs`].
+#[derive(Clone, Copy, Debug)]
+pub struct PublicInputHash {
+    /// Offset of hash limbs in instances.
+    pub offset: usize,
+    /// Number of limbs.
+    pub num_limbs: usize,
+    /// Number of bits per limb.
+    pub num_limb_bits: usize,
+}
+
+impl PublicInputHash {
+    /// Return a new `PublicInputHash`.
+    pub fn new(offset: usize, num_limbs: usize, num_limb_bits: usize) -> Self {
+        Self {
+            offset,
+            num_limbs,
+            num_limb_bits,
+        }
+    }
+
+    /// Return limbs of keccak256 of `public_data`, which are expected to be in instances at
+    /// `offset`.
+    pub fn limbs(&self, public_data: &[u8]) -> Vec<bn256::Fr> {
+        let hash = U256::from_be_bytes(<[u8; 32]>::from(Keccak256::digest(public_data)));
+        (0..self.num_limbs)
+            .map(|idx| {
+                let limb = (hash >> (idx * self.num_limb_bits)) & self.mask();
+                bn256::Fr::from_repr(limb.to_le_bytes()).unwrap()
+            })
+            .collect()
+    }
+
+    pub(crate) fn mask(&self) -> U256 {
+        (U256::from(1) << self.num_limb_bits) - U256::from(1)
+    }
+}
+
+/// Hash function of transcript not driven by [`EvmTranscriptSpec`], which writes proof as
+/// `halo2`'s own transcripts do, with points compressed and scalars in little-endian, and absorbs
+/// each point or scalar as soon as it's read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranscriptHash {
+    /// Blake2b by `halo2_proofs::transcript::Blake2bWrite` with `Challenge255`, which is computed
+    /// by the verifier with the `blake2f` precompile of EIP-152, including the wide reduction of
+    /// 64-byte output into challenge.
+    Blake2b,
+    /// Poseidon by [`PoseidonTranscript`](crate::PoseidonTranscript), which is computed by the
+    /// verifier in Yul without precompile. Requires feature `poseidon`.
+    #[cfg(feature = "poseidon")]
+    Poseidon,
+}
+
+/// Transcript the proof is created with, which the verifier replays to derive challenges.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Transcript {
+    /// [`EvmTranscript`](crate::EvmTranscript) with the spec.
+    Evm(EvmTranscriptFns),
+    /// Transcript hashing by `TranscriptHash`.
+    LittleEndian(TranscriptHash),
+}
+
+impl Transcript {
+    pub(crate) fn evm<S: EvmTranscriptSpec>() -> Self {
+        Self::Evm(EvmTranscriptFns::new::<S>())
+    }
+
+    pub(crate) fn little_endian_hash(&self) -> Option<TranscriptHash> {
+        match self {
+            Self::Evm(_) => None,
+            Self::LittleEndian(transcript_hash) => Some(*transcript_hash),
+        }
+    }
+}
+
+/// Functions of [`EvmTranscriptSpec`] with the spec type erased, so the generator doesn't need to
+/// be generic over it.
+#[derive(Clone, Copy)]
+pub(crate) struct EvmTranscriptFns {
+    name: &'static str,
+    pub(crate) yul_hash: fn(&str, &str) -> Vec<String>,
+    pub(crate) yul_hash_gas: fn(u64) -> u64,
+    pub(crate) precompile: fn() -> Option<u8>,
+    pub(crate) verify: diagnostic::VerifyFn,
+}
+
+impl EvmTranscriptFns {
+    fn new<S: EvmTranscriptSpec>() -> Self {
+        Self {
+            name: std::any::type_name::<S>(),
+            yul_hash: S::yul_hash,
+            yul_hash_gas: S::yul_hash_gas,
+            precompile: S::precompile,
+            verify: diagnostic::verify_evm_transcript::<S>,
+        }
+    }
+}
+
+impl Debug for EvmTranscriptFns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EvmTranscriptFns").field(&self.name).finish()
+    }
+}
+
+/// Error of unsupported circuit or invalid configuration given to [`SolidityGenerator`] or
+/// [`SolidityMultiGenerator`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GeneratorError {
+    /// Circuit has no advice column.
+    NoAdviceColumn,
+    /// Number of instances is not given for each instance column.
+    InstanceColumnMismatch {
+        /// Number of instance columns of circuit.
+        num_instance_columns: usize,
+        /// Number of instance columns given in `num_instances`.
+        num_given: usize,
+    },
+    /// Number of instances of an instance column is larger than usable rows.
+    TooManyInstances {
+        /// Index of instance column.
+        column_index: usize,
+        /// Number of instances of the instance column.
+        num_instances: usize,
+        /// Number of usable rows.
+        num_usable_rows: usize,
+    },
+    /// Accumulator limbs are out of instances.
+    AccumulatorOutOfRange {
+        /// End of accumulator limbs in instances, which is `offset + 4 * num_limbs`.
+        end: usize,
+        /// Total number of instances.
+        num_instances: usize,
+    },
+    /// Public input hash limbs are out of instances.
+    PublicInputHashOutOfRange {
+        /// End of public input hash limbs in instances, which is `offset + num_limbs`.
+        end: usize,
+        /// Total number of instances.
+        num_instances: usize,
+    },
+    /// Public input hash limbs are empty, don't fit in scalar field, or exceed 256 bits in total.
+    InvalidPublicInputHashLimbs {
+        /// Number of limbs.
+        num_limbs: usize,
+        /// Number of bits per limb.
+        num_limb_bits: usize,
+    },
+    /// KZG parameters are too small for domain of verifying key.
+    ParamsTooSmall {
+        /// `k` of KZG parameters.
+        params_k: u32,
+        /// `k` of domain of verifying key.
+        vk_k: u32,
+    },
+    /// No generator is given to [`SolidityMultiGenerator`].
+    NoGenerator,
+    /// Generator given to [`SolidityMultiGenerator`] doesn't share the same `g2` and `s_g2` of KZG
+    /// parameters with the first one.
+    ParamsMismatch {
+        /// Index of the generator.
+        index: usize,
+    },
+    /// Generator given to [`SolidityMultiGenerator`] has [`PublicInputHash`] set, which is not
+    /// supported yet.
+    MultiPublicInputHash {
+        /// Index of the generator.
+        index: usize,
+    },
+    /// Generator given to [`SolidityMultiGenerator`] has custom errors set, which is not
+    /// supported yet.
+    MultiCustomErrors {
+        /// Index of the generator.
+        index: usize,
+    },
+    /// Generator given to [`SolidityMultiGenerator`] is set to be non-reverting, which is not
+    /// supported yet.
+    MultiNonReverting {
+        /// Index of the generator.
+        index: usize,
+    },
+    /// Number of writers given to [`SolidityMultiGenerator::render_into`] is not the number of
+    /// generators.
+    WriterMismatch {
+        /// Number of generators.
+        num_generators: usize,
+        /// Number of writers given.
+        num_writers: usize,
+    },
+    /// Estimated runtime code size of a contract rendered by [`SolidityGenerator::render_split`]
+    /// still exceeds the maximum code size after splitting.
+    CodeSizeTooLarge {
+        /// Name of the contract.
+        contract: String,
+        /// Estimated runtime code size.
+        size: usize,
+        /// Maximum runtime code size.
+        max_code_size: usize,
+    },
+    /// Failed to render template.
+    Render(fmt::Error),
+}
+
+impl fmt::Display for GeneratorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoAdviceColumn => write!(f, "Circuit should have at least 1 advice column"),
+            Self::InstanceColumnMismatch {
+                num_instance_columns,
+                num_given,
+            } => write!(
+                f,
+                "Expected num_instances of {num_instance_columns} columns, but got {num_given}"
+            ),
+            Self::TooManyInstances {
+                column_index,
+                num_instances,
+                num_usable_rows,
+            } => write!(
+                f,
+                "Instance column {column_index} has {num_instances} instances > {num_usable_rows} usable rows"
+            ),
+            Self::AccumulatorOutOfRange { end, num_instances } => write!(
+                f,
+                "Accumulator limbs end at {end}, out of {num_instances} instances"
+            ),
+            Self::PublicInputHashOutOfRange { end, num_instances } => write!(
+                f,
+                "Public input hash limbs end at {end}, out of {num_instances} instances"
+            ),
+            Self::InvalidPublicInputHashLimbs {
+                num_limbs,
+                num_limb_bits,
+            } => write!(
+                f,
+                "Public input hash with {num_limbs} limbs of {num_limb_bits} bits is not supported"
+            ),
+            Self::ParamsTooSmall { params_k, vk_k } => write!(
+                f,
+                "KZG parameters with k = {params_k} are smaller than domain k = {vk_k}"
+            ),
+            Self::NoGenerator => write!(f, "Multi verifier should have at least 1 generator"),
+            Self::ParamsMismatch { index } => write!(
+                f,
+                "Generator {index} doesn't share the same g2 and s_g2 with generator 0"
+            ),
+            Self::MultiPublicInputHash { index } => write!(
+                f,
+                "Generator {index} has public input hash, which is not supported by multi verifier"
+            ),
+            Self::MultiCustomErrors { index } => write!(
+                f,
+                "Generator {index} has custom errors, which is not supported by multi verifier"
+            ),
+            Self::MultiNonReverting { index } => write!(
+                f,
+                "Generator {index} is non-reverting, which is not supported by multi verifier"
+            ),
+            Self::WriterMismatch {
+                num_generators,
+                num_writers,
+            } => write!(
+                f,
+                "Expected {num_generators} verifier writers, but got {num_writers}"
+            ),
+            Self::CodeSizeTooLarge {
+                contract,
+                size,
+                max_code_size,
+            } => write!(
+                f,
+                "Estimated runtime code size of {contract} {size} exceeds {max_code_size} after splitting"
+            ),
+            Self::Render(err) => write!(f, "Failed to render template: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for GeneratorError {}
+
+impl From<fmt::Error> for GeneratorError {
+    fn from(err: fmt::Error) -> Self {
+        Self::Render(err)
+    }
+}
+
 impl<'a> SolidityGenerator<'a> {
     /// Return a new `SolidityGenerator`.
+    ///
+    /// The `num_instances` is the number of instances of each instance column.
+    ///
+    /// # Panics
+    /// Panics if the circuit is not supported or `num_instances` is invalid, see
+    /// [`SolidityGenerator::try_new`] for the fallible version.
     pub fn new(
         params: &'a ParamsKZG<bn256::Bn256>,
         vk: &'a VerifyingKey<bn256::G1Affine>,
         scheme: BatchOpenScheme,
-        num_instances: usize,
+        num_instances: Vec<usize>,
     ) -> Self {
-        assert_ne!(vk.cs().num_advice_columns(), 0);
-        assert_eq!(
-            vk.cs().num_instance_columns(),
-            1,
-            "Multiple instance columns is not yet implemented"
-        );
-        assert!(
-            !vk.cs()
-                .instance_queries()
-                .iter()
-                .any(|(_, rotation)| *rotation != Rotation::cur()),
-            "Rotated query to instance column is not yet implemented"
-        );
-        assert_eq!(
-            scheme,
-            BatchOpenScheme::Bdfg21,
-            "BatchOpenScheme::Gwc19 is not yet implemented"
-        );
+        Self::try_new(params, vk, scheme, num_instances).unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        Self {
+    /// Return a new `SolidityGenerator`, or `GeneratorError` if the circuit is not supported or
+    /// `num_instances` is invalid.
+    ///
+    /// The `num_instances` is the number of instances of each instance column.
+    pub fn try_new(
+        params: &'a ParamsKZG<bn256::Bn256>,
+        vk: &'a VerifyingKey<bn256::G1Affine>,
+        scheme: BatchOpenScheme,
+        num_instances: Vec<usize>,
+    ) -> Result<Self, GeneratorError> {
+        let cs = vk.cs();
+        if cs.num_advice_columns() == 0 {
+            return Err(GeneratorError::NoAdviceColumn);
+        }
+        if cs.num_instance_columns() != num_instances.len() {
+            return Err(GeneratorError::InstanceColumnMismatch {
+                num_instance_columns: cs.num_instance_columns(),
+                num_given: num_instances.len(),
+            });
+        }
+        let vk_k = vk.get_domain().k();
+        if params.k() < vk_k {
+            return Err(GeneratorError::ParamsTooSmall {
+                params_k: params.k(),
+                vk_k,
+            });
+        }
+
+        let meta = ConstraintSystemMeta::new(cs);
+        let num_usable_rows = (1 << vk_k) - meta.rotation_last.unsigned_abs() as usize;
+        if let Some((column_index, num_instances)) = num_instances
+            .iter()
+            .copied()
+            .enumerate()
+            .find(|(_, num_instances)| *num_instances > num_usable_rows)
+        {
+            return Err(GeneratorError::TooManyInstances {
+                column_index,
+                num_instances,
+                num_usable_rows,
+            });
+        }
+
+        Ok(Self {
             params,
             vk,
             scheme,
             num_instances,
             acc_encoding: None,
-            meta: ConstraintSystemMeta::new(vk.cs()),
-        }
+            public_input_hash: None,
+            custom_errors: false,
+            non_reverting: false,
+            compressed_points: false,
+            transcript: Transcript::evm::<Keccak256Spec>(),
+            max_code_size: MAX_CODE_SIZE,
+            meta,
+        })
     }
 
     /// Set `AccumulatorEncoding`.
-    pub fn set_acc_encoding(mut self, acc_encoding: Option<AccumulatorEncoding>) -> Self {
+    ///
+    /// # Panics
+    /// Panics if the accumulator limbs are out of instances, see
+    /// [`SolidityGenerator::try_set_acc_encoding`] for the fallible version.
+    pub fn set_acc_encoding(self, acc_encoding: Option<AccumulatorEncoding>) -> Self {
+        self.try_set_acc_encoding(acc_encoding)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Set `AccumulatorEncoding`, or return `GeneratorError` if the accumulator limbs are out of
+    /// instances.
+    pub fn try_set_acc_encoding(
+        mut self,
+        acc_encoding: Option<AccumulatorEncoding>,
+    ) -> Result<Self, GeneratorError> {
+        if let Some(acc_encoding) = acc_encoding {
+            let end = acc_encoding.offset + 4 * acc_encoding.num_limbs;
+            let num_instances = self.num_instances.iter().sum();
+            if end > num_instances {
+                return Err(GeneratorError::AccumulatorOutOfRange { end, num_instances });
+            }
+        }
         self.acc_encoding = acc_encoding;
+        Ok(self)
+    }
+
+    /// Set `PublicInputHash`, which adds a `bytes publicData` argument to functions of the
+    /// verifier and checks its keccak256 against limbs in instances.
+    ///
+    /// Calldata should be encoded by
+    /// [`encode_calldata_with_public_data`](crate::encode_calldata_with_public_data) or
+    /// [`encode_calldata_batch_with_public_data`](crate::encode_calldata_batch_with_public_data).
+    ///
+    /// # Panics
+    /// Panics if the hash limbs are invalid or out of instances, see
+    /// [`SolidityGenerator::try_set_public_input_hash`] for the fallible version.
+    pub fn set_public_input_hash(self, public_input_hash: Option<PublicInputHash>) -> Self {
+        self.try_set_public_input_hash(public_input_hash)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Set `PublicInputHash`, or return `GeneratorError` if the hash limbs are invalid or out of
+    /// instances.
+    pub fn try_set_public_input_hash(
+        mut self,
+        public_input_hash: Option<PublicInputHash>,
+    ) -> Result<Self, GeneratorError> {
+        if let Some(public_input_hash) = public_input_hash {
+            let PublicInputHash {
+                offset,
+                num_limbs,
+                num_limb_bits,
+            } = public_input_hash;
+            // Limb of at most 253 bits is always less than scalar field modulus
+            if num_limbs == 0
+                || !(1..=253).contains(&num_limb_bits)
+                || num_limbs * num_limb_bits > 256
+            {
+                return Err(GeneratorError::InvalidPublicInputHashLimbs {
+                    num_limbs,
+                    num_limb_bits,
+                });
+            }
+            let end = offset + num_limbs;
+            let num_instances = self.num_instances.iter().sum();
+            if end > num_instances {
+                return Err(GeneratorError::PublicInputHashOutOfRange { end, num_instances });
+            }
+        }
+        self.public_input_hash = public_input_hash;
+        Ok(self)
+    }
+
+    /// Set whether the verifier reverts with custom error at the first failing check, instead of
+    /// reverting with empty data after all checks. Default is `false`.
+    ///
+    /// The revert data can be decoded by [`decode_revert_data`](crate::decode_revert_data).
+    pub fn set_custom_errors(mut self, custom_errors: bool) -> Self {
+        self.custom_errors = custom_errors;
+        self
+    }
+
+    /// Set whether `verifyProof` and `verifyProofs` return `false` on invalid proof, instead of
+    /// reverting. Default is `false`.
+    ///
+    /// When enabled, calldata not encoded in the layout expected by the verifier is also treated
+    /// as invalid proof, and custom errors are not used, see
+    /// [`SolidityGenerator::set_custom_errors`].
+    pub fn set_non_reverting(mut self, non_reverting: bool) -> Self {
+        self.non_reverting = non_reverting;
+        self
+    }
+
+    /// Set whether points in proof are compressed into x coordinate with parity of y coordinate in
+    /// the most significant bit, which takes 32 bytes instead of 64 bytes. Default is `false`.
+    ///
+    /// Proof should be created by [`EvmTranscript`](crate::EvmTranscript) with
+    /// [`EvmTranscript::set_compressed_points`](crate::EvmTranscript::set_compressed_points)
+    /// set to the same, and the verifier decompresses points by `modexp` precompile, which costs
+    /// more gas for execution but less for calldata.
+    pub fn set_compressed_points(mut self, compressed_points: bool) -> Self {
+        self.compressed_points = compressed_points;
+        self
+    }
+
+    /// Set [`EvmTranscriptSpec`] of the [`EvmTranscript`](crate::EvmTranscript) proof is created
+    /// with. Default is [`Keccak256Spec`].
+    ///
+    /// The verifier squeezes challenges by [`EvmTranscriptSpec::yul_hash`], so with
+    /// [`Sha256Spec`](crate::Sha256Spec), proof should be created by
+    /// [`Sha256Transcript`](crate::Sha256Transcript), and the verifier calls `sha256` precompile
+    /// for each squeeze instead of `keccak256`.
+    pub fn set_transcript_hash<S: EvmTranscriptSpec>(mut self) -> Self {
+        self.transcript = Transcript::evm::<S>();
+        self
+    }
+
+    /// Set `TranscriptHash` of the transcript proof is created with, instead of
+    /// [`SolidityGenerator::set_transcript_hash`].
+    ///
+    /// With [`TranscriptHash::Blake2b`], proof created by `halo2`'s own `Blake2bWrite` can be
+    /// verified without creating it again for EVM, which costs more gas for execution since
+    /// `blake2f` precompile is called for every 128 bytes absorbed.
+    ///
+    /// With `TranscriptHash::Poseidon` of feature `poseidon`, proof should be created by
+    /// `PoseidonTranscript`, and the verifier computes Poseidon
+    /// permutation in Yul, which costs much more gas but keeps the same proof format for
+    /// aggregation circuit.
+    ///
+    /// Points in proof are always compressed with `TranscriptHash`, so
+    /// [`SolidityGenerator::set_compressed_points`] is ignored.
+    pub fn set_little_endian_transcript_hash(mut self, transcript_hash: TranscriptHash) -> Self {
+        self.transcript = Transcript::LittleEndian(transcript_hash);
+        self
+    }
+
+    /// Set the maximum runtime code size in bytes each contract rendered by
+    /// [`SolidityGenerator::render_split`] should fit in. Default is 24,576 by EIP-170.
+    pub fn set_max_code_size(mut self, max_code_size: usize) -> Self {
+        self.max_code_size = max_code_size;
         self
     }
 }
@@ -164,6 +697,75 @@ impl<'a> SolidityGenerator<'a> {
         Ok((verifier_output, vk_output))
     }
 
+    /// Render `Halo2Verifier.sol` with verifying key embedded, and helper contracts it delegates
+    /// computations to, and return them as `String`.
+    ///
+    /// When the estimated runtime code size of `Halo2Verifier` exceeds the maximum code size (see
+    /// [`SolidityGenerator::set_max_code_size`]), the quotient evaluation is moved into helpers,
+    /// then the PCS computations too if it still exceeds. Otherwise the helpers are empty and the
+    /// verifier is the same as [`SolidityGenerator::render`].
+    ///
+    /// The helpers should be deployed first, then `Halo2Verifier` with their addresses in the same
+    /// order as constructor argument.
+    ///
+    /// Code sizes are estimated without compiling, so a safety margin is added to them before
+    /// comparing with the maximum code size. Returns [`GeneratorError::CodeSizeTooLarge`] if any
+    /// contract still exceeds it after splitting.
+    pub fn render_split(&self) -> Result<(String, Vec<String>), GeneratorError> {
+        let (verifier, helpers) = self.generate_split(false)?;
+        self.check_split(&verifier, &helpers)?;
+        let mut verifier_output = String::new();
+        verifier.render(&mut verifier_output)?;
+        Ok((verifier_output, render_helpers(&helpers)?))
+    }
+
+    /// Render `Halo2Verifier.sol`, helper contracts it delegates computations to, and
+    /// `Halo2VerifyingKey.sol`, and return them as `String`.
+    ///
+    /// See [`SolidityGenerator::render_split`] for when and how the verifier is split.
+    pub fn render_split_separately(&self) -> Result<(String, Vec<String>, String), GeneratorError> {
+        let (verifier, helpers) = self.generate_split(true)?;
+        self.check_split(&verifier, &helpers)?;
+        let mut verifier_output = String::new();
+        let mut vk_output = String::new();
+        verifier.render(&mut verifier_output)?;
+        self.generate_vk().render(&mut vk_output)?;
+        Ok((verifier_output, render_helpers(&helpers)?, vk_output))
+    }
+
+    /// Render `IHalo2Verifier.sol`, the interface of `Halo2Verifier.sol` rendered by
+    /// [`SolidityGenerator::render`], and return it as `String`.
+    pub fn render_interface(&self) -> Result<String, fmt::Error> {
+        let mut output = String::new();
+        self.generate_interface(false).render(&mut output)?;
+        Ok(output)
+    }
+
+    /// Render `IHalo2Verifier.sol`, the interface of `Halo2Verifier.sol` rendered by
+    /// [`SolidityGenerator::render_separately`], and return it as `String`.
+    pub fn render_interface_separately(&self) -> Result<String, fmt::Error> {
+        let mut output = String::new();
+        self.generate_interface(true).render(&mut output)?;
+        Ok(output)
+    }
+
+    /// Return ABI JSON of `Halo2Verifier.sol` rendered by [`SolidityGenerator::render`].
+    pub fn render_abi(&self) -> String {
+        abi::abi_json(
+            &abi::functions(false, self.public_input_hash.is_some()),
+            &self.custom_errors(),
+        )
+    }
+
+    /// Return ABI JSON of `Halo2Verifier.sol` rendered by
+    /// [`SolidityGenerator::render_separately`].
+    pub fn render_abi_separately(&self) -> String {
+        abi::abi_json(
+            &abi::functions(true, self.public_input_hash.is_some()),
+            &self.custom_errors(),
+        )
+    }
+
     fn generate_vk(&self) -> Halo2VerifyingKey {
         let constants = {
             let domain = self.vk.get_domain();
@@ -173,10 +775,10 @@ impl<'a> SolidityGenerator<'a> {
             let omega = fr_to_u256(domain.get_omega());
             let omega_inv = fr_to_u256(domain.get_omega_inv());
             let omega_inv_to_l = {
-                let l = self.meta.rotation_last.unsigned_abs() as u64;
+                let l = self.meta.num_neg_lagranges() as u64;
                 fr_to_u256(domain.get_omega_inv().pow_vartime([l]))
             };
-            let num_instances = U256::from(self.num_instances);
+            let num_instances = U256::from(self.num_instances.iter().sum::<usize>());
             let has_accumulator = U256::from(self.acc_encoding.is_some());
             let acc_offset = self
                 .acc_encoding
@@ -217,6 +819,17 @@ impl<'a> SolidityGenerator<'a> {
                 ("neg_s_g2_y_1", neg_s_g2[2]),
                 ("neg_s_g2_y_2", neg_s_g2[3]),
             ]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .chain(
+                self.num_instances
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, num_instances)| {
+                        (format!("num_instances_{idx}"), U256::from(*num_instances))
+                    }),
+            )
+            .collect()
         };
         let fixed_comms = chain![self.vk.fixed_commitments()]
             .flat_map(g1_to_u256s)
@@ -234,18 +847,29 @@ impl<'a> SolidityGenerator<'a> {
     }
 
     fn generate_verifier(&self, separate: bool) -> Halo2Verifier {
-        let proof_cptr = Ptr::calldata(if separate { 0x84 } else { 0x64 });
+        // Function signature and head of arguments, followed by length of proof
+        let num_args = 2 + usize::from(separate) + usize::from(self.public_input_hash.is_some());
+        let proof_cptr = Ptr::calldata(0x04 + 0x20 * (num_args + 1));
 
         let vk = self.generate_vk();
         let vk_len = vk.len();
         let vk_mptr = Ptr::memory(self.estimate_static_working_memory_size(&vk, proof_cptr));
-        let data = Data::new(&self.meta, &vk, vk_mptr, proof_cptr);
+        let data = Data::new(
+            &self.meta,
+            &vk,
+            vk_mptr,
+            proof_cptr,
+            self.scheme,
+            self.proof_encoding(),
+        );
 
         let evaluator = Evaluator::new(self.vk.cs(), &self.meta, &data);
+        let quotient_eval_shared_computations = evaluator.shared_computations();
         let quotient_eval_numer_computations = chain![
             evaluator.gate_computations(),
             evaluator.permutation_computations(),
-            evaluator.lookup_computations()
+            evaluator.lookup_computations(),
+            evaluator.shuffle_computations()
         ]
         .enumerate()
         .map(|(idx, (mut lines, var))| {
@@ -261,31 +885,181 @@ impl<'a> SolidityGenerator<'a> {
         })
         .collect();
 
+        let instance_computations = evaluator.instance_computations();
+
         let pcs_computations = match self.scheme {
             Bdfg21 => bdfg21_computations(&self.meta, &data),
-            Gwc19 => unimplemented!(),
+            Gwc19 => gwc19_computations(&self.meta, &data),
         };
 
         Halo2Verifier {
             scheme: self.scheme,
+            custom_errors: !self.custom_errors().is_empty(),
+            errors: self.custom_errors(),
+            non_reverting: self.non_reverting,
             vk: (!separate).then_some(vk),
             vk_len,
             vk_mptr,
-            num_neg_lagranges: self.meta.rotation_last.unsigned_abs() as usize,
+            num_neg_lagranges: self.meta.num_neg_lagranges(),
+            num_trailing_lagranges: self.meta.num_trailing_lagranges(),
+            num_blinding_factors: self.meta.num_blinding_factors(),
             num_advices: self.meta.num_advices(),
             num_challenges: self.meta.num_challenges(),
+            num_instance_columns: self.meta.num_instance_columns,
+            num_instance_evals: self.meta.instance_queries.len(),
             num_evals: self.meta.num_evals,
             num_quotients: self.meta.num_quotients,
+            num_rotations: self.meta.num_rotations(),
+            compressed_points: self.proof_encoding().compressed_points(),
+            transcript: self.transcript,
             proof_cptr,
             quotient_comm_cptr: data.quotient_comm_cptr,
-            proof_len: self.meta.proof_len(self.scheme),
+            eval_cptr: data.eval_cptr,
+            proof_len: self.proof_len(),
             challenge_mptr: data.challenge_mptr,
+            ec_point_mptr: data.ec_point_mptr,
             theta_mptr: data.theta_mptr,
+            num_instances: self.num_instances.iter().sum(),
+            public_input_hash: self.public_input_hash,
+            instance_computations,
+            quotient_eval_shared_computations,
             quotient_eval_numer_computations,
             pcs_computations,
+            num_quotient_helpers: 0,
+            pcs_helper: false,
         }
     }
 
+    pub(crate) fn generate_split(
+        &self,
+        separate: bool,
+    ) -> Result<(Halo2Verifier, Vec<Halo2VerifierHelper>), fmt::Error> {
+        let fits = |verifier: &Halo2Verifier| -> Result<bool, fmt::Error> {
+            let mut output = String::new();
+            verifier.render(&mut output)?;
+            Ok(fits_in(estimate_code_size(&output), self.max_code_size))
+        };
+
+        let mut verifier = self.generate_verifier(separate);
+        if fits(&verifier)? {
+            return Ok((verifier, Vec::new()));
+        }
+
+        // Move quotient evaluation into helpers, each takes as many code blocks as it can fit
+        let quotient_eval_numer_computations =
+            mem::take(&mut verifier.quotient_eval_numer_computations);
+        let helper_base_size = {
+            let helper = Halo2VerifierHelper::new(
+                &verifier,
+                "Halo2VerifierQuotientHelper".to_string(),
+                HelperKind::Quotient,
+                Vec::new(),
+            );
+            let mut output = String::new();
+            helper.render(&mut output)?;
+            estimate_code_size(&output)
+        };
+        let mut chunks = Vec::<Vec<Vec<String>>>::new();
+        let mut chunk_size = 0;
+        for code_block in quotient_eval_numer_computations {
+            let size = estimate_yul_code_size(code_block.iter().map(String::as_str));
+            match chunks.last_mut() {
+                Some(chunk)
+                    if fits_in(helper_base_size + chunk_size + size, self.max_code_size) =>
+                {
+                    chunk.push(code_block);
+                    chunk_size += size;
+                }
+                _ => {
+                    chunks.push(vec![code_block]);
+                    chunk_size = size;
+                }
+            }
+        }
+        verifier.num_quotient_helpers = chunks.len();
+        let mut helpers = chunks
+            .into_iter()
+            .enumerate()
+            .map(|(idx, chunk)| {
+                let name = format!("Halo2VerifierQuotientHelper{idx}");
+                Halo2VerifierHelper::new(&verifier, name, HelperKind::Quotient, chunk)
+            })
+            .collect_vec();
+
+        // Move PCS computations into a helper if verifier still doesn't fit
+        if !fits(&verifier)? {
+            let pcs_computations = mem::take(&mut verifier.pcs_computations);
+            verifier.pcs_helper = true;
+            helpers.push(Halo2VerifierHelper::new(
+                &verifier,
+                "Halo2VerifierPcsHelper".to_string(),
+                HelperKind::Pcs,
+                pcs_computations,
+            ));
+        }
+
+        Ok((verifier, helpers))
+    }
+
+    /// Return [`GeneratorError::CodeSizeTooLarge`] if any contract split by
+    /// [`SolidityGenerator::generate_split`] still doesn't fit, which happens when a single code
+    /// block or the rest of verifier is too large.
+    fn check_split(
+        &self,
+        verifier: &Halo2Verifier,
+        helpers: &[Halo2VerifierHelper],
+    ) -> Result<(), GeneratorError> {
+        let mut verifier_output = String::new();
+        verifier.render(&mut verifier_output)?;
+        let outputs = chain![
+            [("Halo2Verifier".to_string(), verifier_output)],
+            helpers
+                .iter()
+                .map(|helper| helper.name.clone())
+                .zip(render_helpers(helpers)?)
+        ];
+        for (contract, output) in outputs {
+            let size = estimate_code_size(&output);
+            if !fits_in(size, self.max_code_size) {
+                return Err(GeneratorError::CodeSizeTooLarge {
+                    contract,
+                    size,
+                    max_code_size: self.max_code_size,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn generate_interface(&self, separate: bool) -> IHalo2Verifier {
+        IHalo2Verifier {
+            functions: abi::functions(separate, self.public_input_hash.is_some()).to_vec(),
+            errors: self.custom_errors(),
+        }
+    }
+
+    fn custom_errors(&self) -> Vec<CustomError> {
+        if self.custom_errors && !self.non_reverting {
+            chain![
+                CUSTOM_ERRORS,
+                self.public_input_hash
+                    .map(|_| abi::INVALID_PUBLIC_INPUT_HASH)
+            ]
+            .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub(crate) fn proof_encoding(&self) -> ProofEncoding {
+        ProofEncoding::new(self.transcript, self.compressed_points)
+    }
+
+    pub(crate) fn proof_len(&self) -> usize {
+        self.meta
+            .proof_len(self.scheme, self.proof_encoding().compressed_points())
+    }
+
     fn estimate_static_working_memory_size(
         &self,
         vk: &Halo2VerifyingKey,
@@ -294,12 +1068,23 @@ impl<'a> SolidityGenerator<'a> {
         let pcs_computation = match self.scheme {
             Bdfg21 => {
                 let mock_vk_mptr = Ptr::memory(0x100000);
-                let mock = Data::new(&self.meta, vk, mock_vk_mptr, proof_cptr);
+                let mock = Data::new(
+                    &self.meta,
+                    vk,
+                    mock_vk_mptr,
+                    proof_cptr,
+                    self.scheme,
+                    self.proof_encoding(),
+                );
                 let (superset, sets) = rotation_sets(&queries(&self.meta, &mock));
                 let num_coeffs = sets.iter().map(|set| set.rots().len()).sum::<usize>();
                 2 * (1 + num_coeffs) + 6 + 2 * superset.len() + 1 + 3 * sets.len()
             }
-            Gwc19 => unimplemented!(),
+            Gwc19 => {
+                let num_rotations = self.meta.num_rotations();
+                // Hashing W's, and evaluation points stored after scratch space of EC operations
+                (2 * num_rotations + 1).max(8 + num_rotations)
+            }
         };
 
         itertools::max(chain![
@@ -307,16 +1092,125 @@ impl<'a> SolidityGenerator<'a> {
             chain![self.meta.num_advices().into_iter()].map(|n| n * 2 + 1),
             // Hashing evaluations
             [self.meta.num_evals + 1],
+            // Values shared by quotient evaluation
+            [num_shared_slots(self.vk.cs(), &self.meta) + 1],
             // PCS computation
             [pcs_computation],
             // Pairing
             [12],
+            // State and pending input of blake2b in memory[0x00..0x240], or state of poseidon in
+            // memory[0x00..0xa0], see `Halo2Verifier.sol`
+            self.transcript
+                .little_endian_hash()
+                .map(|transcript_hash| match transcript_hash {
+                    TranscriptHash::Blake2b => 18,
+                    #[cfg(feature = "poseidon")]
+                    TranscriptHash::Poseidon => 5,
+                }),
         ])
         .unwrap()
             * 0x20
     }
 }
 
+impl<'a> SolidityMultiGenerator<'a> {
+    /// Return a new `SolidityMultiGenerator` with `SolidityGenerator` of each circuit.
+    ///
+    /// # Panics
+    /// Panics if [`SolidityMultiGenerator::try_new`] returns `GeneratorError`.
+    pub fn new(generators: Vec<SolidityGenerator<'a>>) -> Self {
+        Self::try_new(generators).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Return a new `SolidityMultiGenerator` with `SolidityGenerator` of each circuit, or
+    /// `GeneratorError` if `generators` is empty, or generators don't share the same `g2` and
+    /// `s_g2`, or any generator has [`PublicInputHash`], custom errors or non-reverting set, which
+    /// are not supported yet.
+    ///
+    /// The pair `(g2, s_g2)` is the whole compatibility contract between generators, since it's
+    /// all `Halo2MultiVerifier` pairs the accumulated pairing input of every `Halo2Verifier`
+    /// with, while each `Halo2Verifier` computes its pairing input with its own `g1`.
+    pub fn try_new(generators: Vec<SolidityGenerator<'a>>) -> Result<Self, GeneratorError> {
+        let params = generators
+            .first()
+            .ok_or(GeneratorError::NoGenerator)?
+            .params;
+        for (index, generator) in generators.iter().enumerate() {
+            if generator.public_input_hash.is_some() {
+                return Err(GeneratorError::MultiPublicInputHash { index });
+            }
+            if generator.custom_errors {
+                return Err(GeneratorError::MultiCustomErrors { index });
+            }
+            if generator.non_reverting {
+                return Err(GeneratorError::MultiNonReverting { index });
+            }
+            if (generator.params.g2(), generator.params.s_g2()) != (params.g2(), params.s_g2()) {
+                return Err(GeneratorError::ParamsMismatch { index });
+            }
+        }
+        Ok(Self { generators })
+    }
+
+    /// Render `Halo2Verifier.sol` of each circuit with verifying key embedded into writers, and
+    /// `Halo2MultiVerifier.sol` into `multi_verifier_writer`.
+    ///
+    /// The `Halo2MultiVerifier` should be deployed with addresses of each `Halo2Verifier` in the
+    /// same order as given generators, then its `verifyProofs` takes proofs and instances in the
+    /// same order.
+    ///
+    /// Returns `GeneratorError` if number of `verifier_writers` is not the number of generators,
+    /// or failed to render.
+    pub fn render_into(
+        &self,
+        verifier_writers: &mut [impl fmt::Write],
+        multi_verifier_writer: &mut impl fmt::Write,
+    ) -> Result<(), GeneratorError> {
+        if verifier_writers.len() != self.generators.len() {
+            return Err(GeneratorError::WriterMismatch {
+                num_generators: self.generators.len(),
+                num_writers: verifier_writers.len(),
+            });
+        }
+        for (generator, verifier_writer) in self.generators.iter().zip(verifier_writers) {
+            generator.render_into(verifier_writer)?;
+        }
+        self.generate_multi_verifier()
+            .render(multi_verifier_writer)?;
+        Ok(())
+    }
+
+    /// Render `Halo2Verifier.sol` of each circuit with verifying key embedded, and
+    /// `Halo2MultiVerifier.sol`, and return them as `String`.
+    pub fn render(&self) -> Result<(Vec<String>, String), GeneratorError> {
+        let mut verifier_outputs = vec![String::new(); self.generators.len()];
+        let mut multi_verifier_output = String::new();
+        self.render_into(&mut verifier_outputs, &mut multi_verifier_output)?;
+        Ok((verifier_outputs, multi_verifier_output))
+    }
+
+    fn generate_multi_verifier(&self) -> Halo2MultiVerifier {
+        let params = self.generators[0].params;
+        Halo2MultiVerifier {
+            num_verifiers: self.generators.len(),
+            fn_sig_pairing_input: u32::from_be_bytes(abi::PAIRING_INPUT.selector()),
+            g2: g2_to_u256s(params.g2()),
+            neg_s_g2: g2_to_u256s(-params.s_g2()),
+        }
+    }
+}
+
+fn render_helpers(helpers: &[Halo2VerifierHelper]) -> Result<Vec<String>, fmt::Error> {
+    helpers
+        .iter()
+        .map(|helper| {
+            let mut helper_output = String::new();
+            helper.render(&mut helper_output)?;
+            Ok(helper_output)
+        })
+        .collect()
+}
+
 // Remove when `vk.transcript_repr()` is ready for usage.
 fn vk_transcript_repr(vk: &VerifyingKey<bn256::G1Affine>) -> bn256::Fr {
     use blake2b_simd::Params;
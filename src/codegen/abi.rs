@@ -0,0 +1,262 @@
+use itertools::Itertools;
+use ruint::aliases::U256;
+use sha3::{Digest, Keccak256};
+
+const PROOF: Param = Param::new("bytes", "proof");
+const INSTANCES: Param = Param::new("uint256[]", "instances");
+const PROOFS: Param = Param::new("bytes[]", "proofs");
+const INSTANCES_BATCH: Param = Param::new("uint256[][]", "instances");
+const VK: Param = Param::new("address", "vk");
+const PUBLIC_DATA: Param = Param::new("bytes", "publicData");
+const PUBLIC_DATA_BATCH: Param = Param::new("bytes[]", "publicData");
+const OFFSET: Param = Param::new("uint256", "offset");
+const PRECOMPILE: Param = Param::new("uint8", "precompile");
+const BOOL: Param = Param::new("bool", "");
+const PAIRING_INPUT_OUTPUT: Param = Param::new("uint256[4]", "");
+
+pub(crate) const VERIFY_PROOF: Function =
+    Function::new("verifyProof", &[PROOF, INSTANCES], &[BOOL]);
+pub(crate) const VERIFY_PROOF_WITH_VK_ADDRESS: Function =
+    Function::new("verifyProof", &[VK, PROOF, INSTANCES], &[BOOL]);
+pub(crate) const PAIRING_INPUT: Function =
+    Function::new("pairingInput", &[PROOF, INSTANCES], &[PAIRING_INPUT_OUTPUT]);
+pub(crate) const PAIRING_INPUT_WITH_VK_ADDRESS: Function = Function::new(
+    "pairingInput",
+    &[VK, PROOF, INSTANCES],
+    &[PAIRING_INPUT_OUTPUT],
+);
+pub(crate) const VERIFY_PROOFS: Function =
+    Function::new("verifyProofs", &[PROOFS, INSTANCES_BATCH], &[BOOL]);
+pub(crate) const VERIFY_PROOFS_WITH_VK_ADDRESS: Function =
+    Function::new("verifyProofs", &[VK, PROOFS, INSTANCES_BATCH], &[BOOL]);
+
+pub(crate) const VERIFY_PROOF_WITH_PUBLIC_DATA: Function =
+    Function::new("verifyProof", &[PROOF, INSTANCES, PUBLIC_DATA], &[BOOL]);
+pub(crate) const VERIFY_PROOF_WITH_VK_ADDRESS_AND_PUBLIC_DATA: Function =
+    Function::new("verifyProof", &[VK, PROOF, INSTANCES, PUBLIC_DATA], &[BOOL]);
+pub(crate) const PAIRING_INPUT_WITH_PUBLIC_DATA: Function = Function::new(
+    "pairingInput",
+    &[PROOF, INSTANCES, PUBLIC_DATA],
+    &[PAIRING_INPUT_OUTPUT],
+);
+pub(crate) const PAIRING_INPUT_WITH_VK_ADDRESS_AND_PUBLIC_DATA: Function = Function::new(
+    "pairingInput",
+    &[VK, PROOF, INSTANCES, PUBLIC_DATA],
+    &[PAIRING_INPUT_OUTPUT],
+);
+pub(crate) const VERIFY_PROOFS_WITH_PUBLIC_DATA: Function = Function::new(
+    "verifyProofs",
+    &[PROOFS, INSTANCES_BATCH, PUBLIC_DATA_BATCH],
+    &[BOOL],
+);
+pub(crate) const VERIFY_PROOFS_WITH_VK_ADDRESS_AND_PUBLIC_DATA: Function = Function::new(
+    "verifyProofs",
+    &[VK, PROOFS, INSTANCES_BATCH, PUBLIC_DATA_BATCH],
+    &[BOOL],
+);
+
+pub(crate) const INVALID_PROOF_LENGTH: CustomError = CustomError::new("InvalidProofLength", &[]);
+pub(crate) const INVALID_INSTANCES_LENGTH: CustomError =
+    CustomError::new("InvalidInstancesLength", &[]);
+pub(crate) const INVALID_EC_POINT: CustomError = CustomError::new("InvalidEcPoint", &[OFFSET]);
+pub(crate) const INVALID_SCALAR: CustomError = CustomError::new("InvalidScalar", &[OFFSET]);
+pub(crate) const INVALID_ACCUMULATOR: CustomError = CustomError::new("InvalidAccumulator", &[]);
+pub(crate) const PRECOMPILE_FAILED: CustomError =
+    CustomError::new("PrecompileFailed", &[PRECOMPILE]);
+pub(crate) const PAIRING_FAILED: CustomError = CustomError::new("PairingFailed", &[]);
+pub(crate) const INVALID_PUBLIC_INPUT_HASH: CustomError =
+    CustomError::new("InvalidPublicInputHash", &[]);
+
+pub(crate) const CUSTOM_ERRORS: [CustomError; 7] = [
+    INVALID_PROOF_LENGTH,
+    INVALID_INSTANCES_LENGTH,
+    INVALID_EC_POINT,
+    INVALID_SCALAR,
+    INVALID_ACCUMULATOR,
+    PRECOMPILE_FAILED,
+    PAIRING_FAILED,
+];
+
+/// Return functions of `Halo2Verifier`, with `address vk` as the first argument if `separate`,
+/// and public data as the last argument if `public_data`.
+pub(crate) fn functions(separate: bool, public_data: bool) -> [Function; 3] {
+    match (separate, public_data) {
+        (false, false) => [VERIFY_PROOF, PAIRING_INPUT, VERIFY_PROOFS],
+        (true, false) => [
+            VERIFY_PROOF_WITH_VK_ADDRESS,
+            PAIRING_INPUT_WITH_VK_ADDRESS,
+            VERIFY_PROOFS_WITH_VK_ADDRESS,
+        ],
+        (false, true) => [
+            VERIFY_PROOF_WITH_PUBLIC_DATA,
+            PAIRING_INPUT_WITH_PUBLIC_DATA,
+            VERIFY_PROOFS_WITH_PUBLIC_DATA,
+        ],
+        (true, true) => [
+            VERIFY_PROOF_WITH_VK_ADDRESS_AND_PUBLIC_DATA,
+            PAIRING_INPUT_WITH_VK_ADDRESS_AND_PUBLIC_DATA,
+            VERIFY_PROOFS_WITH_VK_ADDRESS_AND_PUBLIC_DATA,
+        ],
+    }
+}
+
+/// Return ABI JSON of given functions and custom errors.
+pub(crate) fn abi_json(functions: &[Function], errors: &[CustomError]) -> String {
+    let entries = functions
+        .iter()
+        .map(Function::abi_json)
+        .chain(errors.iter().map(CustomError::abi_json))
+        .map(|entry| format!("  {entry}"))
+        .join(",\n");
+    format!("[\n{entries}\n]\n")
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Param {
+    ty: &'static str,
+    name: &'static str,
+}
+
+impl Param {
+    const fn new(ty: &'static str, name: &'static str) -> Self {
+        Self { ty, name }
+    }
+
+    fn is_dynamic(&self) -> bool {
+        self.ty == "bytes" || self.ty == "string" || self.ty.ends_with(']')
+    }
+
+    fn declaration(&self, location: Option<&str>) -> String {
+        let location = match location {
+            Some(location) if self.is_dynamic() => format!(" {location}"),
+            _ => String::new(),
+        };
+        let name = if self.name.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", self.name)
+        };
+        format!("{}{location}{name}", self.ty)
+    }
+
+    fn abi_json(&self) -> String {
+        format!(
+            r#"{{"name":"{}","type":"{}","internalType":"{}"}}"#,
+            self.name, self.ty, self.ty
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Function {
+    name: &'static str,
+    inputs: &'static [Param],
+    outputs: &'static [Param],
+}
+
+impl Function {
+    const fn new(name: &'static str, inputs: &'static [Param], outputs: &'static [Param]) -> Self {
+        Self {
+            name,
+            inputs,
+            outputs,
+        }
+    }
+
+    pub(crate) fn selector(&self) -> [u8; 4] {
+        selector(&signature(self.name, self.inputs))
+    }
+
+    /// Return header of function without body, which is shared by `IHalo2Verifier.sol` and
+    /// `Halo2Verifier.sol`, so they can't disagree.
+    pub(crate) fn header(&self) -> String {
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|input| input.declaration(Some("calldata")));
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|output| output.declaration(Some("memory")));
+        format!(
+            "function {}({}) external view returns ({})",
+            self.name,
+            inputs.format(", "),
+            outputs.format(", ")
+        )
+    }
+
+    pub(crate) fn declaration(&self) -> String {
+        format!("{};", self.header())
+    }
+
+    fn abi_json(&self) -> String {
+        format!(
+            r#"{{"type":"function","name":"{}","inputs":[{}],"outputs":[{}],"stateMutability":"view"}}"#,
+            self.name,
+            self.inputs.iter().map(Param::abi_json).format(","),
+            self.outputs.iter().map(Param::abi_json).format(",")
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct CustomError {
+    name: &'static str,
+    inputs: &'static [Param],
+}
+
+impl CustomError {
+    const fn new(name: &'static str, inputs: &'static [Param]) -> Self {
+        Self { name, inputs }
+    }
+
+    pub(crate) fn selector(&self) -> [u8; 4] {
+        selector(&signature(self.name, self.inputs))
+    }
+
+    /// Return selector left-aligned in a word.
+    pub(crate) fn sig(&self) -> U256 {
+        U256::from(u32::from_be_bytes(self.selector())) << 224
+    }
+
+    pub(crate) fn declaration(&self) -> String {
+        let inputs = self.inputs.iter().map(|input| input.declaration(None));
+        format!("error {}({});", self.name, inputs.format(", "))
+    }
+
+    /// Return name of constant holding the left-aligned selector in `Halo2Verifier.sol`, e.g.
+    /// `INVALID_EC_POINT_SIG` for `InvalidEcPoint`.
+    pub(crate) fn sig_name(&self) -> String {
+        let mut sig_name = String::new();
+        for char in self.name.chars() {
+            if char.is_ascii_uppercase() && !sig_name.is_empty() {
+                sig_name.push('_');
+            }
+            sig_name.push(char.to_ascii_uppercase());
+        }
+        sig_name + "_SIG"
+    }
+
+    fn abi_json(&self) -> String {
+        format!(
+            r#"{{"type":"error","name":"{}","inputs":[{}]}}"#,
+            self.name,
+            self.inputs.iter().map(Param::abi_json).format(",")
+        )
+    }
+}
+
+/// Return canonical signature `name(type_0,type_1,...)`.
+fn signature(name: &str, inputs: &[Param]) -> String {
+    format!(
+        "{name}({})",
+        inputs.iter().map(|input| input.ty).format(",")
+    )
+}
+
+/// Return the first 4 bytes of keccak256 of canonical signature.
+fn selector(signature: &str) -> [u8; 4] {
+    let hash = Keccak256::digest(signature);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
@@ -0,0 +1,577 @@
+use crate::codegen::{
+    gas::{CALLDATA_NON_ZERO_BYTE_GAS, TX_BASE_GAS},
+    render_helpers,
+    template::HelperKind,
+    SolidityGenerator,
+};
+#[cfg(any(test, feature = "evm"))]
+use crate::evm::test::try_compile_solidity;
+use itertools::chain;
+use ruint::aliases::U256;
+use std::{
+    array,
+    fmt::{self, Display},
+};
+
+/// Maximum runtime code size of a contract by EIP-170.
+pub(crate) const MAX_CODE_SIZE: usize = 0x6000;
+
+/// Maximum creation code size of a contract by EIP-3860, which is twice of the maximum runtime
+/// code size.
+const MAX_INITCODE_SIZE_FACTOR: usize = 2;
+
+/// Estimated code size of function dispatcher and ABI decoding generated by compiler, which are
+/// outside of inline assembly.
+const SOLIDITY_OVERHEAD: usize = 0x800;
+
+/// Estimated code size of Yul function call, which pushes return label and function label, then
+/// jumps to function and back.
+const FUNCTION_CALL_SIZE: usize = 8;
+
+/// Estimated code size of Yul function definition excluding its body, which rearranges stack to
+/// return.
+const FUNCTION_DEFINITION_SIZE: usize = 8;
+
+/// Estimated code size of constructor generated by compiler, which checks call value then copies
+/// runtime code into memory and returns it.
+const CONSTRUCTOR_SIZE: usize = 0x20;
+
+/// Safety margin in percent added to estimated code size before comparing it with code size limit,
+/// since the estimate could be smaller than the size compiled by `solc`.
+const CODE_SIZE_MARGIN_PERCENT: usize = 20;
+
+const CREATE_GAS: u64 = 32000;
+const INITCODE_WORD_GAS: u64 = 2;
+const CODE_DEPOSIT_BYTE_GAS: u64 = 200;
+
+/// Section of `Halo2Verifier.sol`, starting from the line of the comment in the template.
+const SECTIONS: [(&str, Section); 11] = [
+    ("// Load vk into memory", Section::VkLoading),
+    ("// Copy vk into memory", Section::VkLoading),
+    ("// Check valid length of proof", Section::Transcript),
+    (
+        "// Return earlier if anything from calldata is invalid",
+        Section::Other,
+    ),
+    (
+        "// Revert earlier if anything from calldata is invalid",
+        Section::Other,
+    ),
+    (
+        "// Compute lagrange evaluations and instance evaluations",
+        Section::Quotient,
+    ),
+    ("// Compute pairing lhs and rhs", Section::Pcs),
+    (
+        "// Return pairing input if only pairing input is requested",
+        Section::Other,
+    ),
+    ("// Perform pairing", Section::Pcs),
+    ("// Return 0 as result if anything fails", Section::Other),
+    ("// Revert if anything fails", Section::Other),
+];
+
+const BUILTINS: [&str; 36] = [
+    "add",
+    "sub",
+    "mul",
+    "div",
+    "mod",
+    "addmod",
+    "mulmod",
+    "exp",
+    "lt",
+    "gt",
+    "eq",
+    "iszero",
+    "and",
+    "or",
+    "xor",
+    "not",
+    "shl",
+    "shr",
+    "byte",
+    "keccak256",
+    "pop",
+    "mload",
+    "mstore",
+    "mstore8",
+    "calldataload",
+    "calldatasize",
+    "calldatacopy",
+    "returndatasize",
+    "returndatacopy",
+    "extcodecopy",
+    "staticcall",
+    "gas",
+    "address",
+    "return",
+    "revert",
+    "invalid",
+];
+
+/// Return estimated runtime code size of Solidity source rendered by templates.
+///
+/// Only inline assembly is estimated line by line, and the rest is assumed to take a constant
+/// size.
+pub(crate) fn estimate_code_size(source: &str) -> usize {
+    SOLIDITY_OVERHEAD + estimate_yul_code_size(assembly_blocks(source).into_iter().flatten())
+}
+
+/// Return whether estimated code size fits in the limit after adding the safety margin.
+pub(crate) fn fits_in(size: usize, limit: usize) -> bool {
+    size + size * CODE_SIZE_MARGIN_PERCENT / 100 <= limit
+}
+
+/// Return estimated runtime code size of `Halo2Verifier.sol` split by section.
+fn estimate_verifier_code_size(source: &str) -> CodeSizeSections {
+    let mut sections = CodeSizeSections {
+        other: SOLIDITY_OVERHEAD,
+        ..Default::default()
+    };
+    for block in assembly_blocks(source) {
+        let mut section = Section::Other;
+        for line in block {
+            if let Some((_, next)) = SECTIONS.iter().find(|(comment, _)| line.trim() == *comment) {
+                section = *next;
+            }
+            let size = estimate_yul_code_size([line]);
+            match section {
+                Section::VkLoading => sections.vk_loading += size,
+                Section::Transcript => sections.transcript += size,
+                Section::Quotient => sections.quotient += size,
+                Section::Pcs => sections.pcs += size,
+                Section::Other => sections.other += size,
+            }
+        }
+    }
+    sections
+}
+
+/// Return lines inside each inline assembly block of Solidity source, excluding the braces of
+/// the block.
+fn assembly_blocks(source: &str) -> Vec<Vec<&str>> {
+    let mut blocks = Vec::<Vec<_>>::new();
+    let mut depth = 0;
+    for line in source.lines() {
+        let code = strip_comment(line);
+        let num_opens = code.matches('{').count();
+        let num_closes = code.matches('}').count();
+        if depth > 0 {
+            depth = (depth + num_opens).saturating_sub(num_closes);
+            if depth > 0 {
+                blocks.last_mut().unwrap().push(line);
+            }
+        } else if code.trim_start().starts_with("assembly") {
+            depth = num_opens.saturating_sub(num_closes);
+            blocks.push(Vec::new());
+        }
+    }
+    blocks
+}
+
+/// Return estimated code size of Yul lines.
+///
+/// Each builtin is assumed to compile to 1 opcode, each literal to a `PUSH` with minimal bytes,
+/// each constant to a `PUSH2`, and each variable access or assignment to `DUP` and `SWAP`.
+pub(crate) fn estimate_yul_code_size<'a>(lines: impl IntoIterator<Item = &'a str>) -> usize {
+    lines
+        .into_iter()
+        .flat_map(yul_tokens)
+        .map(|token| match token {
+            YulToken::Builtin(_) => 1,
+            YulToken::FunctionCall => FUNCTION_CALL_SIZE,
+            YulToken::FunctionDefinition => FUNCTION_DEFINITION_SIZE,
+            YulToken::Keyword("let") => 0,
+            YulToken::Keyword("true" | "false") => 2,
+            YulToken::Keyword("for") => 10,
+            YulToken::Keyword(_) => 6,
+            YulToken::Constant => 3,
+            YulToken::Variable => 1,
+            YulToken::Literal(byte_len) => 1 + byte_len,
+            // Assignment swaps new value into place of variable then pops the old one
+            YulToken::Assignment => 2,
+        })
+        .sum()
+}
+
+/// Token of Yul relevant to estimation of code size or gas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum YulToken<'a> {
+    Builtin(&'a str),
+    FunctionCall,
+    FunctionDefinition,
+    Keyword(&'a str),
+    Constant,
+    Variable,
+    /// Literal with its minimal byte length.
+    Literal(usize),
+    Assignment,
+}
+
+/// Return tokens of a Yul line, where a function definition is taken as a single token.
+pub(crate) fn yul_tokens(line: &str) -> Vec<YulToken<'_>> {
+    let line = strip_comment(line).trim();
+    if line.starts_with("function ") {
+        return vec![YulToken::FunctionDefinition];
+    }
+
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some((start, char)) = chars.next() {
+        if char.is_ascii_alphanumeric() || char == '_' {
+            let mut end = start + 1;
+            while let Some((idx, _)) =
+                chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+            {
+                end = idx + 1;
+            }
+            let token = &line[start..end];
+            let is_call = line[end..].starts_with('(');
+            tokens.push(if char.is_ascii_digit() {
+                YulToken::Literal(literal_size(token))
+            } else if is_call {
+                if BUILTINS.contains(&token) {
+                    YulToken::Builtin(token)
+                } else {
+                    YulToken::FunctionCall
+                }
+            } else {
+                match token {
+                    "let" | "true" | "false" | "if" | "for" | "leave" => YulToken::Keyword(token),
+                    _ if token.starts_with(|c: char| c.is_ascii_uppercase()) => YulToken::Constant,
+                    _ => YulToken::Variable,
+                }
+            });
+        } else if char == ':' {
+            tokens.push(YulToken::Assignment);
+        }
+    }
+    tokens
+}
+
+fn literal_size(literal: &str) -> usize {
+    let value = if let Some(hex) = literal.strip_prefix("0x") {
+        U256::from_str_radix(hex, 16)
+    } else {
+        U256::from_str_radix(literal, 10)
+    };
+    value.map_or(32, |value| value.byte_len().max(1))
+}
+
+fn strip_comment(line: &str) -> &str {
+    line.split("//").next().unwrap()
+}
+
+/// Code size and deployment cost of contracts rendered by [`SolidityGenerator`], returned by
+/// [`SolidityGenerator::code_size_report`].
+///
+/// Sizes are compiled ones if `solc` is available, otherwise estimated from the rendered source,
+/// which are only rough and could differ from the compiled ones by tens of percent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodeSizeReport {
+    /// `Halo2Verifier.sol` rendered by [`SolidityGenerator::render`].
+    pub verifier: ContractCodeSize,
+    /// `Halo2Verifier.sol` rendered by [`SolidityGenerator::render_separately`].
+    pub separate_verifier: ContractCodeSize,
+    /// `Halo2VerifyingKey.sol` rendered by [`SolidityGenerator::render_separately`].
+    pub vk: ContractCodeSize,
+    /// `Halo2Verifier.sol` rendered by [`SolidityGenerator::render_split`].
+    pub split_verifier: ContractCodeSize,
+    /// Helper contracts rendered by [`SolidityGenerator::render_split`], which is empty if the
+    /// verifier fits without splitting.
+    pub helpers: Vec<ContractCodeSize>,
+    /// Whether sizes are estimated from the rendered source as fallback, instead of compiled.
+    pub estimated: bool,
+    /// Contracts exceeding the runtime or creation code size limit, or within its safety margin
+    /// when estimated.
+    pub warnings: Vec<CodeSizeWarning>,
+}
+
+/// Code size and deployment cost of a contract.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ContractCodeSize {
+    /// Runtime code size in bytes.
+    pub runtime: usize,
+    /// Creation code size in bytes.
+    pub creation: usize,
+    /// Gas of the deployment transaction, assuming creation code has no zero byte and excluding
+    /// execution of constructor.
+    pub deployment_gas: u64,
+    /// Runtime code size split by section.
+    pub sections: CodeSizeSections,
+}
+
+/// Runtime code size in bytes split by section of the verifier.
+///
+/// The runtime code of `Halo2VerifyingKey.sol` is the verifying key itself, so it's taken as
+/// `vk_loading` entirely.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CodeSizeSections {
+    /// Loading verifying key into memory.
+    pub vk_loading: usize,
+    /// Reading proof and instances and generating challenges.
+    pub transcript: usize,
+    /// Computing lagrange evaluations, instance evaluations, quotient evaluation and quotient
+    /// commitment.
+    pub quotient: usize,
+    /// Computing pairing input by batch opening scheme, then performing pairing.
+    pub pcs: usize,
+    /// Function dispatcher, ABI decoding, shared Yul functions and the rest.
+    pub other: usize,
+}
+
+impl CodeSizeSections {
+    /// Return total code size of all sections.
+    pub fn total(&self) -> usize {
+        self.vk_loading + self.transcript + self.quotient + self.pcs + self.other
+    }
+}
+
+/// Warning of a contract in [`CodeSizeReport`] exceeding code size limit.
+///
+/// When [`CodeSizeReport::estimated`] is set, a safety margin of 20% is added to the estimated
+/// size before comparing with the limit, since the estimate is rough.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CodeSizeWarning {
+    /// Runtime code size exceeds the maximum code size (EIP-170 by default, see
+    /// [`SolidityGenerator::set_max_code_size`]).
+    RuntimeCodeTooLarge {
+        /// Name of field of the contract in [`CodeSizeReport`], with index for `helpers`.
+        contract: String,
+        /// Runtime code size.
+        size: usize,
+        /// Maximum runtime code size.
+        limit: usize,
+    },
+    /// Creation code size exceeds twice of the maximum code size (EIP-3860 by default).
+    CreationCodeTooLarge {
+        /// Name of field of the contract in [`CodeSizeReport`], with index for `helpers`.
+        contract: String,
+        /// Creation code size.
+        size: usize,
+        /// Maximum creation code size.
+        limit: usize,
+    },
+}
+
+impl Display for CodeSizeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RuntimeCodeTooLarge {
+                contract,
+                size,
+                limit,
+            } => write!(
+                f,
+                "Runtime code size of {contract} {size} exceeds the limit {limit}"
+            ),
+            Self::CreationCodeTooLarge {
+                contract,
+                size,
+                limit,
+            } => write!(
+                f,
+                "Creation code size of {contract} {size} exceeds the limit {limit}"
+            ),
+        }
+    }
+}
+
+impl<'a> SolidityGenerator<'a> {
+    /// Return code size and deployment cost of contracts rendered by
+    /// [`SolidityGenerator::render`], [`SolidityGenerator::render_separately`] and
+    /// [`SolidityGenerator::render_split`].
+    ///
+    /// With feature `evm` and executable `solc` found, contracts are compiled and sizes are the
+    /// compiled ones, where runtime code size is split by section in proportion to the estimate.
+    ///
+    /// Otherwise it falls back to the estimate from rendered source with
+    /// [`CodeSizeReport::estimated`] set, which is the same one [`SolidityGenerator::render_split`]
+    /// uses to decide whether to split the verifier. The estimate is rough and could differ from
+    /// the compiled size by tens of percent, so warnings are then reported with a safety margin of
+    /// 20% added to it.
+    pub fn code_size_report(&self) -> Result<CodeSizeReport, fmt::Error> {
+        let verifier = self.render()?;
+        let (separate_verifier, vk) = self.render_separately()?;
+        let (split_verifier, helpers) = self.generate_split(false)?;
+        let split_verifier = {
+            let mut output = String::new();
+            split_verifier.render(&mut output)?;
+            output
+        };
+        let helper_outputs = render_helpers(&helpers)?;
+
+        let estimate_verifier = |source: &str| {
+            let sections = estimate_verifier_code_size(source);
+            contract_code_size(sections, CONSTRUCTOR_SIZE + sections.total())
+        };
+        let vk_estimate = {
+            let sections = CodeSizeSections {
+                vk_loading: self.generate_vk().len(),
+                ..Default::default()
+            };
+            // Runtime code is returned by constructor instead of being copied from creation code
+            let creation = CONSTRUCTOR_SIZE
+                + estimate_yul_code_size(assembly_blocks(&vk).into_iter().flatten());
+            contract_code_size(sections, creation)
+        };
+        let helper_estimates = helpers.iter().zip(&helper_outputs).map(|(helper, output)| {
+            // Helper takes only either quotient evaluation or PCS computations
+            let size = estimate_yul_code_size(assembly_blocks(output).into_iter().flatten());
+            let sections = match helper.kind {
+                HelperKind::Quotient => CodeSizeSections {
+                    quotient: size,
+                    other: SOLIDITY_OVERHEAD,
+                    ..Default::default()
+                },
+                HelperKind::Pcs => CodeSizeSections {
+                    pcs: size,
+                    other: SOLIDITY_OVERHEAD,
+                    ..Default::default()
+                },
+            };
+            contract_code_size(sections, CONSTRUCTOR_SIZE + sections.total())
+        });
+        let contracts = chain![
+            [
+                (verifier.as_str(), estimate_verifier(&verifier)),
+                (
+                    separate_verifier.as_str(),
+                    estimate_verifier(&separate_verifier)
+                ),
+                (vk.as_str(), vk_estimate),
+                (split_verifier.as_str(), estimate_verifier(&split_verifier)),
+            ],
+            helper_outputs
+                .iter()
+                .map(String::as_str)
+                .zip(helper_estimates),
+        ]
+        .collect::<Vec<_>>();
+
+        let compiled = contracts
+            .iter()
+            .map(|(source, estimate)| compiled_code_size(source, estimate))
+            .collect::<Option<Vec<_>>>();
+        let estimated = compiled.is_none();
+        let mut code_sizes = compiled
+            .map(|mut compiled| {
+                // Runtime code of verifying key is returned by constructor, so it's known exactly
+                compiled[2] = contract_code_size(vk_estimate.sections, compiled[2].creation);
+                compiled
+            })
+            .unwrap_or_else(|| contracts.iter().map(|(_, estimate)| *estimate).collect())
+            .into_iter();
+        let [verifier, separate_verifier, vk, split_verifier] =
+            array::from_fn(|_| code_sizes.next().unwrap());
+        let helpers = code_sizes.collect::<Vec<_>>();
+
+        let fits = |size, limit| {
+            if estimated {
+                fits_in(size, limit)
+            } else {
+                size <= limit
+            }
+        };
+        let max_initcode_size = MAX_INITCODE_SIZE_FACTOR * self.max_code_size;
+        let warnings = chain![
+            [
+                ("verifier".to_string(), &verifier),
+                ("separate_verifier".to_string(), &separate_verifier),
+                ("vk".to_string(), &vk),
+                ("split_verifier".to_string(), &split_verifier),
+            ],
+            helpers
+                .iter()
+                .enumerate()
+                .map(|(idx, helper)| (format!("helpers[{idx}]"), helper))
+        ]
+        .flat_map(|(contract, code_size)| {
+            let runtime = (!fits(code_size.runtime, self.max_code_size)).then(|| {
+                CodeSizeWarning::RuntimeCodeTooLarge {
+                    contract: contract.clone(),
+                    size: code_size.runtime,
+                    limit: self.max_code_size,
+                }
+            });
+            let creation = (!fits(code_size.creation, max_initcode_size)).then(|| {
+                CodeSizeWarning::CreationCodeTooLarge {
+                    contract,
+                    size: code_size.creation,
+                    limit: max_initcode_size,
+                }
+            });
+            [runtime, creation].into_iter().flatten()
+        })
+        .collect();
+
+        Ok(CodeSizeReport {
+            verifier,
+            separate_verifier,
+            vk,
+            split_verifier,
+            helpers,
+            estimated,
+            warnings,
+        })
+    }
+}
+
+/// Return code size of `source` compiled by `solc`, with runtime code size split by section in
+/// proportion to `estimate`, or `None` if `solc` is not found or compilation fails.
+#[cfg(any(test, feature = "evm"))]
+fn compiled_code_size(source: &str, estimate: &ContractCodeSize) -> Option<ContractCodeSize> {
+    let (creation, runtime) = try_compile_solidity(source)?;
+    let sections = scale_sections(estimate.sections, runtime.len());
+    Some(contract_code_size(sections, creation.len()))
+}
+
+/// Return `None` without feature `evm`, so [`SolidityGenerator::code_size_report`] falls back to
+/// the estimate.
+#[cfg(not(any(test, feature = "evm")))]
+fn compiled_code_size(_: &str, _: &ContractCodeSize) -> Option<ContractCodeSize> {
+    None
+}
+
+/// Return `sections` scaled to sum up to `total`, where rounding error goes to `other`.
+#[cfg(any(test, feature = "evm"))]
+fn scale_sections(sections: CodeSizeSections, total: usize) -> CodeSizeSections {
+    let estimated_total = sections.total().max(1);
+    let scale = |size: usize| size * total / estimated_total;
+    let mut scaled = CodeSizeSections {
+        vk_loading: scale(sections.vk_loading),
+        transcript: scale(sections.transcript),
+        quotient: scale(sections.quotient),
+        pcs: scale(sections.pcs),
+        other: 0,
+    };
+    scaled.other = total - scaled.total();
+    scaled
+}
+
+/// Return [`ContractCodeSize`] of contract with given runtime code sections and creation code
+/// size.
+fn contract_code_size(sections: CodeSizeSections, creation: usize) -> ContractCodeSize {
+    let runtime = sections.total();
+    let deployment_gas = TX_BASE_GAS
+        + CREATE_GAS
+        + creation as u64 * CALLDATA_NON_ZERO_BYTE_GAS
+        + ((creation + 0x1f) / 0x20) as u64 * INITCODE_WORD_GAS
+        + runtime as u64 * CODE_DEPOSIT_BYTE_GAS;
+    ContractCodeSize {
+        runtime,
+        creation,
+        deployment_gas,
+        sections,
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Section {
+    VkLoading,
+    Transcript,
+    Quotient,
+    Pcs,
+    Other,
+}
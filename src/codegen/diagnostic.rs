@@ -0,0 +1,436 @@
+#[cfg(feature = "poseidon")]
+use crate::transcript::PoseidonTranscript;
+use crate::{
+    codegen::{
+        util::{fq_to_u256, fr_to_u256},
+        BatchOpenScheme::{self, Bdfg21, Gwc19},
+        ProofField, SolidityGenerator, Transcript, TranscriptHash,
+    },
+    evm::{
+        FN_SIG_VERIFY_PROOF, FN_SIG_VERIFY_PROOF_WITH_PUBLIC_DATA,
+        FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS, FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS_AND_PUBLIC_DATA,
+    },
+    transcript::{EvmTranscript, EvmTranscriptSpec},
+};
+use halo2_proofs::{
+    halo2curves::{
+        bn256,
+        ff::{Field, PrimeField},
+        group::Group,
+        pairing::{MillerLoopResult, MultiMillerLoop},
+        CurveAffine,
+    },
+    plonk::{verify_proof, VerifyingKey},
+    poly::kzg::{
+        commitment::{KZGCommitmentScheme, ParamsKZG},
+        multiopen::{VerifierGWC, VerifierSHPLONK},
+        strategy::SingleStrategy,
+    },
+    transcript::{
+        Blake2bRead, Challenge255, EncodedChallenge, TranscriptRead, TranscriptReadBuffer,
+    },
+};
+use itertools::Itertools;
+use ruint::aliases::U256;
+use std::fmt;
+
+/// The first check performed by `verifyProof` that fails on given calldata, returned by
+/// [`SolidityGenerator::diagnose`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FailedCheck {
+    /// Calldata is not encoded for `verifyProof`.
+    FunctionSignature,
+    /// Length of proof doesn't match the one expected by verifier.
+    ProofLength {
+        /// Calldata offset of length of proof.
+        offset: usize,
+        /// Expected length of proof in bytes.
+        expected: usize,
+    },
+    /// Length of instances doesn't match the one expected by verifier.
+    InstancesLength {
+        /// Calldata offset of length of instances.
+        offset: usize,
+        /// Expected number of instances.
+        expected: usize,
+    },
+    /// Scalar is not less than BN254 scalar field modulus.
+    InvalidScalar {
+        /// Name of the scalar, e.g. `instances[0]` or `advice_evals[1]`.
+        name: String,
+        /// Calldata offset of the scalar.
+        offset: usize,
+    },
+    /// Keccak256 of public data doesn't match its limbs in instances.
+    PublicInputHash {
+        /// Calldata offset of the first limb of the hash.
+        offset: usize,
+    },
+    /// Point has coordinate not less than BN254 base field modulus or is not on curve.
+    InvalidEcPoint {
+        /// Name of the point, e.g. `advice_comms[0]` or `quotient_comms[1]`.
+        name: String,
+        /// Calldata offset of x coordinate of the point.
+        offset: usize,
+    },
+    /// Accumulator decoded from instances is not on curve.
+    InvalidAccumulator {
+        /// Name of the point, either `acc_lhs` or `acc_rhs`.
+        name: String,
+        /// Calldata offset of the first limb of x coordinate of the point.
+        offset: usize,
+    },
+    /// Pairing check fails.
+    Pairing,
+}
+
+impl FailedCheck {
+    /// Return name of the failing check.
+    pub fn name(&self) -> String {
+        match self {
+            Self::FunctionSignature => "function_signature".to_string(),
+            Self::ProofLength { .. } => "proof_length".to_string(),
+            Self::InstancesLength { .. } => "instances_length".to_string(),
+            Self::PublicInputHash { .. } => "public_input_hash".to_string(),
+            Self::InvalidScalar { name, .. }
+            | Self::InvalidEcPoint { name, .. }
+            | Self::InvalidAccumulator { name, .. } => name.clone(),
+            Self::Pairing => "pairing".to_string(),
+        }
+    }
+
+    /// Return calldata offset of the value that makes the check fail, if any.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::FunctionSignature | Self::Pairing => None,
+            Self::ProofLength { offset, .. }
+            | Self::InstancesLength { offset, .. }
+            | Self::PublicInputHash { offset }
+            | Self::InvalidScalar { offset, .. }
+            | Self::InvalidEcPoint { offset, .. }
+            | Self::InvalidAccumulator { offset, .. } => Some(*offset),
+        }
+    }
+}
+
+impl fmt::Display for FailedCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FunctionSignature => write!(f, "Calldata is not encoded for verifyProof"),
+            Self::ProofLength { offset, expected } => {
+                write!(f, "Proof length at 0x{offset:x} is not {expected}")
+            }
+            Self::InstancesLength { offset, expected } => {
+                write!(f, "Instances length at 0x{offset:x} is not {expected}")
+            }
+            Self::InvalidScalar { name, offset } => {
+                write!(f, "Scalar {name} at 0x{offset:x} is not in scalar field")
+            }
+            Self::PublicInputHash { offset } => {
+                write!(
+                    f,
+                    "Public input hash limbs at 0x{offset:x} don't match public data"
+                )
+            }
+            Self::InvalidEcPoint { name, offset } => {
+                write!(
+                    f,
+                    "Point {name} at 0x{offset:x} is not a valid point on curve"
+                )
+            }
+            Self::InvalidAccumulator { name, offset } => {
+                write!(f, "Accumulator {name} at 0x{offset:x} is not on curve")
+            }
+            Self::Pairing => write!(f, "Pairing check fails"),
+        }
+    }
+}
+
+impl std::error::Error for FailedCheck {}
+
+impl<'a> SolidityGenerator<'a> {
+    /// Replay checks performed by `verifyProof` of the generated verifier on `calldata` in the
+    /// same order, and return the first one that fails.
+    ///
+    /// The `calldata` is expected to be encoded by [`encode_calldata`](crate::encode_calldata),
+    /// or [`encode_calldata_with_public_data`](crate::encode_calldata_with_public_data) if
+    /// [`SolidityGenerator::set_public_input_hash`] is set, with or without verifying key address.
+    /// Checks that only fail when precompiles are given invalid input are not replayed, since all
+    /// points from calldata are checked beforehand.
+    pub fn diagnose(&self, calldata: &[u8]) -> Result<(), FailedCheck> {
+        let proof_cptr = match (calldata.get(..4), self.public_input_hash.is_some()) {
+            (Some(fn_sig), false) if fn_sig == FN_SIG_VERIFY_PROOF => 0x64,
+            (Some(fn_sig), false) if fn_sig == FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS => 0x84,
+            (Some(fn_sig), true) if fn_sig == FN_SIG_VERIFY_PROOF_WITH_PUBLIC_DATA => 0x84,
+            (Some(fn_sig), true)
+                if fn_sig == FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS_AND_PUBLIC_DATA =>
+            {
+                0xa4
+            }
+            _ => return Err(FailedCheck::FunctionSignature),
+        };
+        let calldata_word = |offset: usize| {
+            let mut word = [0; 0x20];
+            if let Some(bytes) = calldata.get(offset..) {
+                let len = bytes.len().min(0x20);
+                word[..len].copy_from_slice(&bytes[..len]);
+            }
+            word
+        };
+        let calldataload = |offset: usize| U256::from_be_bytes(calldata_word(offset));
+        // Same as `calldataload` but in little-endian when proof is encoded so
+        let proofload = |offset: usize| {
+            if self.proof_encoding().little_endian() {
+                U256::from_le_bytes(calldata_word(offset))
+            } else {
+                calldataload(offset)
+            }
+        };
+        let q = fq_to_u256(-bn256::Fq::ONE) + U256::from(1);
+        let r = fr_to_u256(-bn256::Fr::ONE) + U256::from(1);
+
+        let proof_len = self.proof_len();
+        let num_instances = self.num_instances.iter().sum::<usize>();
+        let num_instance_cptr = proof_cptr + proof_len;
+        let instance_cptr = num_instance_cptr + 0x20;
+
+        if calldataload(proof_cptr - 0x20) != U256::from(proof_len) {
+            return Err(FailedCheck::ProofLength {
+                offset: proof_cptr - 0x20,
+                expected: proof_len,
+            });
+        }
+
+        if calldataload(num_instance_cptr) != U256::from(num_instances) {
+            return Err(FailedCheck::InstancesLength {
+                offset: num_instance_cptr,
+                expected: num_instances,
+            });
+        }
+
+        let instances = (0..num_instances)
+            .map(|idx| {
+                let offset = instance_cptr + 0x20 * idx;
+                let instance = calldataload(offset);
+                if instance < r {
+                    Ok(bn256::Fr::from_repr(instance.to_le_bytes()).unwrap())
+                } else {
+                    Err(FailedCheck::InvalidScalar {
+                        name: format!("instances[{idx}]"),
+                        offset,
+                    })
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Some(public_input_hash) = self.public_input_hash {
+            // Same as verifier, which copies public data with zeros padded beyond calldata
+            let calldataload_usize =
+                |offset: usize| usize::try_from(calldataload(offset)).unwrap_or(usize::MAX);
+            let public_data_cptr = calldataload_usize(proof_cptr - 0x40).saturating_add(0x04);
+            let public_data = (0..calldataload_usize(public_data_cptr).min(calldata.len()))
+                .map(|idx| {
+                    let cptr = public_data_cptr.saturating_add(0x20 + idx);
+                    calldata.get(cptr).copied().unwrap_or_default()
+                })
+                .collect_vec();
+            let offset = public_input_hash.offset;
+            if instances[offset..offset + public_input_hash.num_limbs]
+                != public_input_hash.limbs(&public_data)
+            {
+                return Err(FailedCheck::PublicInputHash {
+                    offset: instance_cptr + 0x20 * offset,
+                });
+            }
+        }
+
+        let proof_fields = self.proof_layout().fields().into_iter();
+        for (name, field) in proof_fields.map(|(name, field)| (name, field.shifted(proof_cptr))) {
+            match field {
+                ProofField::EcPoint(offset) if self.proof_encoding().compressed_points() => {
+                    // Identity is encoded as zero
+                    let is_identity = proofload(offset) == U256::ZERO;
+                    let x = proofload(offset) & (U256::MAX >> 1);
+                    if !(is_identity || (x < q && is_on_curve_x(x, q))) {
+                        return Err(FailedCheck::InvalidEcPoint { name, offset });
+                    }
+                }
+                ProofField::EcPoint(offset) => {
+                    let (x, y) = (calldataload(offset), calldataload(offset + 0x20));
+                    // Identity is encoded as (0, 0)
+                    let is_identity = x == U256::ZERO && y == U256::ZERO;
+                    if !(is_identity || (x < q && y < q && is_on_curve(x, y, q))) {
+                        return Err(FailedCheck::InvalidEcPoint { name, offset });
+                    }
+                }
+                ProofField::Scalar(offset) => {
+                    if proofload(offset) >= r {
+                        return Err(FailedCheck::InvalidScalar { name, offset });
+                    }
+                }
+            }
+        }
+
+        let acc = if let Some(acc_encoding) = self.acc_encoding {
+            let num_limbs = acc_encoding.num_limbs;
+            let num_limb_bits = acc_encoding.num_limb_bits;
+            let cptr = instance_cptr + 0x20 * acc_encoding.offset;
+            let [lhs_x, lhs_y, rhs_x, rhs_y] = [0, 1, 2, 3].map(|idx| {
+                let start = cptr + 0x20 * num_limbs * idx;
+                // Same as verifier, which reads limbs at index `1..=num_limbs` after the first one
+                (1..=num_limbs).fold(calldataload(start), |acc, limb_idx| {
+                    let limb = calldataload(start + 0x20 * limb_idx);
+                    acc.wrapping_add(limb.wrapping_shl(num_limb_bits * limb_idx))
+                })
+            });
+            let (lhs_cptr, rhs_cptr) = (cptr, cptr + 0x40 * num_limbs);
+            if !is_on_curve(lhs_x, lhs_y, q) {
+                return Err(FailedCheck::InvalidAccumulator {
+                    name: "acc_lhs".to_string(),
+                    offset: lhs_cptr,
+                });
+            }
+            if !is_on_curve(rhs_x, rhs_y, q) {
+                return Err(FailedCheck::InvalidAccumulator {
+                    name: "acc_rhs".to_string(),
+                    offset: rhs_cptr,
+                });
+            }
+            Some([(lhs_x, lhs_y), (rhs_x, rhs_y)])
+        } else {
+            None
+        };
+
+        let instances = self
+            .num_instances
+            .iter()
+            .scan(instances.as_slice(), |instances, num_instances| {
+                let (column, rest) = instances.split_at(*num_instances);
+                *instances = rest;
+                Some(column)
+            })
+            .collect_vec();
+        let proof = (proof_cptr..proof_cptr + proof_len)
+            .map(|cptr| calldata.get(cptr).copied().unwrap_or_default())
+            .collect_vec();
+        let is_proof_valid = match self.transcript {
+            Transcript::Evm(fns) => (fns.verify)(
+                self.params,
+                self.vk,
+                self.scheme,
+                &instances,
+                &proof,
+                self.compressed_points,
+            ),
+            Transcript::LittleEndian(TranscriptHash::Blake2b) => {
+                let transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof.as_slice());
+                verify_native(self.params, self.vk, self.scheme, &instances, transcript)
+            }
+            #[cfg(feature = "poseidon")]
+            Transcript::LittleEndian(TranscriptHash::Poseidon) => {
+                let transcript = PoseidonTranscript::new(proof.as_slice());
+                verify_native(self.params, self.vk, self.scheme, &instances, transcript)
+            }
+        };
+        let is_acc_valid = acc.map_or(true, |acc| {
+            let [lhs, rhs] = acc.map(|(x, y)| {
+                let [x, y] = [x, y].map(|coord| bn256::Fq::from_repr(coord.to_le_bytes()));
+                x.and_then(|x| y.and_then(|y| bn256::G1Affine::from_xy(x, y)))
+            });
+            match (Option::from(lhs), Option::from(rhs)) {
+                (Some(lhs), Some(rhs)) => is_pairing_valid(self.params, lhs, rhs),
+                _ => false,
+            }
+        });
+        if !(is_proof_valid && is_acc_valid) {
+            return Err(FailedCheck::Pairing);
+        }
+
+        Ok(())
+    }
+}
+
+fn is_on_curve(x: U256, y: U256, q: U256) -> bool {
+    let lhs = y.mul_mod(y, q);
+    let rhs = x.mul_mod(x.mul_mod(x, q), q).add_mod(U256::from(3), q);
+    lhs == rhs
+}
+
+/// Return whether `x` is x coordinate of a point on curve, by checking if `x^3 + 3` has square
+/// root `(x^3 + 3)^((q + 1) / 4)` as the verifier does.
+fn is_on_curve_x(x: U256, q: U256) -> bool {
+    let y_square = x.mul_mod(x.mul_mod(x, q), q).add_mod(U256::from(3), q);
+    let y = y_square.pow_mod((q + U256::from(1)) >> 2, q);
+    is_on_curve(x, y, q)
+}
+
+/// [`verify_evm_transcript`] with the spec type erased.
+pub(crate) type VerifyFn = fn(
+    &ParamsKZG<bn256::Bn256>,
+    &VerifyingKey<bn256::G1Affine>,
+    BatchOpenScheme,
+    &[&[bn256::Fr]],
+    &[u8],
+    bool,
+) -> bool;
+
+/// Return whether `proof` created by [`EvmTranscript`] with spec `S` is valid.
+pub(crate) fn verify_evm_transcript<S: EvmTranscriptSpec>(
+    params: &ParamsKZG<bn256::Bn256>,
+    vk: &VerifyingKey<bn256::G1Affine>,
+    scheme: BatchOpenScheme,
+    instances: &[&[bn256::Fr]],
+    proof: &[u8],
+    compressed_points: bool,
+) -> bool {
+    let transcript = EvmTranscript::<_, _, S>::new(proof).set_compressed_points(compressed_points);
+    verify_native(params, vk, scheme, instances, transcript)
+}
+
+fn verify_native<E, T>(
+    params: &ParamsKZG<bn256::Bn256>,
+    vk: &VerifyingKey<bn256::G1Affine>,
+    scheme: BatchOpenScheme,
+    instances: &[&[bn256::Fr]],
+    mut transcript: T,
+) -> bool
+where
+    E: EncodedChallenge<bn256::G1Affine>,
+    T: TranscriptRead<bn256::G1Affine, E>,
+{
+    type Scheme = KZGCommitmentScheme<bn256::Bn256>;
+
+    let strategy = SingleStrategy::new(params);
+    let instances = &[instances];
+    match scheme {
+        Bdfg21 => verify_proof::<Scheme, VerifierSHPLONK<_>, _, _, SingleStrategy<_>>(
+            params,
+            vk,
+            strategy,
+            instances,
+            &mut transcript,
+        )
+        .is_ok(),
+        Gwc19 => verify_proof::<Scheme, VerifierGWC<_>, _, _, SingleStrategy<_>>(
+            params,
+            vk,
+            strategy,
+            instances,
+            &mut transcript,
+        )
+        .is_ok(),
+    }
+}
+
+fn is_pairing_valid(
+    params: &ParamsKZG<bn256::Bn256>,
+    lhs: bn256::G1Affine,
+    rhs: bn256::G1Affine,
+) -> bool {
+    let g2 = bn256::G2Prepared::from(params.g2());
+    let neg_s_g2 = bn256::G2Prepared::from(-params.s_g2());
+    bn256::Bn256::multi_miller_loop(&[(&lhs, &g2), (&rhs, &neg_s_g2)])
+        .final_exponentiation()
+        .is_identity()
+        .into()
+}
@@ -1,6 +1,8 @@
 #![allow(clippy::useless_format)]
 
-use crate::codegen::util::{code_block, fe_to_u256, ConstraintSystemMeta, Data};
+use crate::codegen::util::{
+    code_block, fe_to_u256, for_loop, ConstraintSystemMeta, Data, Ptr, Value, Word,
+};
 use halo2_proofs::{
     halo2curves::ff::PrimeField,
     plonk::{
@@ -10,13 +12,29 @@ use halo2_proofs::{
 };
 use itertools::{chain, izip, Itertools};
 use ruint::aliases::U256;
-use std::{borrow::Borrow, cell::RefCell, cmp::Ordering, collections::HashMap, iter};
+use std::{
+    borrow::Borrow,
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    iter,
+};
+
+/// Memory pointer of the first slot of values shared by quotient evaluation, which are computed
+/// once before it. The scratch space below `VK_MPTR` is free during quotient evaluation, and
+/// `0x00` is left for permutation computations.
+const SHARED_MPTR: usize = 0x20;
 
 #[derive(Debug)]
 pub(crate) struct Evaluator<'a, F: PrimeField> {
-    cs: &'a ConstraintSystem<F>,
     meta: &'a ConstraintSystemMeta,
     data: &'a Data,
+    gates: Vec<Expression<F>>,
+    lookups: Vec<[Vec<Expression<F>>; 2]>,
+    shuffles: Vec<[Vec<Expression<F>>; 2]>,
+    l_active_mptr: Option<Ptr>,
+    shared_expressions: Vec<(String, Expression<F>)>,
+    shared_mptrs: HashMap<String, Ptr>,
     var_counter: RefCell<usize>,
     var_cache: RefCell<HashMap<String, String>>,
 }
@@ -26,31 +44,64 @@ where
     F: PrimeField<Repr = [u8; 0x20]>,
 {
     pub(crate) fn new(
-        cs: &'a ConstraintSystem<F>,
+        cs: &ConstraintSystem<F>,
         meta: &'a ConstraintSystemMeta,
         data: &'a Data,
     ) -> Self {
+        let (gates, lookups, shuffles) = simplified_expressions(cs);
+        let l_active_mptr = has_l_active(meta).then(|| Ptr::memory(SHARED_MPTR));
+        let shared_expressions = shared_expressions(&gates, &lookups, &shuffles);
+        let shared_mptrs = izip!(
+            &shared_expressions,
+            Word::range(Ptr::memory(SHARED_MPTR) + usize::from(l_active_mptr.is_some())),
+        )
+        .map(|((key, _), word)| (key.clone(), word.ptr()))
+        .collect();
         Self {
-            cs,
             meta,
             data,
+            gates,
+            lookups,
+            shuffles,
+            l_active_mptr,
+            shared_expressions,
+            shared_mptrs,
             var_counter: Default::default(),
             var_cache: Default::default(),
         }
     }
 
+    /// Computations of values shared by gate, permutation, lookup and shuffle computations, each
+    /// stores its value into memory to be loaded by them.
+    pub fn shared_computations(&self) -> Vec<Vec<String>> {
+        chain![
+            self.l_active_mptr.map(|l_active_mptr| {
+                let l_inactive = "addmod(mload(L_LAST_MPTR), mload(L_BLIND_MPTR), r)";
+                vec![format!(
+                    "mstore({l_active_mptr}, addmod(1, sub(r, {l_inactive}), r))"
+                )]
+            }),
+            self.shared_expressions.iter().map(|(key, expression)| {
+                let (mut lines, var) = self.evaluate_inner(expression, true);
+                self.reset();
+                lines.push(format!("mstore({}, {var})", self.shared_mptrs[key]));
+                lines
+            })
+        ]
+        .collect()
+    }
+
     pub fn gate_computations(&self) -> Vec<(Vec<String>, String)> {
-        self.cs
-            .gates()
+        self.gates
             .iter()
-            .flat_map(Gate::polynomials)
             .map(|expression| self.evaluate_and_reset(expression))
             .collect()
     }
 
     pub fn permutation_computations(&self) -> Vec<(Vec<String>, String)> {
         let Self { meta, data, .. } = self;
-        let last_chunk_idx = meta.num_permutation_zs - 1;
+        // Circuit without any copy constraint has no permutation chunk
+        let last_chunk_idx = meta.num_permutation_zs.checked_sub(1);
         chain![
             data.permutation_z_evals.first().map(|(z, _, _)| {
                 vec![
@@ -100,17 +151,16 @@ where
                         let item = format!("addmod(addmod({eval}, mload(0x00), r), gamma, r)");
                         chain![
                             [format!("rhs := mulmod(rhs, {item}, r)")],
-                            (!(chunk_idx == last_chunk_idx && idx == last_column_idx))
+                            (!(Some(chunk_idx) == last_chunk_idx && idx == last_column_idx))
                                 .then(|| "mstore(0x00, mulmod(mload(0x00), delta, r))".to_string()),
                         ]
                     }),
                     {
-                        let item = format!("addmod(mload(L_LAST_MPTR), mload(L_BLIND_MPTR), r)");
-                        let item = format!("sub(r, mulmod(left_sub_right, {item}, r))");
-                        [
-                            format!("let left_sub_right := addmod(lhs, sub(r, rhs), r)"),
-                            format!("let eval := addmod(left_sub_right, {item}, r)"),
-                        ]
+                        let l_active = self.l_active();
+                        let left_sub_right = format!("addmod(lhs, sub(r, rhs), r)");
+                        [format!(
+                            "let eval := mulmod({left_sub_right}, {l_active}, r)"
+                        )]
                     }
                 ]
                 .collect_vec()
@@ -122,23 +172,11 @@ where
 
     pub fn lookup_computations(&self) -> Vec<(Vec<String>, String)> {
         let input_tables = self
-            .cs
-            .lookups()
+            .lookups
             .iter()
-            .map(|lookup| {
+            .map(|[inputs, tables]| {
                 let [(input_lines, inputs), (table_lines, tables)] =
-                    [lookup.input_expressions(), lookup.table_expressions()].map(|expressions| {
-                        let (lines, inputs) = expressions
-                            .iter()
-                            .map(|expression| self.evaluate(expression))
-                            .fold((Vec::new(), Vec::new()), |mut acc, result| {
-                                acc.0.extend(result.0);
-                                acc.1.push(result.1);
-                                acc
-                            });
-                        self.reset();
-                        (lines, inputs)
-                    });
+                    [inputs, tables].map(|expressions| self.evaluate_all_and_reset(expressions));
                 (input_lines, inputs, table_lines, tables)
             })
             .collect_vec();
@@ -191,8 +229,7 @@ where
                             ]
                         },
                         {
-                            let l_inactive = "addmod(mload(L_BLIND_MPTR), mload(L_LAST_MPTR), r)";
-                            let l_active = format!("addmod(1, sub(r, {l_inactive}), r)");
+                            let l_active = self.l_active();
                             [format!(
                                 "let eval := mulmod({l_active}, addmod(lhs, sub(r, rhs), r), r)"
                             )]
@@ -205,8 +242,7 @@ where
                         vec![format!("let eval := mulmod({l_0}, {item}, r)")]
                     },
                     {
-                        let l_inactive = "addmod(mload(L_BLIND_MPTR), mload(L_LAST_MPTR), r)";
-                        let l_active = format!("addmod(1, sub(r, {l_inactive}), r)");
+                        let l_active = self.l_active();
                         let lhs = format!("addmod({p_input}, sub(r, {p_table}), r)");
                         let rhs = format!("addmod({p_input}, sub(r, {p_input_prev}), r)");
                         vec![format!(
@@ -219,14 +255,133 @@ where
             .collect_vec()
     }
 
+    pub fn shuffle_computations(&self) -> Vec<(Vec<String>, String)> {
+        let input_shuffles = self
+            .shuffles
+            .iter()
+            .map(|[inputs, shuffles]| {
+                let [(input_lines, inputs), (shuffle_lines, shuffles)] =
+                    [inputs, shuffles].map(|expressions| self.evaluate_all_and_reset(expressions));
+                (input_lines, inputs, shuffle_lines, shuffles)
+            })
+            .collect_vec();
+        izip!(input_shuffles, &self.data.shuffle_evals)
+            .flat_map(|(input_shuffle, evals)| {
+                let (input_lines, inputs, shuffle_lines, shuffles) = input_shuffle;
+                let (input_0, rest_inputs) = inputs.split_first().unwrap();
+                let (shuffle_0, rest_shuffles) = shuffles.split_first().unwrap();
+                let (z, z_next) = evals;
+                [
+                    vec![
+                        format!("let l_0 := mload(L_0_MPTR)"),
+                        format!("let eval := addmod(l_0, mulmod(l_0, sub(r, {z}), r), r)"),
+                    ],
+                    {
+                        let item = format!("addmod(mulmod({z}, {z}, r), sub(r, {z}), r)");
+                        vec![
+                            format!("let l_last := mload(L_LAST_MPTR)"),
+                            format!("let eval := mulmod(l_last, {item}, r)"),
+                        ]
+                    },
+                    chain![
+                        ["let theta := mload(THETA_MPTR)", "let input"].map(str::to_string),
+                        code_block::<1, false>(chain![
+                            input_lines,
+                            [format!("input := {input_0}")],
+                            rest_inputs.iter().map(|input| format!(
+                                "input := addmod(mulmod(input, theta, r), {input}, r)"
+                            ))
+                        ]),
+                        ["let shuffle"].map(str::to_string),
+                        code_block::<1, false>(chain![
+                            shuffle_lines,
+                            [format!("shuffle := {shuffle_0}")],
+                            rest_shuffles.iter().map(|shuffle| format!(
+                                "shuffle := addmod(mulmod(shuffle, theta, r), {shuffle}, r)"
+                            ))
+                        ]),
+                        [
+                            format!("let gamma := mload(GAMMA_MPTR)"),
+                            format!("let lhs := mulmod({z_next}, addmod(shuffle, gamma, r), r)"),
+                            format!("let rhs := mulmod({z}, addmod(input, gamma, r), r)"),
+                        ],
+                        {
+                            let l_active = self.l_active();
+                            [format!(
+                                "let eval := mulmod({l_active}, addmod(lhs, sub(r, rhs), r), r)"
+                            )]
+                        },
+                    ]
+                    .collect_vec(),
+                ]
+            })
+            .zip(iter::repeat("eval".to_string()))
+            .collect_vec()
+    }
+
+    /// Evaluations of queried instance columns, computed by `L_j(x·ω^r) = L_{j-r}(x)` with lagrange
+    /// evaluations starting at `X_N_MPTR`.
+    pub fn instance_computations(&self) -> Vec<String> {
+        let Self { meta, data, .. } = self;
+        let num_neg_lagranges = meta.num_neg_lagranges();
+        chain![
+            [
+                format!("let instance_cptr := INSTANCE_CPTR"),
+                format!("let instance_cptr_end"),
+            ],
+            izip!(0.., &data.num_instances).flat_map(|(column_index, num_instances)| {
+                let rotations = meta
+                    .instance_queries
+                    .iter()
+                    .filter(|(idx, _)| *idx == column_index)
+                    .map(|(_, rotation)| *rotation)
+                    .collect_vec();
+                chain![
+                    [format!(
+                        "instance_cptr_end := add(instance_cptr, mul(0x20, {num_instances}))"
+                    )],
+                    rotations.into_iter().flat_map(|rotation| {
+                        let l_i_offset =
+                            Value::from((num_neg_lagranges as i32 - rotation) as usize * 0x20);
+                        let instance_eval = data.instance_evals[&(column_index, rotation)].ptr();
+                        code_block::<1, false>(chain![
+                            [
+                                format!("let instance_eval := 0"),
+                                format!("let l_i_mptr := add(X_N_MPTR, {l_i_offset})"),
+                            ],
+                            for_loop(
+                                [format!("let cptr := instance_cptr")],
+                                "lt(cptr, instance_cptr_end)",
+                                [
+                                    format!("cptr := add(cptr, 0x20)"),
+                                    format!("l_i_mptr := add(l_i_mptr, 0x20)"),
+                                ],
+                                [format!("instance_eval := addmod(instance_eval, mulmod(mload(l_i_mptr), calldataload(cptr), r), r)")],
+                            ),
+                            [format!("mstore({instance_eval}, instance_eval)")],
+                        ])
+                    }),
+                    [format!("instance_cptr := instance_cptr_end")],
+                ]
+                .collect_vec()
+            }),
+        ]
+        .collect()
+    }
+
     fn eval(&self, column_type: impl Into<Any>, column_index: usize, rotation: i32) -> String {
         match column_type.into() {
             Any::Advice(_) => self.data.advice_evals[&(column_index, rotation)].to_string(),
             Any::Fixed => self.data.fixed_evals[&(column_index, rotation)].to_string(),
-            Any::Instance => self.data.instance_eval.to_string(),
+            Any::Instance => self.data.instance_evals[&(column_index, rotation)].to_string(),
         }
     }
 
+    /// `1 - (l_last + l_blind)`, computed once by [`Evaluator::shared_computations`].
+    fn l_active(&self) -> Word {
+        self.l_active_mptr.unwrap().into()
+    }
+
     fn reset(&self) {
         *self.var_counter.borrow_mut() = Default::default();
         *self.var_cache.borrow_mut() = Default::default();
@@ -239,55 +394,101 @@ where
     }
 
     fn evaluate(&self, expression: &Expression<F>) -> (Vec<String>, String) {
-        evaluate(
-            expression,
-            &|constant| {
-                let constant = u256_string(constant);
+        self.evaluate_inner(expression, false)
+    }
+
+    /// Evaluate expressions in the same scope, so common leaves are loaded only once.
+    fn evaluate_all_and_reset(&self, expressions: &[Expression<F>]) -> (Vec<String>, Vec<String>) {
+        let result = expressions
+            .iter()
+            .map(|expression| self.evaluate(expression))
+            .fold((Vec::new(), Vec::new()), |mut acc, result| {
+                acc.0.extend(result.0);
+                acc.1.push(result.1);
+                acc
+            });
+        self.reset();
+        result
+    }
+
+    /// Evaluate expression, loading shared sub-expressions from memory except the root when
+    /// it's being computed as a shared one.
+    fn evaluate_inner(
+        &self,
+        expression: &Expression<F>,
+        is_shared_root: bool,
+    ) -> (Vec<String>, String) {
+        if !is_shared_root && !is_leaf(expression) {
+            if let Some(mptr) = self.shared_mptrs.get(&expression_key(expression)) {
+                return self.init_var(Word::from(*mptr), None);
+            }
+        }
+        match expression {
+            Expression::Constant(scalar) => {
+                let constant = u256_string(fe_to_u256(*scalar));
                 self.init_var(constant, None)
-            },
-            &|query| {
-                self.init_var(
-                    self.eval(Fixed, query.column_index(), query.rotation().0),
-                    Some(fixed_eval_var(query)),
-                )
-            },
-            &|query| {
-                self.init_var(
-                    self.eval(Advice::default(), query.column_index(), query.rotation().0),
-                    Some(advice_eval_var(query)),
-                )
-            },
-            &|_| self.init_var(self.data.instance_eval, Some("i_eval".to_string())),
-            &|challenge| {
-                self.init_var(
-                    self.data.challenges[challenge.index()],
-                    Some(format!("c_{}", challenge.index())),
-                )
-            },
-            &|(mut acc, var)| {
+            }
+            Expression::Selector(_) => unreachable!(),
+            Expression::Fixed(query) => self.init_var(
+                self.eval(Fixed, query.column_index(), query.rotation().0),
+                Some(fixed_eval_var(*query)),
+            ),
+            Expression::Advice(query) => self.init_var(
+                self.eval(Advice::default(), query.column_index(), query.rotation().0),
+                Some(advice_eval_var(*query)),
+            ),
+            Expression::Instance(query) => self.init_var(
+                self.eval(Any::Instance, query.column_index(), query.rotation().0),
+                Some(instance_eval_var(*query)),
+            ),
+            Expression::Challenge(challenge) => self.init_var(
+                self.data.challenges[challenge.index()],
+                Some(format!("c_{}", challenge.index())),
+            ),
+            Expression::Negated(value) => {
+                let (mut acc, var) = self.evaluate(value);
                 let (lines, var) = self.init_var(format!("sub(r, {var})"), None);
                 acc.extend(lines);
                 (acc, var)
-            },
-            &|(mut lhs_acc, lhs_var), (rhs_acc, rhs_var)| {
+            }
+            Expression::Sum(lhs, rhs) => {
+                // Subtract negated operand in place, or reuse its negation if already computed
+                let (lhs, rhs, is_sub) = match (lhs.as_ref(), rhs.as_ref()) {
+                    (lhs, Expression::Negated(rhs)) | (Expression::Negated(rhs), lhs) => {
+                        (lhs, rhs.as_ref(), true)
+                    }
+                    (lhs, rhs) => (lhs, rhs, false),
+                };
+                let (mut lhs_acc, lhs_var) = self.evaluate(lhs);
+                let (rhs_acc, rhs_var) = self.evaluate(rhs);
+                let rhs_var = if is_sub {
+                    let negated = format!("sub(r, {rhs_var})");
+                    let cached = self.var_cache.borrow().get(&negated).cloned();
+                    cached.unwrap_or(negated)
+                } else {
+                    rhs_var
+                };
                 let (lines, var) = self.init_var(format!("addmod({lhs_var}, {rhs_var}, r)"), None);
                 lhs_acc.extend(rhs_acc);
                 lhs_acc.extend(lines);
                 (lhs_acc, var)
-            },
-            &|(mut lhs_acc, lhs_var), (rhs_acc, rhs_var)| {
+            }
+            Expression::Product(lhs, rhs) => {
+                let (mut lhs_acc, lhs_var) = self.evaluate(lhs);
+                let (rhs_acc, rhs_var) = self.evaluate(rhs);
                 let (lines, var) = self.init_var(format!("mulmod({lhs_var}, {rhs_var}, r)"), None);
                 lhs_acc.extend(rhs_acc);
                 lhs_acc.extend(lines);
                 (lhs_acc, var)
-            },
-            &|(mut acc, var), scalar| {
-                let scalar = u256_string(scalar);
+            }
+            Expression::Scaled(value, scalar) => {
+                let (mut acc, var) = self.evaluate(value);
+                let scalar = u256_string(fe_to_u256(*scalar));
                 let (lines, var) = self.init_var(format!("mulmod({var}, {scalar}, r)"), None);
                 acc.extend(lines);
                 (acc, var)
-            },
-        )
+            }
+        }
     }
 
     fn init_var(&self, value: impl ToString, var: Option<String>) -> (Vec<String>, String) {
@@ -310,6 +511,182 @@ where
     }
 }
 
+/// Whether permutation, lookup or shuffle computations need `l_active`.
+fn has_l_active(meta: &ConstraintSystemMeta) -> bool {
+    meta.num_permutation_zs + meta.num_lookup_zs + meta.num_shuffle_zs > 0
+}
+
+/// Return number of memory slots used by [`Evaluator::shared_computations`].
+pub(crate) fn num_shared_slots<F: PrimeField<Repr = [u8; 0x20]>>(
+    cs: &ConstraintSystem<F>,
+    meta: &ConstraintSystemMeta,
+) -> usize {
+    let (gates, lookups, shuffles) = simplified_expressions(cs);
+    usize::from(has_l_active(meta)) + shared_expressions(&gates, &lookups, &shuffles).len()
+}
+
+/// Return simplified gate polynomials, simplified input and table expressions of lookups, and
+/// simplified input and shuffle expressions of shuffles.
+#[allow(clippy::type_complexity)]
+fn simplified_expressions<F: PrimeField>(
+    cs: &ConstraintSystem<F>,
+) -> (
+    Vec<Expression<F>>,
+    Vec<[Vec<Expression<F>>; 2]>,
+    Vec<[Vec<Expression<F>>; 2]>,
+) {
+    let gates = cs
+        .gates()
+        .iter()
+        .flat_map(Gate::polynomials)
+        .map(simplify)
+        .collect();
+    let lookups = cs
+        .lookups()
+        .iter()
+        .map(|lookup| {
+            [lookup.input_expressions(), lookup.table_expressions()]
+                .map(|expressions| expressions.iter().map(simplify).collect())
+        })
+        .collect();
+    let shuffles = cs
+        .shuffles()
+        .iter()
+        .map(|shuffle| {
+            [shuffle.input_expressions(), shuffle.shuffle_expressions()]
+                .map(|expressions| expressions.iter().map(simplify).collect())
+        })
+        .collect();
+    (gates, lookups, shuffles)
+}
+
+/// Return non-leaf sub-expressions appearing in more than one gate polynomial, lookup or shuffle,
+/// with their keys, in the order inner ones come first.
+fn shared_expressions<F: PrimeField<Repr = [u8; 0x20]>>(
+    gates: &[Expression<F>],
+    lookups: &[[Vec<Expression<F>>; 2]],
+    shuffles: &[[Vec<Expression<F>>; 2]],
+) -> Vec<(String, Expression<F>)> {
+    let scopes = chain![
+        gates.iter().map(|polynomial| vec![polynomial]),
+        chain![lookups, shuffles].map(|argument| argument.iter().flatten().collect_vec()),
+    ];
+    let mut num_scopes = HashMap::<String, usize>::new();
+    let mut expressions = Vec::new();
+    for scope in scopes {
+        let mut keys = HashSet::new();
+        for expression in scope {
+            for expression in sub_expressions(expression) {
+                let key = expression_key(expression);
+                if keys.insert(key.clone()) {
+                    let num_scopes = num_scopes.entry(key.clone()).or_default();
+                    if *num_scopes == 0 {
+                        expressions.push((key, expression.clone()));
+                    }
+                    *num_scopes += 1;
+                }
+            }
+        }
+    }
+    expressions
+        .into_iter()
+        .filter(|(key, _)| num_scopes[key] > 1)
+        .collect()
+}
+
+/// Return non-leaf sub-expressions in post-order.
+fn sub_expressions<F>(expression: &Expression<F>) -> Vec<&Expression<F>> {
+    let children = match expression {
+        Expression::Negated(value) | Expression::Scaled(value, _) => vec![value.as_ref()],
+        Expression::Sum(lhs, rhs) | Expression::Product(lhs, rhs) => {
+            vec![lhs.as_ref(), rhs.as_ref()]
+        }
+        _ => return Vec::new(),
+    };
+    chain![children.into_iter().flat_map(sub_expressions), [expression]].collect()
+}
+
+fn is_leaf<F>(expression: &Expression<F>) -> bool {
+    !matches!(
+        expression,
+        Expression::Negated(_)
+            | Expression::Sum(_, _)
+            | Expression::Product(_, _)
+            | Expression::Scaled(_, _)
+    )
+}
+
+/// Return a key of expression identifying it structurally, regardless of memory layout.
+fn expression_key<F: PrimeField<Repr = [u8; 0x20]>>(expression: &Expression<F>) -> String {
+    evaluate(
+        expression,
+        &u256_string,
+        &fixed_eval_var,
+        &advice_eval_var,
+        &instance_eval_var,
+        &|challenge| format!("c_{}", challenge.index()),
+        &|var| format!("sub(r, {var})"),
+        &|lhs, rhs| format!("addmod({lhs}, {rhs}, r)"),
+        &|lhs, rhs| format!("mulmod({lhs}, {rhs}, r)"),
+        &|var, scalar| format!("mulmod({var}, {}, r)", u256_string(scalar)),
+    )
+}
+
+/// Return expression simplified by folding constants, dropping additions of zero and
+/// multiplications by one, and turning products by constant into [`Expression::Scaled`].
+pub(crate) fn simplify<F: PrimeField>(expression: &Expression<F>) -> Expression<F> {
+    match expression {
+        Expression::Negated(value) => negated(simplify(value)),
+        Expression::Sum(lhs, rhs) => match (simplify(lhs), simplify(rhs)) {
+            (Expression::Constant(lhs), Expression::Constant(rhs)) => {
+                Expression::Constant(lhs + rhs)
+            }
+            (Expression::Constant(zero), value) | (value, Expression::Constant(zero))
+                if zero == F::ZERO =>
+            {
+                value
+            }
+            (lhs, rhs) => Expression::Sum(Box::new(lhs), Box::new(rhs)),
+        },
+        Expression::Product(lhs, rhs) => match (simplify(lhs), simplify(rhs)) {
+            (Expression::Constant(lhs), Expression::Constant(rhs)) => {
+                Expression::Constant(lhs * rhs)
+            }
+            (Expression::Constant(scalar), value) | (value, Expression::Constant(scalar)) => {
+                scaled(value, scalar)
+            }
+            (lhs, rhs) => Expression::Product(Box::new(lhs), Box::new(rhs)),
+        },
+        Expression::Scaled(value, scalar) => scaled(simplify(value), *scalar),
+        _ => expression.clone(),
+    }
+}
+
+fn negated<F: PrimeField>(value: Expression<F>) -> Expression<F> {
+    match value {
+        Expression::Constant(value) => Expression::Constant(-value),
+        Expression::Negated(value) => *value,
+        Expression::Scaled(value, scalar) => scaled(*value, -scalar),
+        value => Expression::Negated(Box::new(value)),
+    }
+}
+
+fn scaled<F: PrimeField>(value: Expression<F>, scalar: F) -> Expression<F> {
+    if scalar == F::ZERO {
+        return Expression::Constant(F::ZERO);
+    }
+    if scalar == F::ONE {
+        return value;
+    }
+    match value {
+        Expression::Constant(value) => Expression::Constant(value * scalar),
+        Expression::Negated(value) => scaled(*value, -scalar),
+        Expression::Scaled(value, inner) => scaled(*value, inner * scalar),
+        value if scalar == -F::ONE => Expression::Negated(Box::new(value)),
+        value => Expression::Scaled(Box::new(value), scalar),
+    }
+}
+
 fn u256_string(value: U256) -> String {
     if value.bit_len() < 64 {
         format!("0x{:x}", value.as_limbs()[0])
@@ -334,6 +711,22 @@ fn fixed_eval_var(fixed_query: FixedQuery) -> String {
     }
 }
 
+fn instance_eval_var(instance_query: InstanceQuery) -> String {
+    let column_index = instance_query.column_index();
+    let rotation = instance_query.rotation().0;
+    match rotation.cmp(&0) {
+        Ordering::Less => {
+            format!("i_eval_{}_prev_{}", column_index, rotation.abs())
+        }
+        Ordering::Equal => {
+            format!("i_eval_{}", column_index)
+        }
+        Ordering::Greater => {
+            format!("i_eval_{}_next_{}", column_index, rotation)
+        }
+    }
+}
+
 fn advice_eval_var(advice_query: AdviceQuery) -> String {
     let column_index = advice_query.column_index();
     let rotation = advice_query.rotation().0;
@@ -0,0 +1,388 @@
+use crate::codegen::{
+    code_size::{yul_tokens, YulToken},
+    pcs::{point_sets, queries, rotation_sets},
+    util::{Data, Ptr},
+    BatchOpenScheme::{Bdfg21, Gwc19},
+    SolidityGenerator, Transcript, TranscriptHash,
+};
+#[cfg(feature = "poseidon")]
+use crate::transcript::PoseidonSpec;
+#[cfg(feature = "poseidon")]
+use halo2_proofs::halo2curves::bn256;
+use itertools::chain;
+
+pub(crate) const TX_BASE_GAS: u64 = 21000;
+const CALLDATA_ZERO_BYTE_GAS: u64 = 4;
+pub(crate) const CALLDATA_NON_ZERO_BYTE_GAS: u64 = 16;
+
+/// Gas of `STATICCALL` to precompile, which is always warm by EIP-2929.
+const PRECOMPILE_CALL_GAS: u64 = 100;
+const EC_ADD_GAS: u64 = 150;
+const EC_MUL_GAS: u64 = 6000;
+/// Gas of `modexp` with 32-byte base, modulus and exponent `r - 2` by EIP-2565.
+const MODEXP_GAS: u64 = 1349;
+/// Gas of `modexp` with 32-byte base, modulus and exponent `(q + 1) / 4` by EIP-2565.
+const DECOMPRESS_MODEXP_GAS: u64 = 1338;
+const PAIRING_BASE_GAS: u64 = 45000;
+const PAIRING_PER_PAIR_GAS: u64 = 34000;
+/// Gas of `blake2f` with 12 rounds by EIP-152.
+const BLAKE2B_COMPRESS_GAS: u64 = 12;
+
+const KECCAK_BASE_GAS: u64 = 30;
+const KECCAK_WORD_GAS: u64 = 6;
+const MEMORY_WORD_GAS: u64 = 3;
+
+// Rough gas of opcodes executed by parts of `Halo2Verifier.sol` not generated per circuit,
+// estimated by counting opcodes of the template.
+const BASE_EXECUTION_GAS: u64 = 2000;
+const VK_WORD_GAS: u64 = 9;
+const INSTANCE_GAS: u64 = 100;
+const EC_POINT_GAS: u64 = 220;
+const COMPRESSED_EC_POINT_GAS: u64 = 180;
+const EVAL_GAS: u64 = 100;
+const SQUEEZE_GAS: u64 = 120;
+const X_N_SQUARING_GAS: u64 = 45;
+const LAGRANGE_GAS: u64 = 130;
+const BATCH_INVERT_GAS: u64 = 160;
+const QUOTIENT_COMM_GAS: u64 = 200;
+const ACC_LIMB_GAS: u64 = 200;
+const ACC_GAS: u64 = 1000;
+const PUBLIC_INPUT_HASH_GAS: u64 = 200;
+const PUBLIC_INPUT_HASH_LIMB_GAS: u64 = 60;
+const BLAKE2B_SCALAR_GAS: u64 = 300;
+const BLAKE2B_EC_POINT_GAS: u64 = 450;
+const BLAKE2B_SQUEEZE_GAS: u64 = 400;
+const BLAKE2B_BLOCK_GAS: u64 = 300;
+const POSEIDON_SCALAR_GAS: u64 = 150;
+const POSEIDON_SBOX_GAS: u64 = 60;
+const FUNCTION_CALL_GAS: u64 = 80;
+
+/// Static gas estimate of `verifyProof` of `Halo2Verifier.sol` rendered by
+/// [`SolidityGenerator::render`], returned by [`SolidityGenerator::estimate_gas`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GasEstimate {
+    /// Intrinsic gas of transaction including calldata, assuming proof and instances are random
+    /// field elements except accumulator limbs, excluding public data if
+    /// [`SolidityGenerator::set_public_input_hash`] is set.
+    pub intrinsic: u64,
+    /// Gas of `ecAdd` precompile calls.
+    pub ec_add: u64,
+    /// Gas of `ecMul` precompile calls.
+    pub ec_mul: u64,
+    /// Gas of `modexp` precompile calls for batch inversion and decompression of points.
+    pub modexp: u64,
+    /// Gas of `ecPairing` precompile call.
+    pub pairing: u64,
+    /// Gas of `keccak256` for random linear combination with accumulator, excluding hashing
+    /// public data.
+    pub keccak: u64,
+    /// Gas of hashing for transcript set by [`SolidityGenerator::set_transcript_hash`], see
+    /// [`EvmTranscriptSpec::yul_hash_gas`](crate::EvmTranscriptSpec::yul_hash_gas).
+    pub transcript_hash: u64,
+    /// Gas of `blake2f` precompile calls for transcript when [`TranscriptHash::Blake2b`] is set.
+    pub blake2b: u64,
+    /// Gas of memory expansion, excluding copying public data.
+    pub memory: u64,
+    /// Gas of the rest of executed opcodes, roughly estimated by counting opcodes.
+    pub execution: u64,
+}
+
+impl GasEstimate {
+    /// Return total estimated gas.
+    pub fn total(&self) -> u64 {
+        self.intrinsic
+            + self.ec_add
+            + self.ec_mul
+            + self.modexp
+            + self.pairing
+            + self.keccak
+            + self.transcript_hash
+            + self.blake2b
+            + self.memory
+            + self.execution
+    }
+}
+
+impl<'a> SolidityGenerator<'a> {
+    /// Return static gas estimate of `verifyProof` of `Halo2Verifier.sol` rendered by
+    /// [`SolidityGenerator::render`], without compiling or executing it.
+    ///
+    /// Precompile calls, keccak words, calldata bytes and memory expansion are counted from the
+    /// constraint system, and the rest is estimated roughly, so the total is expected to be
+    /// close to but not exactly the `gas_used`.
+    ///
+    /// When [`SolidityGenerator::set_public_input_hash`] is set, the cost of public data is not
+    /// included since its length is unknown, which is roughly 16 gas per byte of calldata and 12
+    /// gas per word for copying and hashing it.
+    pub fn estimate_gas(&self) -> GasEstimate {
+        let meta = &self.meta;
+        let verifier = self.generate_verifier(false);
+        let vk = verifier.vk.as_ref().unwrap();
+        let data = Data::new(
+            meta,
+            vk,
+            verifier.vk_mptr,
+            verifier.proof_cptr,
+            self.scheme,
+            self.proof_encoding(),
+        );
+        let queries = queries(meta, &data);
+
+        let num_instances = self.num_instances.iter().sum::<usize>() as u64;
+        let num_advices = meta.num_advices();
+        let num_challenges = meta.num_challenges();
+        let num_evals = meta.num_evals as u64;
+        let num_quotients = meta.num_quotients as u64;
+        let num_acc_limbs = self
+            .acc_encoding
+            .map(|acc_encoding| 4 * acc_encoding.num_limbs as u64)
+            .unwrap_or_default();
+
+        let intrinsic = {
+            let word_gas = |num_non_zero_bytes: u64| {
+                num_non_zero_bytes * CALLDATA_NON_ZERO_BYTE_GAS
+                    + (32 - num_non_zero_bytes) * CALLDATA_ZERO_BYTE_GAS
+            };
+            let acc_limb_word_gas = self
+                .acc_encoding
+                .map(|acc_encoding| word_gas((acc_encoding.num_limb_bits as u64 + 7) / 8))
+                .unwrap_or_default();
+            let num_proof_words = self.proof_len() as u64 / 0x20;
+            let num_dynamic_args = if self.public_input_hash.is_some() {
+                3
+            } else {
+                2
+            };
+            TX_BASE_GAS
+                + 4 * CALLDATA_NON_ZERO_BYTE_GAS
+                // Offsets and lengths of proof, instances and optional public data
+                + 2 * num_dynamic_args * word_gas(2)
+                + (num_proof_words + num_instances - num_acc_limbs) * word_gas(32)
+                + num_acc_limbs * acc_limb_word_gas
+        };
+
+        // (num_ec_add_and_mul, num_batch_inverted) of PCS computations
+        let (num_pcs_ec_ops, num_pcs_batch_inverted) = match self.scheme {
+            Bdfg21 => {
+                let (_, sets) = rotation_sets(&queries);
+                let num_comms = sets.iter().map(|set| set.comms().len()).sum::<usize>();
+                let num_coeffs = sets.iter().map(|set| set.rots().len()).sum::<usize>();
+                (num_comms + 2, 1 + num_coeffs + sets.len())
+            }
+            Gwc19 => {
+                let sets = point_sets(&queries);
+                let num_queries = sets.iter().map(|(_, queries)| queries.len()).sum::<usize>();
+                (num_queries + 2 * sets.len() - 1, 0)
+            }
+        };
+        let num_ec_ops = (num_quotients - 1)
+            + num_pcs_ec_ops as u64
+            + if self.acc_encoding.is_some() { 2 } else { 0 };
+        let ec_add = num_ec_ops * (PRECOMPILE_CALL_GAS + EC_ADD_GAS);
+        let ec_mul = num_ec_ops * (PRECOMPILE_CALL_GAS + EC_MUL_GAS);
+
+        let num_lagranges = {
+            let num_lagranges = self.num_instances.iter().copied().max().unwrap_or_default();
+            (num_lagranges.max(1) + meta.num_trailing_lagranges() + meta.num_neg_lagranges()) as u64
+        };
+        let num_batch_inverted = num_lagranges + 1 + num_pcs_batch_inverted as u64;
+        let num_modexps = match self.scheme {
+            Bdfg21 => 3,
+            Gwc19 => 1,
+        };
+        let num_ec_points = meta.num_proof_ec_points(self.scheme) as u64;
+        let compressed_points = self.proof_encoding().compressed_points();
+        let modexp = num_modexps * (PRECOMPILE_CALL_GAS + MODEXP_GAS)
+            + if compressed_points {
+                num_ec_points * (PRECOMPILE_CALL_GAS + DECOMPRESS_MODEXP_GAS)
+            } else {
+                0
+            };
+
+        let pairing = PRECOMPILE_CALL_GAS + PAIRING_BASE_GAS + 2 * PAIRING_PER_PAIR_GAS;
+
+        let blake2b_transcript =
+            self.transcript.little_endian_hash() == Some(TranscriptHash::Blake2b);
+
+        // Words hashed by each squeeze of transcript
+        let transcript_words = chain![
+            num_advices
+                .iter()
+                .zip(&num_challenges)
+                .enumerate()
+                .flat_map(|(phase, (num_advices, num_challenges))| {
+                    let num_instances = if phase == 0 { num_instances } else { 0 };
+                    chain![
+                        [1 + num_instances + 2 * *num_advices as u64],
+                        vec![2; num_challenges - 1]
+                    ]
+                }),
+            match self.scheme {
+                Bdfg21 => vec![1 + num_evals, 2, 3],
+                Gwc19 => vec![1 + num_evals, 1 + 2 * meta.num_rotations() as u64],
+            },
+        ]
+        .collect::<Vec<_>>();
+        let num_squeezes = transcript_words.len() as u64;
+        let keccak = chain![
+            self.acc_encoding.is_some().then_some(8),
+            self.public_input_hash.is_some().then_some(0),
+        ]
+        .map(|num_words| KECCAK_BASE_GAS + num_words * KECCAK_WORD_GAS)
+        .sum();
+
+        let transcript_hash = match self.transcript {
+            Transcript::Evm(fns) => transcript_words.iter().copied().map(fns.yul_hash_gas).sum(),
+            Transcript::LittleEndian(_) => 0,
+        };
+
+        // Blocks compressed when followed by more input, and the last block once per squeeze
+        let num_blake2b_compressions = if blake2b_transcript {
+            let num_bytes =
+                33 * (1 + num_instances + num_evals) + 65 * num_ec_points + num_squeezes;
+            (num_bytes - 1) / 128 + num_squeezes
+        } else {
+            0
+        };
+        let blake2b = num_blake2b_compressions * (PRECOMPILE_CALL_GAS + BLAKE2B_COMPRESS_GAS);
+
+        let memory = {
+            let word = |ptr: Ptr| ptr.value().as_usize() as u64 / 0x20;
+            let num_instance_evals = meta.instance_queries.len() as u64;
+            let x_n_mptr = word(verifier.theta_mptr) + num_instance_evals + 12;
+            let num_words = itertools::max([
+                // Scratch space of PCS computations
+                word(verifier.vk_mptr),
+                // Hashing instances and advice commitments of first phase
+                1 + num_instances + 2 * num_advices[0] as u64,
+                // Hashing evaluations
+                1 + num_evals,
+                // Batch inversion of lagrange evaluations
+                x_n_mptr + 2 * (num_lagranges + 1) + 4,
+                // End of `PAIRING_RHS_Y_MPTR`, followed by input of `modexp` decompressing points
+                x_n_mptr + 13 + if compressed_points { 6 } else { 0 },
+                // State and pending input of blake2b, or state of poseidon
+                match self.transcript.little_endian_hash() {
+                    Some(TranscriptHash::Blake2b) => 18,
+                    #[cfg(feature = "poseidon")]
+                    Some(TranscriptHash::Poseidon) => 5,
+                    None => 0,
+                },
+            ])
+            .unwrap();
+            MEMORY_WORD_GAS * num_words + num_words * num_words / 512
+        };
+
+        let execution = {
+            let ec_point_gas = if compressed_points {
+                EC_POINT_GAS + COMPRESSED_EC_POINT_GAS
+            } else {
+                EC_POINT_GAS
+            };
+            let transcript = match self.transcript.little_endian_hash() {
+                Some(TranscriptHash::Blake2b) => {
+                    (1 + num_instances + num_evals) * BLAKE2B_SCALAR_GAS
+                        + num_ec_points * BLAKE2B_EC_POINT_GAS
+                        + num_squeezes * BLAKE2B_SQUEEZE_GAS
+                        + num_blake2b_compressions * BLAKE2B_BLOCK_GAS
+                }
+                #[cfg(feature = "poseidon")]
+                Some(TranscriptHash::Poseidon) => {
+                    // Full chunks of rate are permuted when absorbing, and the rest once per squeeze
+                    let num_scalars = 1 + num_instances + 2 * num_ec_points + num_evals;
+                    let num_permutations = num_scalars / 4 + num_squeezes;
+                    num_scalars * POSEIDON_SCALAR_GAS
+                        + num_squeezes * SQUEEZE_GAS
+                        + num_permutations * poseidon_permutation_gas()
+                }
+                None => num_squeezes * SQUEEZE_GAS,
+            };
+            let k = self.vk.get_domain().k() as u64;
+            let acc = self
+                .acc_encoding
+                .map(|acc_encoding| ACC_GAS + acc_encoding.num_limbs as u64 * ACC_LIMB_GAS)
+                .unwrap_or_default();
+            let public_input_hash = self
+                .public_input_hash
+                .map(|public_input_hash| {
+                    PUBLIC_INPUT_HASH_GAS
+                        + public_input_hash.num_limbs as u64 * PUBLIC_INPUT_HASH_LIMB_GAS
+                })
+                .unwrap_or_default();
+            let generated = yul_gas(
+                chain![
+                    &verifier.instance_computations,
+                    verifier.quotient_eval_shared_computations.iter().flatten(),
+                    verifier.quotient_eval_numer_computations.iter().flatten(),
+                    verifier.pcs_computations.iter().flatten(),
+                ]
+                .map(String::as_str),
+            );
+            BASE_EXECUTION_GAS
+                + (vk.len() as u64 / 0x20) * VK_WORD_GAS
+                + num_instances * INSTANCE_GAS
+                + num_ec_points * ec_point_gas
+                + num_evals * EVAL_GAS
+                + transcript
+                + k * X_N_SQUARING_GAS
+                + num_lagranges * LAGRANGE_GAS
+                + num_batch_inverted * BATCH_INVERT_GAS
+                + (num_quotients - 1) * QUOTIENT_COMM_GAS
+                + acc
+                + public_input_hash
+                + generated
+        };
+
+        GasEstimate {
+            intrinsic,
+            ec_add,
+            ec_mul,
+            modexp,
+            pairing,
+            keccak,
+            transcript_hash,
+            blake2b,
+            memory,
+            execution,
+        }
+    }
+}
+
+/// Return rough gas of `poseidon_permutation` of `Halo2Verifier.sol`.
+#[cfg(feature = "poseidon")]
+fn poseidon_permutation_gas() -> u64 {
+    let spec = PoseidonSpec::<bn256::Fr>::new();
+    let permutation = spec.yul_permutation();
+    let num_sboxes = permutation
+        .iter()
+        .filter(|line| line.contains("poseidon_sbox"))
+        .count() as u64;
+    let num_mds = permutation
+        .iter()
+        .filter(|line| *line == "poseidon_mds(r)")
+        .count() as u64;
+    yul_gas(permutation.iter().map(String::as_str))
+        + num_sboxes * POSEIDON_SBOX_GAS
+        + num_mds * yul_gas(spec.yul_mds().iter().map(String::as_str))
+}
+
+/// Return rough gas of executing Yul lines once.
+fn yul_gas<'a>(lines: impl IntoIterator<Item = &'a str>) -> u64 {
+    lines
+        .into_iter()
+        .flat_map(yul_tokens)
+        .map(|token| match token {
+            YulToken::Builtin("addmod" | "mulmod") => 8,
+            // Counted separately
+            YulToken::Builtin("keccak256" | "staticcall") => 0,
+            YulToken::Builtin("gas" | "pop" | "calldatasize" | "returndatasize" | "address") => 2,
+            YulToken::Builtin(_) => 3,
+            YulToken::FunctionCall => FUNCTION_CALL_GAS,
+            YulToken::FunctionDefinition | YulToken::Keyword("let") => 0,
+            YulToken::Keyword("if" | "for") => 20,
+            YulToken::Keyword(_) => 3,
+            YulToken::Constant | YulToken::Variable | YulToken::Literal(_) => 3,
+            YulToken::Assignment => 5,
+        })
+        .sum()
+}
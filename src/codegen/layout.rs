@@ -0,0 +1,409 @@
+use crate::{
+    codegen::{
+        template::Halo2VerifyingKey,
+        util::{
+            fr_to_u256, g1_to_u256s, ConstraintSystemMeta, Data, EcPoint, ProofEncoding, Ptr, Word,
+        },
+        BatchOpenScheme::{self, Bdfg21, Gwc19},
+        SolidityGenerator, Transcript, TranscriptHash,
+    },
+    transcript::{EvmTranscriptSpec, Keccak256Spec, Keccak256Transcript},
+};
+use halo2_proofs::{
+    halo2curves::bn256,
+    plonk::VerifyingKey,
+    transcript::{
+        Blake2bRead, Challenge255, EncodedChallenge, TranscriptRead, TranscriptReadBuffer,
+    },
+};
+use itertools::{chain, Itertools};
+use ruint::aliases::U256;
+use std::{io, mem};
+
+/// Layout of proof of a circuit, which names every point and scalar in proof by its offset, so
+/// proof can be inspected without reverse-engineering byte offsets.
+#[derive(Debug)]
+pub struct ProofLayout {
+    meta: ConstraintSystemMeta,
+    scheme: BatchOpenScheme,
+    compressed_points: bool,
+    transcript: Transcript,
+}
+
+/// Point or scalar in proof, with its offset in bytes from the start of proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofField {
+    /// Point, which takes 64 bytes, or 32 bytes when compressed.
+    EcPoint(usize),
+    /// Scalar, which takes 32 bytes.
+    Scalar(usize),
+}
+
+impl ProofField {
+    /// Return offset in bytes from the start of proof.
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::EcPoint(offset) | Self::Scalar(offset) => *offset,
+        }
+    }
+
+    pub(crate) fn shifted(self, delta: usize) -> Self {
+        match self {
+            Self::EcPoint(offset) => Self::EcPoint(offset + delta),
+            Self::Scalar(offset) => Self::Scalar(offset + delta),
+        }
+    }
+}
+
+/// Proof decoded by [`ProofLayout::decode`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedProof {
+    /// Advice commitments of each phase, in order of column index.
+    pub advice_comms: Vec<Vec<bn256::G1Affine>>,
+    /// Permuted input and table commitments of each lookup.
+    pub lookup_permuted_comms: Vec<(bn256::G1Affine, bn256::G1Affine)>,
+    /// Grand product commitments of each permutation chunk.
+    pub permutation_z_comms: Vec<bn256::G1Affine>,
+    /// Grand product commitments of each lookup.
+    pub lookup_z_comms: Vec<bn256::G1Affine>,
+    /// Grand product commitments of each shuffle.
+    pub shuffle_z_comms: Vec<bn256::G1Affine>,
+    /// Commitment of the random polynomial of vanishing argument.
+    pub random_comm: bn256::G1Affine,
+    /// Commitments of each quotient chunk.
+    pub quotient_comms: Vec<bn256::G1Affine>,
+    /// Evaluations in proof order, named as in [`ProofLayout::fields`].
+    pub evals: Vec<(String, bn256::Fr)>,
+    /// `W` and `W'` with [`BatchOpenScheme::Bdfg21`], or `W` of each rotation with
+    /// [`BatchOpenScheme::Gwc19`].
+    pub ws: Vec<bn256::G1Affine>,
+}
+
+impl ProofLayout {
+    /// Return a new `ProofLayout` of proof of the circuit of `vk` batch opened by `scheme`.
+    pub fn new(vk: &VerifyingKey<bn256::G1Affine>, scheme: BatchOpenScheme) -> Self {
+        Self {
+            meta: ConstraintSystemMeta::new(vk.cs()),
+            scheme,
+            compressed_points: false,
+            transcript: Transcript::evm::<Keccak256Spec>(),
+        }
+    }
+
+    /// Set whether points in proof are compressed, same as
+    /// [`SolidityGenerator::set_compressed_points`]. Default is `false`.
+    pub fn set_compressed_points(mut self, compressed_points: bool) -> Self {
+        self.compressed_points = compressed_points;
+        self
+    }
+
+    /// Set [`EvmTranscriptSpec`] of the transcript the proof is created with, same as
+    /// [`SolidityGenerator::set_transcript_hash`]. Default is [`Keccak256Spec`].
+    pub fn set_transcript_hash<S: EvmTranscriptSpec>(mut self) -> Self {
+        self.transcript = Transcript::evm::<S>();
+        self
+    }
+
+    /// Set `TranscriptHash` of the transcript the proof is created with, same as
+    /// [`SolidityGenerator::set_little_endian_transcript_hash`].
+    pub fn set_little_endian_transcript_hash(mut self, transcript_hash: TranscriptHash) -> Self {
+        self.transcript = Transcript::LittleEndian(transcript_hash);
+        self
+    }
+
+    /// Return length of proof in bytes.
+    pub fn len(&self) -> usize {
+        self.meta
+            .proof_len(self.scheme, self.encoding().compressed_points())
+    }
+
+    /// Return whether proof is empty, which is never the case.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return every field of proof in proof order, named as the verifier names them, e.g.
+    /// `advice_comms[0]`, `lookup_permuted_comms[1].table`, `advice_evals[(2, -1)]` or `w_prime`.
+    pub fn fields(&self) -> Vec<(String, ProofField)> {
+        // Only offsets in proof are used, which don't depend on verifying key
+        let vk = Halo2VerifyingKey {
+            constants: Vec::new(),
+            fixed_comms: Vec::new(),
+            permutation_comms: Vec::new(),
+        };
+        let data = Data::new(
+            &self.meta,
+            &vk,
+            Ptr::memory(0),
+            Ptr::calldata(0),
+            self.scheme,
+            ProofEncoding::Uncompressed,
+        );
+        let named = |name: &str, idx: usize| format!("{name}[{idx}]");
+        let ec_point_offset = |ec_point: &EcPoint| ec_point.x().ptr().value().as_usize();
+        let word_offset = |word: &Word| word.ptr().value().as_usize();
+
+        let ec_points = chain![
+            data.advice_comms
+                .iter()
+                .enumerate()
+                .map(|(idx, comm)| (named("advice_comms", idx), comm)),
+            data.lookup_permuted_comms
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, (input, table))| [
+                    (format!("lookup_permuted_comms[{idx}].input"), input),
+                    (format!("lookup_permuted_comms[{idx}].table"), table),
+                ]),
+            data.permutation_z_comms
+                .iter()
+                .enumerate()
+                .map(|(idx, comm)| (named("permutation_z_comms", idx), comm)),
+            data.lookup_z_comms
+                .iter()
+                .enumerate()
+                .map(|(idx, comm)| (named("lookup_z_comms", idx), comm)),
+            data.shuffle_z_comms
+                .iter()
+                .enumerate()
+                .map(|(idx, comm)| (named("shuffle_z_comms", idx), comm)),
+            [("random_comm".to_string(), &data.random_comm)],
+        ]
+        .map(|(name, comm)| (name, ec_point_offset(comm)))
+        .chain(
+            EcPoint::range(data.quotient_comm_cptr)
+                .take(self.meta.num_quotients)
+                .enumerate()
+                .map(|(idx, comm)| (named("quotient_comms", idx), ec_point_offset(&comm))),
+        )
+        .sorted_by_key(|(_, offset)| *offset)
+        .map(|(name, offset)| (name, ProofField::EcPoint(offset)));
+
+        let eval_names = chain![
+            self.meta
+                .advice_queries
+                .iter()
+                .map(|query| format!("advice_evals[{query:?}]")),
+            self.meta
+                .fixed_queries
+                .iter()
+                .map(|query| format!("fixed_evals[{query:?}]")),
+            ["random_eval".to_string()],
+            (0..self.meta.num_permutations()).map(|idx| named("permutation_evals", idx)),
+            (0..self.meta.num_permutation_z_evals()).map(|idx| named("permutation_z_evals", idx)),
+            (0..5 * self.meta.num_lookups()).map(|idx| named("lookup_evals", idx)),
+            (0..2 * self.meta.num_shuffles()).map(|idx| named("shuffle_evals", idx)),
+        ];
+        let eval_cptr = data.quotient_comm_cptr + 2 * self.meta.num_quotients;
+        let evals = eval_names
+            .zip(Word::range(eval_cptr).take(self.meta.num_evals))
+            .map(|(name, eval)| (name, ProofField::Scalar(word_offset(&eval))));
+
+        let w_names = match self.scheme {
+            Bdfg21 => vec!["w".to_string(), "w_prime".to_string()],
+            Gwc19 => (0..self.meta.num_rotations())
+                .map(|idx| named("ws", idx))
+                .collect(),
+        };
+        let ws = w_names
+            .into_iter()
+            .zip(EcPoint::range(data.w_cptr))
+            .map(|(name, w)| (name, ProofField::EcPoint(ec_point_offset(&w))));
+
+        let fields = chain![ec_points, evals, ws];
+        if !self.encoding().compressed_points() {
+            return fields.collect();
+        }
+
+        // Every field takes 32 bytes when points are compressed
+        fields
+            .scan(0, |offset, (name, field)| {
+                let offset = mem::replace(offset, *offset + 0x20);
+                let field = match field {
+                    ProofField::EcPoint(_) => ProofField::EcPoint(offset),
+                    ProofField::Scalar(_) => ProofField::Scalar(offset),
+                };
+                Some((name, field))
+            })
+            .collect()
+    }
+
+    /// Decode `proof` into [`DecodedProof`], or return error naming the first field that is not
+    /// a valid point or scalar.
+    pub fn decode(&self, proof: &[u8]) -> io::Result<DecodedProof> {
+        if proof.len() != self.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Proof length {} is not {}", proof.len(), self.len()),
+            ));
+        }
+
+        // Only encoding matters for decoding, so any transcript reading the same encoding works
+        let fields = self.fields();
+        let (ec_points, evals) = match self.encoding() {
+            ProofEncoding::Uncompressed => read_fields(&fields, Keccak256Transcript::new(proof))?,
+            ProofEncoding::Compressed => {
+                let transcript = Keccak256Transcript::new(proof).set_compressed_points(true);
+                read_fields(&fields, transcript)?
+            }
+            ProofEncoding::LittleEndian => {
+                let transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+                read_fields(&fields, transcript)?
+            }
+        };
+
+        let meta = &self.meta;
+        let mut ec_points = ec_points.into_iter();
+        let mut take = |n: usize| ec_points.by_ref().take(n).collect_vec();
+        let advice_comms = meta
+            .num_user_advices
+            .iter()
+            .map(|num_advices| take(*num_advices))
+            .collect();
+        let lookup_permuted_comms = take(meta.num_lookup_permuteds)
+            .into_iter()
+            .tuples()
+            .collect();
+        let permutation_z_comms = take(meta.num_permutation_zs);
+        let lookup_z_comms = take(meta.num_lookup_zs);
+        let shuffle_z_comms = take(meta.num_shuffle_zs);
+        let random_comm = take(1)[0];
+        let quotient_comms = take(meta.num_quotients);
+        let ws = ec_points.collect();
+        Ok(DecodedProof {
+            advice_comms,
+            lookup_permuted_comms,
+            permutation_z_comms,
+            lookup_z_comms,
+            shuffle_z_comms,
+            random_comm,
+            quotient_comms,
+            evals,
+            ws,
+        })
+    }
+
+    /// Return JSON of [`ProofLayout::fields`], as an array of objects with `name`, `type` either
+    /// `ec_point` or `scalar`, and `offset` in bytes from the start of proof.
+    pub fn to_json(&self) -> String {
+        let entries = self
+            .fields()
+            .into_iter()
+            .map(|(name, field)| {
+                let ty = match field {
+                    ProofField::EcPoint(_) => "ec_point",
+                    ProofField::Scalar(_) => "scalar",
+                };
+                format!(
+                    r#"  {{"name":"{name}","type":"{ty}","offset":{}}}"#,
+                    field.offset()
+                )
+            })
+            .join(",\n");
+        format!("[\n{entries}\n]\n")
+    }
+
+    fn encoding(&self) -> ProofEncoding {
+        ProofEncoding::new(self.transcript, self.compressed_points)
+    }
+}
+
+impl DecodedProof {
+    /// Return JSON of `DecodedProof` with the same keys as its fields, where points are objects
+    /// with `x` and `y`, identity as `(0, 0)`, and evaluations are an object keyed by name, all
+    /// in big-endian hex.
+    pub fn to_json(&self) -> String {
+        let hex = |value: U256| format!(r#""0x{value:064x}""#);
+        let point = |ec_point: &bn256::G1Affine| {
+            let [x, y] = g1_to_u256s(ec_point);
+            format!(r#"{{"x":{},"y":{}}}"#, hex(x), hex(y))
+        };
+        let points = |ec_points: &[bn256::G1Affine]| {
+            format!("[{}]", ec_points.iter().map(point).format(","))
+        };
+        let entries = [
+            (
+                "advice_comms",
+                format!(
+                    "[{}]",
+                    self.advice_comms
+                        .iter()
+                        .map(|comms| points(comms))
+                        .format(",")
+                ),
+            ),
+            (
+                "lookup_permuted_comms",
+                format!(
+                    "[{}]",
+                    self.lookup_permuted_comms
+                        .iter()
+                        .map(|(input, table)| format!(
+                            r#"{{"input":{},"table":{}}}"#,
+                            point(input),
+                            point(table)
+                        ))
+                        .format(",")
+                ),
+            ),
+            ("permutation_z_comms", points(&self.permutation_z_comms)),
+            ("lookup_z_comms", points(&self.lookup_z_comms)),
+            ("shuffle_z_comms", points(&self.shuffle_z_comms)),
+            ("random_comm", point(&self.random_comm)),
+            ("quotient_comms", points(&self.quotient_comms)),
+            (
+                "evals",
+                format!(
+                    "{{{}}}",
+                    self.evals
+                        .iter()
+                        .map(|(name, eval)| format!(r#""{name}":{}"#, hex(fr_to_u256(eval))))
+                        .format(",")
+                ),
+            ),
+            ("ws", points(&self.ws)),
+        ]
+        .map(|(key, value)| format!(r#"  "{key}":{value}"#));
+        format!("{{\n{}\n}}\n", entries.iter().join(",\n"))
+    }
+}
+
+impl<'a> SolidityGenerator<'a> {
+    /// Return [`ProofLayout`] of proof verified by the generated verifier, with the same
+    /// [`SolidityGenerator::set_compressed_points`] and transcript.
+    pub fn proof_layout(&self) -> ProofLayout {
+        ProofLayout {
+            transcript: self.transcript,
+            ..ProofLayout::new(self.vk, self.scheme).set_compressed_points(self.compressed_points)
+        }
+    }
+}
+
+/// Read every field in proof order from `transcript`, and return points and named evaluations.
+#[allow(clippy::type_complexity)]
+fn read_fields<E, T>(
+    fields: &[(String, ProofField)],
+    mut transcript: T,
+) -> io::Result<(Vec<bn256::G1Affine>, Vec<(String, bn256::Fr)>)>
+where
+    E: EncodedChallenge<bn256::G1Affine>,
+    T: TranscriptRead<bn256::G1Affine, E>,
+{
+    let mut ec_points = Vec::new();
+    let mut evals = Vec::new();
+    for (name, field) in fields {
+        let with_name = |err: io::Error| {
+            io::Error::new(
+                err.kind(),
+                format!("{name} at 0x{:x}: {err}", field.offset()),
+            )
+        };
+        match field {
+            ProofField::EcPoint(_) => ec_points.push(transcript.read_point().map_err(with_name)?),
+            ProofField::Scalar(_) => {
+                evals.push((name.clone(), transcript.read_scalar().map_err(with_name)?))
+            }
+        }
+    }
+    Ok((ec_points, evals))
+}
@@ -60,6 +60,9 @@ pub(crate) fn queries(meta: &ConstraintSystemMeta, data: &Data) -> Vec<Query> {
                 Query::new(z_comm, 1, evals.1),
             ]
         }),
+        izip!(&data.shuffle_z_comms, &data.shuffle_evals).flat_map(|(&comm, evals)| {
+            [Query::new(comm, 0, evals.0), Query::new(comm, 1, evals.1)]
+        }),
         meta.fixed_queries.iter().map(|query| {
             let comm = data.fixed_comms[query.0];
             let eval = data.fixed_evals[query];
@@ -157,11 +160,60 @@ pub(crate) fn rotation_sets(queries: &[Query]) -> (BTreeSet<i32>, Vec<RotationSe
     (superset, sets)
 }
 
+pub(crate) fn point_sets(queries: &[Query]) -> Vec<(i32, Vec<&Query>)> {
+    queries
+        .iter()
+        .fold(Vec::<(i32, Vec<&Query>)>::new(), |mut sets, query| {
+            if let Some(pos) = sets.iter().position(|(rot, _)| *rot == query.rot) {
+                sets[pos].1.push(query);
+            } else {
+                sets.push((query.rot, vec![query]));
+            }
+            sets
+        })
+}
+
+fn point_computations(points: &BTreeMap<i32, Word>) -> Vec<String> {
+    let min_rot = *points.first_key_value().unwrap().0;
+    let max_rot = *points.last_key_value().unwrap().0;
+    chain![
+        [
+            "let x := mload(X_MPTR)",
+            "let omega := mload(OMEGA_MPTR)",
+            "let omega_inv := mload(OMEGA_INV_MPTR)",
+            "let x_pow_of_omega := mulmod(x, omega, r)"
+        ]
+        .map(str::to_string),
+        (1..=max_rot).flat_map(|rot| {
+            chain![
+                points
+                    .get(&rot)
+                    .map(|point| format!("mstore({}, x_pow_of_omega)", point.ptr())),
+                (rot != max_rot)
+                    .then(|| { "x_pow_of_omega := mulmod(x_pow_of_omega, omega, r)".to_string() })
+            ]
+        }),
+        [
+            format!("mstore({}, x)", points[&0].ptr()),
+            format!("x_pow_of_omega := mulmod(x, omega_inv, r)")
+        ],
+        (min_rot..0).rev().flat_map(|rot| {
+            chain![
+                points
+                    .get(&rot)
+                    .map(|point| format!("mstore({}, x_pow_of_omega)", point.ptr())),
+                (rot != min_rot).then(|| {
+                    "x_pow_of_omega := mulmod(x_pow_of_omega, omega_inv, r)".to_string()
+                })
+            ]
+        })
+    ]
+    .collect_vec()
+}
+
 pub(crate) fn bdfg21_computations(meta: &ConstraintSystemMeta, data: &Data) -> Vec<Vec<String>> {
     let queries = queries(meta, data);
     let (superset, sets) = rotation_sets(&queries);
-    let min_rot = *superset.first().unwrap();
-    let max_rot = *superset.last().unwrap();
     let num_coeffs = sets.iter().map(|set| set.rots().len()).sum::<usize>();
 
     let w = EcPoint::from(data.w_cptr);
@@ -190,7 +242,8 @@ pub(crate) fn bdfg21_computations(meta: &ConstraintSystemMeta, data: &Data) -> V
 
     let point_vars =
         izip!(&superset, (0..).map(|idx| format!("point_{idx}"))).collect::<BTreeMap<_, _>>();
-    let points = izip!(&superset, Word::range(point_mptr)).collect::<BTreeMap<_, _>>();
+    let points =
+        izip!(superset.iter().copied(), Word::range(point_mptr)).collect::<BTreeMap<_, _>>();
     let mu_minus_points =
         izip!(&superset, Word::range(mu_minus_point_mptr)).collect::<BTreeMap<_, _>>();
     let vanishing_0 = Word::from(vanishing_0_mptr);
@@ -198,39 +251,7 @@ pub(crate) fn bdfg21_computations(meta: &ConstraintSystemMeta, data: &Data) -> V
     let r_evals = Word::range(r_eval_mptr).take(sets.len()).collect_vec();
     let sums = Word::range(sum_mptr).take(sets.len()).collect_vec();
 
-    let point_computations = chain![
-        [
-            "let x := mload(X_MPTR)",
-            "let omega := mload(OMEGA_MPTR)",
-            "let omega_inv := mload(OMEGA_INV_MPTR)",
-            "let x_pow_of_omega := mulmod(x, omega, r)"
-        ]
-        .map(str::to_string),
-        (1..=max_rot).flat_map(|rot| {
-            chain![
-                points
-                    .get(&rot)
-                    .map(|point| format!("mstore({}, x_pow_of_omega)", point.ptr())),
-                (rot != max_rot)
-                    .then(|| { "x_pow_of_omega := mulmod(x_pow_of_omega, omega, r)".to_string() })
-            ]
-        }),
-        [
-            format!("mstore({}, x)", points[&0].ptr()),
-            format!("x_pow_of_omega := mulmod(x, omega_inv, r)")
-        ],
-        (min_rot..0).rev().flat_map(|rot| {
-            chain![
-                points
-                    .get(&rot)
-                    .map(|point| format!("mstore({}, x_pow_of_omega)", point.ptr())),
-                (rot != min_rot).then(|| {
-                    "x_pow_of_omega := mulmod(x_pow_of_omega, omega_inv, r)".to_string()
-                })
-            ]
-        })
-    ]
-    .collect_vec();
+    let point_computations = point_computations(&points);
 
     let vanishing_computations = chain![
         ["let mu := mload(MU_MPTR)".to_string()],
@@ -590,3 +611,127 @@ pub(crate) fn bdfg21_computations(meta: &ConstraintSystemMeta, data: &Data) -> V
     ]
     .collect_vec()
 }
+
+pub(crate) fn gwc19_computations(meta: &ConstraintSystemMeta, data: &Data) -> Vec<Vec<String>> {
+    let queries = queries(meta, data);
+    let sets = point_sets(&queries);
+
+    let ws = EcPoint::range(data.w_cptr).take(sets.len()).collect_vec();
+
+    let point_mptr = Ptr::memory(0x100);
+    let points = izip!(sets.iter().map(|(rot, _)| *rot), Word::range(point_mptr))
+        .collect::<BTreeMap<_, _>>();
+
+    let point_computations = point_computations(&points);
+
+    let eval_computations = chain![
+        [
+            "let v := mload(V_MPTR)",
+            "let u := mload(U_MPTR)",
+            "let eval",
+            "let batch"
+        ]
+        .map(str::to_string),
+        izip!(0.., sets.iter().rev()).flat_map(|(set_idx, (_, queries))| {
+            let (last_query, rest_queries) = queries.split_last().unwrap();
+            chain![
+                [format!("batch := {}", last_query.eval)],
+                rest_queries.iter().rev().map(|query| {
+                    format!("batch := addmod(mulmod(batch, v, r), {}, r)", query.eval)
+                }),
+                [if set_idx == 0 {
+                    format!("eval := batch")
+                } else {
+                    format!("eval := addmod(mulmod(eval, u, r), batch, r)")
+                }],
+            ]
+        }),
+        ["mstore(R_EVAL_MPTR, eval)".to_string()],
+    ]
+    .collect_vec();
+
+    let pairing_rhs_computations = {
+        let w_last = ws.last().unwrap();
+        chain![
+            [
+                format!("let u := mload(U_MPTR)"),
+                format!("mstore(0x00, {})", w_last.x()),
+                format!("mstore(0x20, {})", w_last.y()),
+            ],
+            ws.iter().rev().skip(1).flat_map(|w| {
+                [
+                    format!("success := ec_mul_acc(success, u)"),
+                    format!("success := ec_add_acc(success, {}, {})", w.x(), w.y()),
+                ]
+            }),
+            [
+                format!("mstore(PAIRING_RHS_X_MPTR, mload(0x00))"),
+                format!("mstore(PAIRING_RHS_Y_MPTR, mload(0x20))"),
+            ],
+        ]
+        .collect_vec()
+    };
+
+    let pairing_lhs_computations =
+        izip!(0.., sets.iter().rev(), ws.iter().rev()).map(|(set_idx, (rot, queries), w)| {
+            let is_first_set = set_idx == 0;
+
+            let ec_add = &format!("ec_add_{}", if is_first_set { "acc" } else { "tmp" });
+            let ec_mul = &format!("ec_mul_{}", if is_first_set { "acc" } else { "tmp" });
+            let batch_x = Ptr::memory(0x00) + if is_first_set { 0 } else { 4 };
+            let batch_y = batch_x + 1;
+
+            let (last_query, rest_queries) = queries.split_last().unwrap();
+            chain![
+                [
+                    format!("let v := mload(V_MPTR)"),
+                    format!("mstore({batch_x}, {})", last_query.comm.x()),
+                    format!("mstore({batch_y}, {})", last_query.comm.y()),
+                ],
+                rest_queries.iter().rev().flat_map(|query| {
+                    let (x, y) = (query.comm.x(), query.comm.y());
+                    [
+                        format!("success := {ec_mul}(success, v)"),
+                        format!("success := {ec_add}(success, {x}, {y})"),
+                    ]
+                }),
+                (!is_first_set)
+                    .then(|| {
+                        [
+                            format!("success := ec_mul_acc(success, mload(U_MPTR))"),
+                            format!("success := ec_add_acc(success, mload(0x80), mload(0xa0))"),
+                        ]
+                    })
+                    .into_iter()
+                    .flatten(),
+                [
+                    format!("mstore(0x80, {})", w.x()),
+                    format!("mstore(0xa0, {})", w.y()),
+                    format!("success := ec_mul_tmp(success, {})", points[rot]),
+                    format!("success := ec_add_acc(success, mload(0x80), mload(0xa0))"),
+                ],
+            ]
+            .collect_vec()
+        });
+
+    let pairing_lhs_finalization = [
+        format!("mstore(0x80, mload(G1_X_MPTR))"),
+        format!("mstore(0xa0, mload(G1_Y_MPTR))"),
+        format!("success := ec_mul_tmp(success, sub(r, mload(R_EVAL_MPTR)))"),
+        format!("success := ec_add_acc(success, mload(0x80), mload(0xa0))"),
+        format!("mstore(PAIRING_LHS_X_MPTR, mload(0x00))"),
+        format!("mstore(PAIRING_LHS_Y_MPTR, mload(0x20))"),
+    ]
+    .to_vec();
+
+    chain![
+        [
+            point_computations,
+            eval_computations,
+            pairing_rhs_computations
+        ],
+        pairing_lhs_computations,
+        [pairing_lhs_finalization],
+    ]
+    .collect_vec()
+}
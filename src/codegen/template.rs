@@ -1,20 +1,34 @@
 use crate::codegen::{
+    abi::{self, CustomError, Function},
     pcs::BatchOpenScheme::{self, Bdfg21, Gwc19},
     util::Ptr,
+    PublicInputHash, Transcript, TranscriptHash,
 };
+#[cfg(feature = "poseidon")]
+use crate::transcript::PoseidonSpec;
 use askama::{Error, Template};
+#[cfg(feature = "poseidon")]
+use halo2_proofs::halo2curves::bn256;
 use ruint::aliases::U256;
 use std::fmt;
+use HelperKind::{Pcs, Quotient};
 
 #[derive(Template)]
 #[template(path = "Halo2VerifyingKey.sol")]
 pub(crate) struct Halo2VerifyingKey {
-    pub(crate) constants: Vec<(&'static str, U256)>,
+    pub(crate) constants: Vec<(String, U256)>,
     pub(crate) fixed_comms: Vec<(U256, U256)>,
     pub(crate) permutation_comms: Vec<(U256, U256)>,
 }
 
 impl Halo2VerifyingKey {
+    pub(crate) fn constant(&self, name: &str) -> U256 {
+        self.constants
+            .iter()
+            .find_map(|(key, value)| (key == name).then_some(*value))
+            .unwrap()
+    }
+
     pub(crate) fn len(&self) -> usize {
         (self.constants.len() * 0x20)
             + (self.fixed_comms.len() + self.permutation_comms.len()) * 0x40
@@ -25,21 +39,194 @@ impl Halo2VerifyingKey {
 #[template(path = "Halo2Verifier.sol")]
 pub(crate) struct Halo2Verifier {
     pub(crate) scheme: BatchOpenScheme,
+    pub(crate) custom_errors: bool,
+    pub(crate) errors: Vec<CustomError>,
+    pub(crate) non_reverting: bool,
     pub(crate) vk: Option<Halo2VerifyingKey>,
     pub(crate) vk_len: usize,
+    pub(crate) compressed_points: bool,
+    pub(crate) transcript: Transcript,
     pub(crate) proof_len: usize,
     pub(crate) vk_mptr: Ptr,
     pub(crate) challenge_mptr: Ptr,
+    pub(crate) ec_point_mptr: Ptr,
     pub(crate) theta_mptr: Ptr,
     pub(crate) proof_cptr: Ptr,
     pub(crate) quotient_comm_cptr: Ptr,
+    pub(crate) eval_cptr: Ptr,
     pub(crate) num_neg_lagranges: usize,
+    pub(crate) num_trailing_lagranges: usize,
+    pub(crate) num_blinding_factors: usize,
     pub(crate) num_advices: Vec<usize>,
     pub(crate) num_challenges: Vec<usize>,
+    pub(crate) num_instance_columns: usize,
+    pub(crate) num_instance_evals: usize,
     pub(crate) num_evals: usize,
     pub(crate) num_quotients: usize,
+    pub(crate) num_rotations: usize,
+    pub(crate) num_instances: usize,
+    pub(crate) public_input_hash: Option<PublicInputHash>,
+    pub(crate) instance_computations: Vec<String>,
+    pub(crate) quotient_eval_shared_computations: Vec<Vec<String>>,
     pub(crate) quotient_eval_numer_computations: Vec<Vec<String>>,
     pub(crate) pcs_computations: Vec<Vec<String>>,
+    pub(crate) num_quotient_helpers: usize,
+    pub(crate) pcs_helper: bool,
+}
+
+impl Halo2Verifier {
+    pub(crate) fn num_helpers(&self) -> usize {
+        self.num_quotient_helpers + usize::from(self.pcs_helper)
+    }
+
+    /// Return `verifyProof`, `pairingInput` and `verifyProofs` of the verifier.
+    pub(crate) fn functions(&self) -> [Function; 3] {
+        abi::functions(self.vk.is_none(), self.public_input_hash.is_some())
+    }
+
+    pub(crate) fn ec_point_len(&self) -> usize {
+        if self.compressed_points {
+            0x20
+        } else {
+            0x40
+        }
+    }
+
+    pub(crate) fn blake2b(&self) -> bool {
+        self.transcript.little_endian_hash() == Some(TranscriptHash::Blake2b)
+    }
+
+    pub(crate) fn little_endian(&self) -> bool {
+        self.transcript.little_endian_hash().is_some()
+    }
+
+    pub(crate) fn transcript_hash_yul(&self, success: &str, len: &str) -> Vec<String> {
+        // Only rendered for `EvmTranscriptSpec`, since Blake2b and Poseidon squeeze by their own
+        // functions
+        let fns = match self.transcript {
+            Transcript::Evm(fns) => fns,
+            Transcript::LittleEndian(_) => return Vec::new(),
+        };
+        let mut lines = (fns.yul_hash)(success, len);
+        if let Some(precompile) = (fns.precompile)() {
+            if self.custom_errors {
+                lines.push(format!(
+                    "if iszero({success}) {{ revert_with_arg(PRECOMPILE_FAILED_SIG, {precompile:#04x}) }}"
+                ));
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(feature = "poseidon")]
+impl Halo2Verifier {
+    pub(crate) fn poseidon(&self) -> bool {
+        self.transcript.little_endian_hash() == Some(TranscriptHash::Poseidon)
+    }
+
+    pub(crate) fn poseidon_mds(&self) -> Vec<String> {
+        PoseidonSpec::<bn256::Fr>::new().yul_mds()
+    }
+
+    pub(crate) fn poseidon_permutation(&self) -> Vec<String> {
+        PoseidonSpec::<bn256::Fr>::new().yul_permutation()
+    }
+}
+
+// Poseidon functions are still compiled into the template without feature `poseidon`, but never
+// rendered since `TranscriptHash::Poseidon` doesn't exist.
+#[cfg(not(feature = "poseidon"))]
+impl Halo2Verifier {
+    pub(crate) fn poseidon(&self) -> bool {
+        false
+    }
+
+    pub(crate) fn poseidon_mds(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    pub(crate) fn poseidon_permutation(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Computations moved from `Halo2Verifier` into a helper contract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum HelperKind {
+    /// Part of quotient evaluation numerator accumulation.
+    Quotient,
+    /// Computation of pairing lhs and rhs by batch opening scheme.
+    Pcs,
+}
+
+#[derive(Template)]
+#[template(path = "Halo2VerifierHelper.sol")]
+pub(crate) struct Halo2VerifierHelper {
+    pub(crate) name: String,
+    pub(crate) kind: HelperKind,
+    pub(crate) scheme: BatchOpenScheme,
+    pub(crate) custom_errors: bool,
+    pub(crate) errors: Vec<CustomError>,
+    pub(crate) compressed_points: bool,
+    pub(crate) proof_len: usize,
+    pub(crate) vk_mptr: Ptr,
+    pub(crate) challenge_mptr: Ptr,
+    pub(crate) ec_point_mptr: Ptr,
+    pub(crate) theta_mptr: Ptr,
+    pub(crate) proof_cptr: Ptr,
+    pub(crate) quotient_comm_cptr: Ptr,
+    pub(crate) num_instance_evals: usize,
+    pub(crate) num_quotients: usize,
+    pub(crate) num_instances: usize,
+    pub(crate) shared_computations: Vec<Vec<String>>,
+    pub(crate) computations: Vec<Vec<String>>,
+}
+
+impl Halo2VerifierHelper {
+    pub(crate) fn new(
+        verifier: &Halo2Verifier,
+        name: String,
+        kind: HelperKind,
+        computations: Vec<Vec<String>>,
+    ) -> Self {
+        Self {
+            name,
+            kind,
+            scheme: verifier.scheme,
+            custom_errors: verifier.custom_errors,
+            errors: verifier.errors.clone(),
+            compressed_points: verifier.compressed_points,
+            proof_len: verifier.proof_len,
+            vk_mptr: verifier.vk_mptr,
+            challenge_mptr: verifier.challenge_mptr,
+            ec_point_mptr: verifier.ec_point_mptr,
+            theta_mptr: verifier.theta_mptr,
+            proof_cptr: verifier.proof_cptr,
+            quotient_comm_cptr: verifier.quotient_comm_cptr,
+            num_instance_evals: verifier.num_instance_evals,
+            num_quotients: verifier.num_quotients,
+            num_instances: verifier.num_instances,
+            shared_computations: verifier.quotient_eval_shared_computations.clone(),
+            computations,
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "IHalo2Verifier.sol")]
+pub(crate) struct IHalo2Verifier {
+    pub(crate) functions: Vec<Function>,
+    pub(crate) errors: Vec<CustomError>,
+}
+
+#[derive(Template)]
+#[template(path = "Halo2MultiVerifier.sol")]
+pub(crate) struct Halo2MultiVerifier {
+    pub(crate) num_verifiers: usize,
+    pub(crate) fn_sig_pairing_input: u32,
+    pub(crate) g2: [U256; 4],
+    pub(crate) neg_s_g2: [U256; 4],
 }
 
 impl Halo2VerifyingKey {
@@ -60,6 +247,33 @@ impl Halo2Verifier {
     }
 }
 
+impl Halo2VerifierHelper {
+    pub(crate) fn render(&self, writer: &mut impl fmt::Write) -> Result<(), fmt::Error> {
+        self.render_into(writer).map_err(|err| match err {
+            Error::Fmt(err) => err,
+            _ => unreachable!(),
+        })
+    }
+}
+
+impl IHalo2Verifier {
+    pub(crate) fn render(&self, writer: &mut impl fmt::Write) -> Result<(), fmt::Error> {
+        self.render_into(writer).map_err(|err| match err {
+            Error::Fmt(err) => err,
+            _ => unreachable!(),
+        })
+    }
+}
+
+impl Halo2MultiVerifier {
+    pub(crate) fn render(&self, writer: &mut impl fmt::Write) -> Result<(), fmt::Error> {
+        self.render_into(writer).map_err(|err| match err {
+            Error::Fmt(err) => err,
+            _ => unreachable!(),
+        })
+    }
+}
+
 mod filters {
     use std::fmt::LowerHex;
 
@@ -1,29 +1,33 @@
 use crate::codegen::{
     template::Halo2VerifyingKey,
     BatchOpenScheme::{self, Bdfg21, Gwc19},
+    Transcript,
 };
 use halo2_proofs::{
-    halo2curves::{bn256, ff::PrimeField, CurveAffine},
+    halo2curves::{bn256, ff::PrimeField, Coordinates, CurveAffine},
     plonk::{Any, Column, ConstraintSystem},
 };
 use itertools::{chain, izip, Itertools};
 use ruint::{aliases::U256, UintTryFrom};
 use std::{
     borrow::Borrow,
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     fmt::{self, Display, Formatter},
     ops::{Add, Sub},
 };
 
 #[derive(Debug)]
 pub(crate) struct ConstraintSystemMeta {
+    pub(crate) num_instance_columns: usize,
     pub(crate) num_fixeds: usize,
     pub(crate) permutation_columns: Vec<Column<Any>>,
     pub(crate) permutation_chunk_len: usize,
     pub(crate) num_lookup_permuteds: usize,
     pub(crate) num_permutation_zs: usize,
     pub(crate) num_lookup_zs: usize,
+    pub(crate) num_shuffle_zs: usize,
     pub(crate) num_quotients: usize,
+    pub(crate) instance_queries: Vec<(usize, i32)>,
     pub(crate) advice_queries: Vec<(usize, i32)>,
     pub(crate) fixed_queries: Vec<(usize, i32)>,
     pub(crate) num_evals: usize,
@@ -36,6 +40,7 @@ pub(crate) struct ConstraintSystemMeta {
 
 impl ConstraintSystemMeta {
     pub(crate) fn new(cs: &ConstraintSystem<impl PrimeField>) -> Self {
+        let num_instance_columns = cs.num_instance_columns();
         let num_fixeds = cs.num_fixed_columns();
         let permutation_columns = cs.permutation().get_columns();
         let permutation_chunk_len = cs.degree() - 2;
@@ -46,7 +51,13 @@ impl ConstraintSystemMeta {
             .chunks(cs.degree() - 2)
             .count();
         let num_lookup_zs = cs.lookups().len();
+        let num_shuffle_zs = cs.shuffles().len();
         let num_quotients = cs.degree() - 1;
+        let instance_queries = cs
+            .instance_queries()
+            .iter()
+            .map(|(column, rotation)| (column.index(), rotation.0))
+            .collect_vec();
         let advice_queries = cs
             .advice_queries()
             .iter()
@@ -61,8 +72,9 @@ impl ConstraintSystemMeta {
             + fixed_queries.len()
             + 1
             + cs.permutation().get_columns().len()
-            + (3 * num_permutation_zs - 1)
-            + 5 * cs.lookups().len();
+            + num_permutation_z_evals(num_permutation_zs)
+            + 5 * cs.lookups().len()
+            + 2 * cs.shuffles().len();
         let num_phase = *cs.advice_column_phase().iter().max().unwrap_or(&0) as usize + 1;
         // Indices of advice and challenge are not same as their position in calldata/memory,
         // because we support multiple phases, we need to remap them and find their actual indices.
@@ -92,13 +104,16 @@ impl ConstraintSystemMeta {
         let (num_user_challenges, challenge_indices) = remapping(cs.challenge_phase());
         let rotation_last = -(cs.blinding_factors() as i32 + 1);
         Self {
+            num_instance_columns,
             num_fixeds,
             permutation_columns,
             permutation_chunk_len,
             num_lookup_permuteds,
             num_permutation_zs,
             num_lookup_zs,
+            num_shuffle_zs,
             num_quotients,
+            instance_queries,
             advice_queries,
             fixed_queries,
             num_evals,
@@ -115,8 +130,9 @@ impl ConstraintSystemMeta {
             self.num_user_advices.iter().cloned(),
             (self.num_lookup_permuteds != 0).then_some(self.num_lookup_permuteds), // lookup permuted
             [
-                self.num_permutation_zs + self.num_lookup_zs + 1, // permutation and lookup grand products, random
-                self.num_quotients,                               // quotients
+                // permutation, lookup and shuffle grand products, random
+                self.num_permutation_zs + self.num_lookup_zs + self.num_shuffle_zs + 1,
+                self.num_quotients, // quotients
             ],
         ]
         .collect()
@@ -148,33 +164,131 @@ impl ConstraintSystemMeta {
         self.permutation_columns.len()
     }
 
+    pub(crate) fn num_permutation_z_evals(&self) -> usize {
+        num_permutation_z_evals(self.num_permutation_zs)
+    }
+
     pub(crate) fn num_lookups(&self) -> usize {
         self.num_lookup_zs
     }
 
-    pub(crate) fn proof_len(&self, scheme: BatchOpenScheme) -> usize {
-        self.num_advices().iter().sum::<usize>() * 0x40
-            + self.num_evals * 0x20
-            + self.batch_open_proof_len(scheme)
+    pub(crate) fn num_shuffles(&self) -> usize {
+        self.num_shuffle_zs
+    }
+
+    pub(crate) fn num_blinding_factors(&self) -> usize {
+        self.rotation_last.unsigned_abs() as usize - 1
     }
 
-    pub(crate) fn batch_open_proof_len(&self, scheme: BatchOpenScheme) -> usize {
+    /// Number of lagrange evaluations to compute before `L_0`, which covers `L_last`, `L_blind`,
+    /// and those needed by instance queries with positive rotation.
+    pub(crate) fn num_neg_lagranges(&self) -> usize {
+        chain![
+            [self.rotation_last.unsigned_abs() as usize],
+            self.instance_queries
+                .iter()
+                .map(|(_, rotation)| (*rotation).max(0) as usize),
+        ]
+        .max()
+        .unwrap()
+    }
+
+    /// Number of lagrange evaluations to compute after the longest instance column, which are
+    /// needed by instance queries with negative rotation.
+    pub(crate) fn num_trailing_lagranges(&self) -> usize {
+        self.instance_queries
+            .iter()
+            .map(|(_, rotation)| rotation.min(&0).unsigned_abs() as usize)
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn rotations(&self) -> BTreeSet<i32> {
+        chain![
+            self.advice_queries.iter().map(|(_, rotation)| *rotation),
+            self.fixed_queries.iter().map(|(_, rotation)| *rotation),
+            [0],
+            (self.num_permutation_zs + self.num_shuffle_zs > 0).then_some(1),
+            (self.num_permutation_zs > 1).then_some(self.rotation_last),
+            (self.num_lookup_zs > 0)
+                .then_some([-1, 1])
+                .into_iter()
+                .flatten(),
+        ]
+        .collect()
+    }
+
+    pub(crate) fn num_rotations(&self) -> usize {
+        self.rotations().len()
+    }
+
+    pub(crate) fn proof_len(&self, scheme: BatchOpenScheme, compressed_points: bool) -> usize {
+        let ec_point_len = if compressed_points { 0x20 } else { 0x40 };
+        self.num_proof_ec_points(scheme) * ec_point_len + self.num_evals * 0x20
+    }
+
+    pub(crate) fn num_proof_ec_points(&self, scheme: BatchOpenScheme) -> usize {
+        self.num_advices().iter().sum::<usize>() + self.num_batch_open_ec_points(scheme)
+    }
+
+    pub(crate) fn num_batch_open_ec_points(&self, scheme: BatchOpenScheme) -> usize {
         match scheme {
-            Bdfg21 => 2 * 0x40,
-            Gwc19 => {
-                unimplemented!()
-            }
+            Bdfg21 => 2,
+            Gwc19 => self.num_rotations(),
         }
     }
 }
 
+/// Return number of evaluations of permutation grand products, which are `z`, `z_next` of each
+/// chunk and `z_last` of all chunks but the last one, and none when there is no copy constraint.
+fn num_permutation_z_evals(num_permutation_zs: usize) -> usize {
+    (3 * num_permutation_zs).saturating_sub(1)
+}
+
+/// Encoding of points and scalars in proof, which decides where the verifier reads them from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ProofEncoding {
+    /// Points as big-endian `(x, y)` and scalars as big-endian, both read from calldata.
+    Uncompressed,
+    /// Points as big-endian `x` with parity of `y` in the most significant bit, decompressed into
+    /// memory, and scalars as big-endian read from calldata.
+    Compressed,
+    /// Points as little-endian `x` with parity of `y` in the most significant bit and scalars as
+    /// little-endian, as written by `halo2`'s `Blake2bWrite`, both decoded into memory.
+    LittleEndian,
+}
+
+impl ProofEncoding {
+    pub(crate) fn new(transcript: Transcript, compressed_points: bool) -> Self {
+        match (transcript, compressed_points) {
+            (Transcript::LittleEndian(_), _) => Self::LittleEndian,
+            (_, true) => Self::Compressed,
+            (_, false) => Self::Uncompressed,
+        }
+    }
+
+    pub(crate) fn compressed_points(&self) -> bool {
+        !matches!(self, Self::Uncompressed)
+    }
+
+    pub(crate) fn little_endian(&self) -> bool {
+        matches!(self, Self::LittleEndian)
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct Data {
     pub(crate) challenge_mptr: Ptr,
+    pub(crate) ec_point_mptr: Ptr,
     pub(crate) theta_mptr: Ptr,
 
+    // Both point to memory instead of calldata when points in proof are compressed, since they
+    // are decompressed into `ec_point_mptr` in the same order as proof.
     pub(crate) quotient_comm_cptr: Ptr,
     pub(crate) w_cptr: Ptr,
+    // Points to memory instead of calldata when scalars in proof are little-endian, since they
+    // are decoded right after decompressed points.
+    pub(crate) eval_cptr: Ptr,
 
     pub(crate) fixed_comms: Vec<EcPoint>,
     pub(crate) permutation_comms: HashMap<Column<Any>, EcPoint>,
@@ -182,17 +296,21 @@ pub(crate) struct Data {
     pub(crate) lookup_permuted_comms: Vec<(EcPoint, EcPoint)>,
     pub(crate) permutation_z_comms: Vec<EcPoint>,
     pub(crate) lookup_z_comms: Vec<EcPoint>,
+    pub(crate) shuffle_z_comms: Vec<EcPoint>,
     pub(crate) random_comm: EcPoint,
 
     pub(crate) challenges: Vec<Word>,
 
-    pub(crate) instance_eval: Word,
+    pub(crate) num_instances: Vec<Word>,
+
+    pub(crate) instance_evals: HashMap<(usize, i32), Word>,
     pub(crate) advice_evals: HashMap<(usize, i32), Word>,
     pub(crate) fixed_evals: HashMap<(usize, i32), Word>,
     pub(crate) random_eval: Word,
     pub(crate) permutation_evals: HashMap<Column<Any>, Word>,
     pub(crate) permutation_z_evals: Vec<(Word, Word, Word)>,
     pub(crate) lookup_evals: Vec<(Word, Word, Word, Word, Word)>,
+    pub(crate) shuffle_evals: Vec<(Word, Word)>,
 
     pub(crate) computed_quotient_comm: EcPoint,
     pub(crate) computed_quotient_eval: Word,
@@ -204,27 +322,52 @@ impl Data {
         vk: &Halo2VerifyingKey,
         vk_mptr: Ptr,
         proof_cptr: Ptr,
+        scheme: BatchOpenScheme,
+        encoding: ProofEncoding,
     ) -> Self {
+        let compressed_points = encoding.compressed_points();
         let fixed_comm_mptr = vk_mptr + vk.constants.len();
         let permutation_comm_mptr = fixed_comm_mptr + 2 * vk.fixed_comms.len();
         let challenge_mptr = permutation_comm_mptr + 2 * vk.permutation_comms.len();
-        let theta_mptr = challenge_mptr + meta.challenge_indices.len();
+        let ec_point_mptr = challenge_mptr + meta.challenge_indices.len();
+        let eval_mptr = ec_point_mptr + 2 * meta.num_proof_ec_points(scheme);
+        let theta_mptr = match encoding {
+            ProofEncoding::Uncompressed => ec_point_mptr,
+            ProofEncoding::Compressed => eval_mptr,
+            ProofEncoding::LittleEndian => eval_mptr + meta.num_evals,
+        };
 
-        let advice_comm_start = proof_cptr;
+        let advice_comm_start = if compressed_points {
+            ec_point_mptr
+        } else {
+            proof_cptr
+        };
         let lookup_permuted_comm_start = advice_comm_start + 2 * meta.advice_indices.len();
         let permutation_z_comm_start = lookup_permuted_comm_start + 2 * meta.num_lookup_permuteds;
         let lookup_z_comm_start = permutation_z_comm_start + 2 * meta.num_permutation_zs;
-        let random_comm_start = lookup_z_comm_start + 2 * meta.num_lookup_zs;
+        let shuffle_z_comm_start = lookup_z_comm_start + 2 * meta.num_lookup_zs;
+        let random_comm_start = shuffle_z_comm_start + 2 * meta.num_shuffle_zs;
         let quotient_comm_start = random_comm_start + 2;
 
-        let eval_cptr = quotient_comm_start + 2 * meta.num_quotients;
+        let eval_cptr = if encoding.little_endian() {
+            eval_mptr
+        } else if compressed_points {
+            proof_cptr + meta.num_advices().iter().sum::<usize>()
+        } else {
+            quotient_comm_start + 2 * meta.num_quotients
+        };
         let advice_eval_cptr = eval_cptr;
         let fixed_eval_cptr = advice_eval_cptr + meta.advice_queries.len();
         let random_eval_cptr = fixed_eval_cptr + meta.fixed_queries.len();
         let permutation_eval_cptr = random_eval_cptr + 1;
         let permutation_z_eval_cptr = permutation_eval_cptr + meta.num_permutations();
-        let lookup_eval_cptr = permutation_z_eval_cptr + 3 * meta.num_permutation_zs - 1;
-        let w_cptr = lookup_eval_cptr + 5 * meta.num_lookups();
+        let lookup_eval_cptr = permutation_z_eval_cptr + meta.num_permutation_z_evals();
+        let shuffle_eval_cptr = lookup_eval_cptr + 5 * meta.num_lookups();
+        let w_cptr = if compressed_points {
+            quotient_comm_start + 2 * meta.num_quotients
+        } else {
+            shuffle_eval_cptr + 2 * meta.num_shuffles()
+        };
 
         let fixed_comms = EcPoint::range(fixed_comm_mptr)
             .take(meta.num_fixeds)
@@ -250,6 +393,9 @@ impl Data {
         let lookup_z_comms = EcPoint::range(lookup_z_comm_start)
             .take(meta.num_lookup_zs)
             .collect();
+        let shuffle_z_comms = EcPoint::range(shuffle_z_comm_start)
+            .take(meta.num_shuffle_zs)
+            .collect();
         let random_comm = random_comm_start.into();
         let computed_quotient_comm = EcPoint::new(
             Ptr::memory("QUOTIENT_X_MPTR"),
@@ -262,7 +408,16 @@ impl Data {
             .map(|idx| challenge_mptr + *idx)
             .map_into()
             .collect_vec();
-        let instance_eval = Ptr::memory("INSTANCE_EVAL_MPTR").into();
+        let num_instances = Word::range(fixed_comm_mptr - meta.num_instance_columns)
+            .take(meta.num_instance_columns)
+            .collect_vec();
+
+        // Instance evaluations are placed right after the accumulator, see `Halo2Verifier.sol`.
+        let instance_evals = izip!(
+            meta.instance_queries.iter().cloned(),
+            Word::range(theta_mptr + 12)
+        )
+        .collect();
         let advice_evals = izip!(
             meta.advice_queries.iter().cloned(),
             Word::range(advice_eval_cptr)
@@ -287,13 +442,19 @@ impl Data {
             .take(5 * meta.num_lookup_zs)
             .tuples()
             .collect_vec();
+        let shuffle_evals = Word::range(shuffle_eval_cptr)
+            .take(2 * meta.num_shuffle_zs)
+            .tuples()
+            .collect_vec();
         let computed_quotient_eval = Ptr::memory("QUOTIENT_EVAL_MPTR").into();
 
         Self {
             challenge_mptr,
+            ec_point_mptr,
             theta_mptr,
             quotient_comm_cptr: quotient_comm_start,
             w_cptr,
+            eval_cptr,
 
             fixed_comms,
             permutation_comms,
@@ -301,17 +462,20 @@ impl Data {
             lookup_permuted_comms,
             permutation_z_comms,
             lookup_z_comms,
+            shuffle_z_comms,
             random_comm,
             computed_quotient_comm,
 
             challenges,
+            num_instances,
 
-            instance_eval,
+            instance_evals,
             advice_evals,
             fixed_evals,
             permutation_evals,
             permutation_z_evals,
             lookup_evals,
+            shuffle_evals,
             random_eval,
             computed_quotient_eval,
         }
@@ -574,9 +738,11 @@ pub(crate) fn for_loop(
     .collect()
 }
 
+/// Return coordinates of `ec_point`, or `(0, 0)` for identity as EVM precompiles take it.
 pub(crate) fn g1_to_u256s(ec_point: impl Borrow<bn256::G1Affine>) -> [U256; 2] {
-    let coords = ec_point.borrow().coordinates().unwrap();
-    [coords.x(), coords.y()].map(fq_to_u256)
+    Option::<Coordinates<_>>::from(ec_point.borrow().coordinates())
+        .map(|coords| [coords.x(), coords.y()].map(fq_to_u256))
+        .unwrap_or_default()
 }
 
 pub(crate) fn g2_to_u256s(ec_point: impl Borrow<bn256::G2Affine>) -> [U256; 4] {
@@ -1,7 +1,8 @@
 use crate::codegen::util::{fr_to_u256, to_u256_be_bytes};
 use halo2_proofs::halo2curves::bn256;
-use itertools::chain;
+use itertools::{chain, Itertools};
 use ruint::aliases::U256;
+use std::{fmt, iter};
 
 /// Function signature of `verifyProof(bytes,uint256[])`.
 pub const FN_SIG_VERIFY_PROOF: [u8; 4] = [0x1e, 0x8e, 0x1e, 0x13];
@@ -9,39 +10,314 @@ pub const FN_SIG_VERIFY_PROOF: [u8; 4] = [0x1e, 0x8e, 0x1e, 0x13];
 /// Function signature of `verifyProof(address,bytes,uint256[])`.
 pub const FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS: [u8; 4] = [0xaf, 0x83, 0xa1, 0x8d];
 
+/// Function signature of `pairingInput(bytes,uint256[])`.
+pub const FN_SIG_PAIRING_INPUT: [u8; 4] = [0x33, 0x90, 0xab, 0x0d];
+
+/// Function signature of `verifyProofs(bytes[],uint256[][])`.
+pub const FN_SIG_VERIFY_PROOFS: [u8; 4] = [0xad, 0x85, 0x6b, 0xf5];
+
+/// Function signature of `verifyProofs(address,bytes[],uint256[][])`.
+pub const FN_SIG_VERIFY_PROOFS_WITH_VK_ADDRESS: [u8; 4] = [0x16, 0x35, 0xf0, 0x2c];
+
+/// Function signature of `verifyProof(bytes,uint256[],bytes)`.
+pub const FN_SIG_VERIFY_PROOF_WITH_PUBLIC_DATA: [u8; 4] = [0xe8, 0xdc, 0x04, 0x52];
+
+/// Function signature of `verifyProof(address,bytes,uint256[],bytes)`.
+pub const FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS_AND_PUBLIC_DATA: [u8; 4] = [0x1c, 0xcb, 0x84, 0x03];
+
+/// Function signature of `verifyProofs(bytes[],uint256[][],bytes[])`.
+pub const FN_SIG_VERIFY_PROOFS_WITH_PUBLIC_DATA: [u8; 4] = [0xe0, 0x27, 0xfe, 0x6c];
+
+/// Function signature of `verifyProofs(address,bytes[],uint256[][],bytes[])`.
+pub const FN_SIG_VERIFY_PROOFS_WITH_VK_ADDRESS_AND_PUBLIC_DATA: [u8; 4] = [0xd6, 0x51, 0x73, 0x61];
+
+pub(crate) const ERR_SIG_INVALID_PROOF_LENGTH: [u8; 4] = [0x4d, 0xc5, 0xf6, 0xa4];
+pub(crate) const ERR_SIG_INVALID_INSTANCES_LENGTH: [u8; 4] = [0xa0, 0xb9, 0x79, 0x87];
+pub(crate) const ERR_SIG_INVALID_EC_POINT: [u8; 4] = [0xa5, 0x20, 0x56, 0x81];
+pub(crate) const ERR_SIG_INVALID_SCALAR: [u8; 4] = [0x16, 0x33, 0x19, 0xdd];
+pub(crate) const ERR_SIG_INVALID_ACCUMULATOR: [u8; 4] = [0xa8, 0x8d, 0x64, 0x54];
+pub(crate) const ERR_SIG_PRECOMPILE_FAILED: [u8; 4] = [0x7a, 0x5e, 0xb0, 0x6f];
+pub(crate) const ERR_SIG_PAIRING_FAILED: [u8; 4] = [0x4d, 0xf4, 0x5e, 0x2f];
+pub(crate) const ERR_SIG_INVALID_PUBLIC_INPUT_HASH: [u8; 4] = [0xe1, 0xcd, 0xd3, 0xac];
+
+/// Custom error reverted by `Halo2Verifier` generated with
+/// [`SolidityGenerator::set_custom_errors`](crate::SolidityGenerator::set_custom_errors) enabled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifierError {
+    /// `InvalidProofLength()`, length of proof doesn't match the one expected by verifier.
+    InvalidProofLength,
+    /// `InvalidInstancesLength()`, length of instances doesn't match the one expected by
+    /// verifier, or numbers of proofs, instances and public data given to `verifyProofs` are
+    /// different.
+    InvalidInstancesLength,
+    /// `InvalidEcPoint(uint256)`, point in proof is not a valid point on curve.
+    InvalidEcPoint {
+        /// Calldata offset of x coordinate of the point.
+        offset: usize,
+    },
+    /// `InvalidScalar(uint256)`, scalar in instances or proof is not in scalar field.
+    InvalidScalar {
+        /// Calldata offset of the scalar.
+        offset: usize,
+    },
+    /// `InvalidAccumulator()`, accumulator decoded from instances is not on curve.
+    InvalidAccumulator,
+    /// `PrecompileFailed(uint8)`, call to precompile fails.
+    PrecompileFailed {
+        /// Address of the precompile.
+        precompile: u8,
+    },
+    /// `PairingFailed()`, pairing check fails.
+    PairingFailed,
+    /// `InvalidPublicInputHash()`, keccak256 of public data doesn't match limbs in instances.
+    InvalidPublicInputHash,
+}
+
+impl fmt::Display for VerifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidProofLength => write!(f, "Invalid proof length"),
+            Self::InvalidInstancesLength => write!(f, "Invalid instances length"),
+            Self::InvalidEcPoint { offset } => write!(f, "Invalid EC point at 0x{offset:x}"),
+            Self::InvalidScalar { offset } => write!(f, "Invalid scalar at 0x{offset:x}"),
+            Self::InvalidAccumulator => write!(f, "Invalid accumulator"),
+            Self::PrecompileFailed { precompile } => {
+                write!(f, "Precompile 0x{precompile:02x} fails")
+            }
+            Self::PairingFailed => write!(f, "Pairing check fails"),
+            Self::InvalidPublicInputHash => write!(f, "Invalid public input hash"),
+        }
+    }
+}
+
+impl std::error::Error for VerifierError {}
+
+/// Decode revert data of `Halo2Verifier` into [`VerifierError`], or return `None` if it's not
+/// one of the custom errors.
+pub fn decode_revert_data(revert_data: &[u8]) -> Option<VerifierError> {
+    let sig = <[u8; 4]>::try_from(revert_data.get(..4)?).unwrap();
+    let arg = match revert_data.len() - 4 {
+        0 => None,
+        0x20 => Some(U256::try_from_be_slice(&revert_data[4..]).unwrap()),
+        _ => return None,
+    };
+    let error = match (sig, arg) {
+        (ERR_SIG_INVALID_PROOF_LENGTH, None) => VerifierError::InvalidProofLength,
+        (ERR_SIG_INVALID_INSTANCES_LENGTH, None) => VerifierError::InvalidInstancesLength,
+        (ERR_SIG_INVALID_EC_POINT, Some(offset)) => VerifierError::InvalidEcPoint {
+            offset: usize::try_from(offset).ok()?,
+        },
+        (ERR_SIG_INVALID_SCALAR, Some(offset)) => VerifierError::InvalidScalar {
+            offset: usize::try_from(offset).ok()?,
+        },
+        (ERR_SIG_INVALID_ACCUMULATOR, None) => VerifierError::InvalidAccumulator,
+        (ERR_SIG_PRECOMPILE_FAILED, Some(precompile)) => VerifierError::PrecompileFailed {
+            precompile: u8::try_from(precompile).ok()?,
+        },
+        (ERR_SIG_PAIRING_FAILED, None) => VerifierError::PairingFailed,
+        (ERR_SIG_INVALID_PUBLIC_INPUT_HASH, None) => VerifierError::InvalidPublicInputHash,
+        _ => return None,
+    };
+    Some(error)
+}
+
 /// Encode proof into calldata to invoke `Halo2Verifier.verifyProof`.
 ///
 /// For `vk_address`:
 /// - Pass `None` if verifying key is embedded in `Halo2Verifier`
 /// - Pass `Some(vk_address)` if verifying key is separated and deployed at `vk_address`
+///
+/// The `instances` are given per instance column, and they are concatenated in order of columns,
+/// which is the same order `halo2` absorbs them into transcript.
+///
+/// The `proof` is encoded as is, so its points should be compressed if and only if the verifier
+/// is rendered with [`SolidityGenerator::set_compressed_points`](crate::SolidityGenerator::set_compressed_points),
+/// otherwise the verifier rejects it by proof length.
 pub fn encode_calldata(
     vk_address: Option<[u8; 20]>,
     proof: &[u8],
-    instances: &[bn256::Fr],
+    instances: &[Vec<bn256::Fr>],
+) -> Vec<u8> {
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS
+    } else {
+        FN_SIG_VERIFY_PROOF
+    };
+    encode_args(
+        fn_sig,
+        vk_address,
+        [encode_bytes(proof), encode_instances(instances)],
+    )
+}
+
+/// Encode proof and public data into calldata to invoke `Halo2Verifier.verifyProof` rendered
+/// with [`SolidityGenerator::set_public_input_hash`](crate::SolidityGenerator::set_public_input_hash).
+///
+/// The `vk_address`, `proof` and `instances` are the same as [`encode_calldata`], where
+/// `instances` should contain limbs of keccak256 of `public_data`, see
+/// [`PublicInputHash::limbs`](crate::PublicInputHash::limbs).
+pub fn encode_calldata_with_public_data(
+    vk_address: Option<[u8; 20]>,
+    proof: &[u8],
+    instances: &[Vec<bn256::Fr>],
+    public_data: &[u8],
+) -> Vec<u8> {
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS_AND_PUBLIC_DATA
+    } else {
+        FN_SIG_VERIFY_PROOF_WITH_PUBLIC_DATA
+    };
+    encode_args(
+        fn_sig,
+        vk_address,
+        [
+            encode_bytes(proof),
+            encode_instances(instances),
+            encode_bytes(public_data),
+        ],
+    )
+}
+
+/// Encode proofs into calldata to invoke `Halo2Verifier.verifyProofs`, which verifies all proofs
+/// with a single pairing.
+///
+/// The `vk_address` is the same as [`encode_calldata`], and the `instances` are given per proof,
+/// each in the same form as [`encode_calldata`].
+///
+/// # Panics
+/// Panics if `proofs` and `instances` have different length.
+pub fn encode_calldata_batch(
+    vk_address: Option<[u8; 20]>,
+    proofs: &[Vec<u8>],
+    instances: &[Vec<Vec<bn256::Fr>>],
+) -> Vec<u8> {
+    assert_eq!(proofs.len(), instances.len());
+
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_PROOFS_WITH_VK_ADDRESS
+    } else {
+        FN_SIG_VERIFY_PROOFS
+    };
+    encode_args(
+        fn_sig,
+        vk_address,
+        [
+            encode_dynamic_array(proofs.iter().map(Vec::as_slice).map(encode_bytes)),
+            encode_dynamic_array(instances.iter().map(Vec::as_slice).map(encode_instances)),
+        ],
+    )
+}
+
+/// Encode proofs and public data into calldata to invoke `Halo2Verifier.verifyProofs` rendered
+/// with [`SolidityGenerator::set_public_input_hash`](crate::SolidityGenerator::set_public_input_hash).
+///
+/// The `vk_address`, `proofs` and `instances` are the same as [`encode_calldata_batch`], and the
+/// `public_data` are given per proof, each in the same form as
+/// [`encode_calldata_with_public_data`].
+///
+/// # Panics
+/// Panics if `proofs`, `instances` and `public_data` have different length.
+pub fn encode_calldata_batch_with_public_data(
+    vk_address: Option<[u8; 20]>,
+    proofs: &[Vec<u8>],
+    instances: &[Vec<Vec<bn256::Fr>>],
+    public_data: &[Vec<u8>],
 ) -> Vec<u8> {
-    let (fn_sig, offset) = if vk_address.is_some() {
-        (FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS, 0x60)
+    assert_eq!(proofs.len(), instances.len());
+    assert_eq!(proofs.len(), public_data.len());
+
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_PROOFS_WITH_VK_ADDRESS_AND_PUBLIC_DATA
     } else {
-        (FN_SIG_VERIFY_PROOF, 0x40)
+        FN_SIG_VERIFY_PROOFS_WITH_PUBLIC_DATA
     };
-    let vk_address = if let Some(vk_address) = vk_address {
+    encode_args(
+        fn_sig,
+        vk_address,
+        [
+            encode_dynamic_array(proofs.iter().map(Vec::as_slice).map(encode_bytes)),
+            encode_dynamic_array(instances.iter().map(Vec::as_slice).map(encode_instances)),
+            encode_dynamic_array(public_data.iter().map(Vec::as_slice).map(encode_bytes)),
+        ],
+    )
+}
+
+/// Encode function signature, optional verifying key address, and already encoded dynamic
+/// `args`, which are prefixed by their offsets.
+fn encode_args(
+    fn_sig: [u8; 4],
+    vk_address: Option<[u8; 20]>,
+    args: impl IntoIterator<Item = Vec<u8>>,
+) -> Vec<u8> {
+    let vk_address = encode_vk_address(vk_address);
+    let args = args.into_iter().collect_vec();
+    let offsets = args
+        .iter()
+        .scan(vk_address.len() + args.len() * 0x20, |offset, arg| {
+            let current = *offset;
+            *offset += arg.len();
+            Some(current)
+        })
+        .collect_vec();
+    chain![
+        fn_sig,                                         // function signature
+        vk_address,                                     // verifying key address
+        offsets.into_iter().flat_map(to_u256_be_bytes), // offsets of arguments
+        args.into_iter().flatten(),                     // arguments
+    ]
+    .collect()
+}
+
+/// Encode `bytes` with its length, padded to multiple of 32 bytes.
+fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let padding = (0x20 - bytes.len() % 0x20) % 0x20;
+    chain![
+        to_u256_be_bytes(bytes.len()),
+        bytes.iter().cloned(),
+        iter::repeat(0).take(padding),
+    ]
+    .collect()
+}
+
+/// Encode instances of all instance columns concatenated as `uint256[]`.
+fn encode_instances(instances: &[Vec<bn256::Fr>]) -> Vec<u8> {
+    let instances = instances.iter().flatten().collect_vec();
+    chain![
+        to_u256_be_bytes(instances.len()),
+        instances
+            .into_iter()
+            .map(fr_to_u256)
+            .flat_map(to_u256_be_bytes),
+    ]
+    .collect()
+}
+
+fn encode_vk_address(vk_address: Option<[u8; 20]>) -> Vec<u8> {
+    if let Some(vk_address) = vk_address {
         U256::try_from_be_slice(&vk_address)
             .unwrap()
             .to_be_bytes::<0x20>()
             .to_vec()
     } else {
         Vec::new()
-    };
-    let num_instances = instances.len();
+    }
+}
+
+/// Encode dynamic array with already encoded `elements`, which are prefixed by their offsets.
+fn encode_dynamic_array(elements: impl IntoIterator<Item = Vec<u8>>) -> Vec<u8> {
+    let elements = elements.into_iter().collect_vec();
+    let offsets = elements
+        .iter()
+        .scan(elements.len() * 0x20, |offset, element| {
+            let current = *offset;
+            *offset += element.len();
+            Some(current)
+        })
+        .collect_vec();
     chain![
-        fn_sig,                                                      // function signature
-        vk_address,                                                  // verifying key address
-        to_u256_be_bytes(offset),                                    // offset of proof
-        to_u256_be_bytes(offset + 0x20 + proof.len()),               // offset of instances
-        to_u256_be_bytes(proof.len()),                               // length of proof
-        proof.iter().cloned(),                                       // proof
-        to_u256_be_bytes(num_instances),                             // length of instances
-        instances.iter().map(fr_to_u256).flat_map(to_u256_be_bytes), // instances
+        to_u256_be_bytes(elements.len()),
+        offsets.into_iter().flat_map(to_u256_be_bytes),
+        elements.into_iter().flatten(),
     ]
     .collect()
 }
@@ -55,7 +331,7 @@ pub(crate) mod test {
     use std::{
         fmt::{self, Debug, Formatter},
         io::{self, Write},
-        process::{Command, Stdio},
+        process::{self, Command, Stdio},
         str,
     };
 
@@ -64,16 +340,8 @@ pub(crate) mod test {
     /// # Panics
     /// Panics if executable `solc` can not be found, or compilation fails.
     pub fn compile_solidity(solidity: impl AsRef<[u8]>) -> Vec<u8> {
-        let mut process = match Command::new("solc")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .arg("--bin")
-            .arg("--optimize")
-            .arg("-")
-            .spawn()
-        {
-            Ok(process) => process,
+        let output = match solc(solidity.as_ref(), &["--bin"]) {
+            Ok(output) => output,
             Err(err) if err.kind() == io::ErrorKind::NotFound => {
                 panic!("Command 'solc' not found");
             }
@@ -81,13 +349,6 @@ pub(crate) mod test {
                 panic!("Failed to spwan process with command 'solc':\n{err}");
             }
         };
-        process
-            .stdin
-            .take()
-            .unwrap()
-            .write_all(solidity.as_ref())
-            .unwrap();
-        let output = process.wait_with_output().unwrap();
         let stdout = str::from_utf8(&output.stdout).unwrap();
         if let Some(binary) = find_binary(stdout) {
             binary
@@ -99,6 +360,33 @@ pub(crate) mod test {
         }
     }
 
+    /// Compile solidity as [`compile_solidity`] does, then return creation bytecode and runtime
+    /// bytecode, or `None` if executable `solc` can not be found, or compilation fails.
+    pub(crate) fn try_compile_solidity(solidity: impl AsRef<[u8]>) -> Option<(Vec<u8>, Vec<u8>)> {
+        let output = solc(solidity.as_ref(), &["--bin", "--bin-runtime"]).ok()?;
+        let mut lines = str::from_utf8(&output.stdout).ok()?.lines();
+        let mut next_binary = |title: &str| {
+            let hex = lines.by_ref().skip_while(|line| *line != title).nth(1)?;
+            hex::decode(hex).ok()
+        };
+        let creation = next_binary("Binary:")?;
+        let runtime = next_binary("Binary of the runtime part:")?;
+        Some((creation, runtime))
+    }
+
+    fn solc(solidity: &[u8], args: &[&str]) -> io::Result<process::Output> {
+        let mut process = Command::new("solc")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .args(args)
+            .arg("--optimize")
+            .arg("-")
+            .spawn()?;
+        process.stdin.take().unwrap().write_all(solidity)?;
+        process.wait_with_output()
+    }
+
     fn find_binary(stdout: &str) -> Option<Vec<u8>> {
         let start = stdout.find("Binary:")? + 8;
         Some(hex::decode(&stdout[start..stdout.len() - 1]).unwrap())
@@ -162,6 +450,35 @@ pub(crate) mod test {
             }
         }
 
+        /// Apply call transaction to given `address` with `calldata`.
+        /// Returns `gas_used` and `return_data` if execution succeeds, otherwise `revert_data`.
+        ///
+        /// # Panics
+        /// Panics if execution halts unexpectedly.
+        pub fn try_call(
+            &mut self,
+            address: Address,
+            calldata: Vec<u8>,
+        ) -> Result<(u64, Vec<u8>), Vec<u8>> {
+            self.evm.env.tx = TxEnv {
+                gas_limit: u64::MAX,
+                transact_to: TransactTo::Call(address),
+                data: calldata.into(),
+                ..Default::default()
+            };
+            let result = self.evm.transact_commit().unwrap();
+            self.evm.env.tx = Default::default();
+            match result {
+                ExecutionResult::Success {
+                    gas_used,
+                    output: Output::Call(output),
+                    ..
+                } => Ok((gas_used, output.into())),
+                ExecutionResult::Revert { output, .. } => Err(output.into()),
+                result => panic!("Transaction halts unexpectedly with result {:?}", result),
+            }
+        }
+
         /// Apply call transaction to given `address` with `calldata`.
         /// Returns `gas_used` and `return_data`.
         ///
@@ -13,9 +13,26 @@ mod transcript;
 #[cfg(test)]
 mod test;
 
-pub use codegen::{AccumulatorEncoding, BatchOpenScheme, SolidityGenerator};
-pub use evm::{encode_calldata, FN_SIG_VERIFY_PROOF, FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS};
-pub use transcript::Keccak256Transcript;
+pub use codegen::{
+    AccumulatorEncoding, BatchOpenScheme, CodeSizeReport, CodeSizeSections, CodeSizeWarning,
+    ContractCodeSize, DecodedProof, FailedCheck, GasEstimate, GeneratorError, ProofField,
+    ProofLayout, PublicInputHash, SolidityGenerator, SolidityMultiGenerator, TranscriptHash,
+};
+pub use evm::{
+    decode_revert_data, encode_calldata, encode_calldata_batch,
+    encode_calldata_batch_with_public_data, encode_calldata_with_public_data, VerifierError,
+    FN_SIG_PAIRING_INPUT, FN_SIG_VERIFY_PROOF, FN_SIG_VERIFY_PROOFS,
+    FN_SIG_VERIFY_PROOFS_WITH_PUBLIC_DATA, FN_SIG_VERIFY_PROOFS_WITH_VK_ADDRESS,
+    FN_SIG_VERIFY_PROOFS_WITH_VK_ADDRESS_AND_PUBLIC_DATA, FN_SIG_VERIFY_PROOF_WITH_PUBLIC_DATA,
+    FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS, FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS_AND_PUBLIC_DATA,
+};
+pub use transcript::{
+    EvmTranscript, EvmTranscriptSpec, Keccak256Spec, Keccak256Transcript, Sha256Spec,
+    Sha256Transcript,
+};
+
+#[cfg(feature = "poseidon")]
+pub use transcript::PoseidonTranscript;
 
 #[cfg(feature = "evm")]
 pub use evm::test::{compile_solidity, Evm};
@@ -1,56 +1,1367 @@
 use crate::{
-    codegen::{AccumulatorEncoding, BatchOpenScheme::Bdfg21, SolidityGenerator},
-    encode_calldata,
-    evm::test::{compile_solidity, Evm},
-    FN_SIG_VERIFY_PROOF, FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS,
+    codegen::{
+        abi,
+        util::fr_to_u256,
+        AccumulatorEncoding,
+        BatchOpenScheme::{self, Bdfg21, Gwc19},
+        CodeSizeWarning, FailedCheck, GeneratorError, ProofField, ProofLayout, PublicInputHash,
+        SolidityGenerator, SolidityMultiGenerator,
+    },
+    decode_revert_data, encode_calldata, encode_calldata_batch,
+    encode_calldata_batch_with_public_data, encode_calldata_with_public_data,
+    evm::{
+        test::{compile_solidity, Evm},
+        ERR_SIG_INVALID_ACCUMULATOR, ERR_SIG_INVALID_EC_POINT, ERR_SIG_INVALID_INSTANCES_LENGTH,
+        ERR_SIG_INVALID_PROOF_LENGTH, ERR_SIG_INVALID_PUBLIC_INPUT_HASH, ERR_SIG_INVALID_SCALAR,
+        ERR_SIG_PAIRING_FAILED, ERR_SIG_PRECOMPILE_FAILED,
+    },
+    transcript::{
+        ChallengeEvm, EvmTranscript, EvmTranscriptSpec, Keccak256Spec, Keccak256Transcript,
+        Sha256Spec,
+    },
+    VerifierError, FN_SIG_PAIRING_INPUT, FN_SIG_VERIFY_PROOF, FN_SIG_VERIFY_PROOFS,
+    FN_SIG_VERIFY_PROOFS_WITH_PUBLIC_DATA, FN_SIG_VERIFY_PROOFS_WITH_VK_ADDRESS,
+    FN_SIG_VERIFY_PROOFS_WITH_VK_ADDRESS_AND_PUBLIC_DATA, FN_SIG_VERIFY_PROOF_WITH_PUBLIC_DATA,
+    FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS, FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS_AND_PUBLIC_DATA,
 };
-use halo2_proofs::halo2curves::bn256::{Bn256, Fr};
+use halo2_proofs::{
+    halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        ff::Field,
+    },
+    poly::kzg::commitment::ParamsKZG,
+    transcript::{EncodedChallenge, Transcript, TranscriptRead},
+};
+use itertools::chain;
 use rand::{rngs::StdRng, RngCore, SeedableRng};
 use sha3::Digest;
 use std::{fs::File, io::Write};
 
 #[test]
 fn function_signature() {
-    for (fn_name, fn_sig) in [
-        ("verifyProof(bytes,uint256[])", FN_SIG_VERIFY_PROOF),
+    for (fn_name, function, fn_sig) in [
+        (
+            "pairingInput(bytes,uint256[])",
+            abi::PAIRING_INPUT,
+            FN_SIG_PAIRING_INPUT,
+        ),
+        (
+            "verifyProof(bytes,uint256[])",
+            abi::VERIFY_PROOF,
+            FN_SIG_VERIFY_PROOF,
+        ),
         (
             "verifyProof(address,bytes,uint256[])",
+            abi::VERIFY_PROOF_WITH_VK_ADDRESS,
             FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS,
         ),
+        (
+            "verifyProofs(bytes[],uint256[][])",
+            abi::VERIFY_PROOFS,
+            FN_SIG_VERIFY_PROOFS,
+        ),
+        (
+            "verifyProofs(address,bytes[],uint256[][])",
+            abi::VERIFY_PROOFS_WITH_VK_ADDRESS,
+            FN_SIG_VERIFY_PROOFS_WITH_VK_ADDRESS,
+        ),
+        (
+            "verifyProof(bytes,uint256[],bytes)",
+            abi::VERIFY_PROOF_WITH_PUBLIC_DATA,
+            FN_SIG_VERIFY_PROOF_WITH_PUBLIC_DATA,
+        ),
+        (
+            "verifyProof(address,bytes,uint256[],bytes)",
+            abi::VERIFY_PROOF_WITH_VK_ADDRESS_AND_PUBLIC_DATA,
+            FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS_AND_PUBLIC_DATA,
+        ),
+        (
+            "verifyProofs(bytes[],uint256[][],bytes[])",
+            abi::VERIFY_PROOFS_WITH_PUBLIC_DATA,
+            FN_SIG_VERIFY_PROOFS_WITH_PUBLIC_DATA,
+        ),
+        (
+            "verifyProofs(address,bytes[],uint256[][],bytes[])",
+            abi::VERIFY_PROOFS_WITH_VK_ADDRESS_AND_PUBLIC_DATA,
+            FN_SIG_VERIFY_PROOFS_WITH_VK_ADDRESS_AND_PUBLIC_DATA,
+        ),
     ] {
         assert_eq!(
             <[u8; 32]>::from(sha3::Keccak256::digest(fn_name))[..4],
             fn_sig,
         );
+        assert_eq!(function.selector(), fn_sig);
+    }
+}
+
+#[test]
+fn error_signature() {
+    for (error_name, error, error_sig) in [
+        (
+            "InvalidProofLength()",
+            abi::INVALID_PROOF_LENGTH,
+            ERR_SIG_INVALID_PROOF_LENGTH,
+        ),
+        (
+            "InvalidInstancesLength()",
+            abi::INVALID_INSTANCES_LENGTH,
+            ERR_SIG_INVALID_INSTANCES_LENGTH,
+        ),
+        (
+            "InvalidEcPoint(uint256)",
+            abi::INVALID_EC_POINT,
+            ERR_SIG_INVALID_EC_POINT,
+        ),
+        (
+            "InvalidScalar(uint256)",
+            abi::INVALID_SCALAR,
+            ERR_SIG_INVALID_SCALAR,
+        ),
+        (
+            "InvalidAccumulator()",
+            abi::INVALID_ACCUMULATOR,
+            ERR_SIG_INVALID_ACCUMULATOR,
+        ),
+        (
+            "PrecompileFailed(uint8)",
+            abi::PRECOMPILE_FAILED,
+            ERR_SIG_PRECOMPILE_FAILED,
+        ),
+        (
+            "PairingFailed()",
+            abi::PAIRING_FAILED,
+            ERR_SIG_PAIRING_FAILED,
+        ),
+        (
+            "InvalidPublicInputHash()",
+            abi::INVALID_PUBLIC_INPUT_HASH,
+            ERR_SIG_INVALID_PUBLIC_INPUT_HASH,
+        ),
+    ] {
+        assert_eq!(
+            <[u8; 32]>::from(sha3::Keccak256::digest(error_name))[..4],
+            error_sig,
+        );
+        assert_eq!(error.selector(), error_sig);
+    }
+}
+
+#[test]
+fn generator_error() {
+    use halo2::TestCircuit;
+    type C = halo2::huge::HugeCircuit<Bn256>;
+
+    let (params, vk, instances, _) =
+        halo2::create_testdata::<C>(C::min_k(), Bdfg21, None, std_rng());
+    let num_instances = num_instances(&instances);
+
+    assert_eq!(
+        SolidityGenerator::try_new(&params, &vk, Bdfg21, vec![10]).unwrap_err(),
+        GeneratorError::InstanceColumnMismatch {
+            num_instance_columns: 2,
+            num_given: 1
+        }
+    );
+    assert!(matches!(
+        SolidityGenerator::try_new(&params, &vk, Bdfg21, vec![1 << C::min_k(), 5]).unwrap_err(),
+        GeneratorError::TooManyInstances {
+            column_index: 0,
+            ..
+        }
+    ));
+    let small_params = ParamsKZG::<Bn256>::setup(C::min_k() - 1, std_rng());
+    assert_eq!(
+        SolidityGenerator::try_new(&small_params, &vk, Bdfg21, num_instances.clone()).unwrap_err(),
+        GeneratorError::ParamsTooSmall {
+            params_k: C::min_k() - 1,
+            vk_k: C::min_k()
+        }
+    );
+    assert_eq!(
+        SolidityGenerator::try_new(&params, &vk, Bdfg21, num_instances.clone())
+            .unwrap()
+            .try_set_acc_encoding(AccumulatorEncoding::new(0, 4, 68).into())
+            .unwrap_err(),
+        GeneratorError::AccumulatorOutOfRange {
+            end: 16,
+            num_instances: 15
+        }
+    );
+    assert_eq!(
+        SolidityGenerator::try_new(&params, &vk, Bdfg21, num_instances.clone())
+            .unwrap()
+            .try_set_public_input_hash(PublicInputHash::new(14, 2, 128).into())
+            .unwrap_err(),
+        GeneratorError::PublicInputHashOutOfRange {
+            end: 16,
+            num_instances: 15
+        }
+    );
+    for (num_limbs, num_limb_bits) in [(0, 128), (1, 254), (3, 128)] {
+        assert_eq!(
+            SolidityGenerator::try_new(&params, &vk, Bdfg21, num_instances.clone())
+                .unwrap()
+                .try_set_public_input_hash(PublicInputHash::new(0, num_limbs, num_limb_bits).into())
+                .unwrap_err(),
+            GeneratorError::InvalidPublicInputHashLimbs {
+                num_limbs,
+                num_limb_bits
+            }
+        );
+    }
+
+    assert_eq!(
+        SolidityMultiGenerator::try_new(Vec::new()).unwrap_err(),
+        GeneratorError::NoGenerator
+    );
+    let other_params = ParamsKZG::<Bn256>::setup(C::min_k(), StdRng::seed_from_u64(1));
+    assert_eq!(
+        SolidityMultiGenerator::try_new(vec![
+            SolidityGenerator::new(&params, &vk, Bdfg21, num_instances.clone()),
+            SolidityGenerator::new(&other_params, &vk, Bdfg21, num_instances.clone()),
+        ])
+        .unwrap_err(),
+        GeneratorError::ParamsMismatch { index: 1 }
+    );
+    assert_eq!(
+        SolidityMultiGenerator::try_new(vec![
+            SolidityGenerator::new(&params, &vk, Bdfg21, num_instances.clone()),
+            SolidityGenerator::new(&params, &vk, Bdfg21, num_instances.clone())
+                .set_public_input_hash(PublicInputHash::new(0, 2, 128).into()),
+        ])
+        .unwrap_err(),
+        GeneratorError::MultiPublicInputHash { index: 1 }
+    );
+    assert_eq!(
+        SolidityMultiGenerator::try_new(vec![SolidityGenerator::new(
+            &params,
+            &vk,
+            Bdfg21,
+            num_instances.clone()
+        )
+        .set_custom_errors(true)])
+        .unwrap_err(),
+        GeneratorError::MultiCustomErrors { index: 0 }
+    );
+    assert_eq!(
+        SolidityMultiGenerator::try_new(vec![SolidityGenerator::new(
+            &params,
+            &vk,
+            Bdfg21,
+            num_instances.clone()
+        )
+        .set_non_reverting(true)])
+        .unwrap_err(),
+        GeneratorError::MultiNonReverting { index: 0 }
+    );
+    let mut verifier_outputs = vec![String::new(); 2];
+    assert_eq!(
+        SolidityMultiGenerator::new(vec![SolidityGenerator::new(
+            &params,
+            &vk,
+            Bdfg21,
+            num_instances
+        )])
+        .render_into(&mut verifier_outputs, &mut String::new())
+        .unwrap_err(),
+        GeneratorError::WriterMismatch {
+            num_generators: 1,
+            num_writers: 2
+        }
+    );
+}
+
+#[test]
+fn diagnose() {
+    type C = halo2::huge::HugeCircuit<Bn256>;
+
+    let acc_encoding = AccumulatorEncoding::new(0, 4, 68).into();
+    let (params, vk, instances, proof) =
+        halo2::create_testdata::<C>(C::min_k(), Bdfg21, acc_encoding, std_rng());
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, num_instances(&instances))
+        .set_acc_encoding(acc_encoding);
+
+    let calldata = encode_calldata(None, &proof, &instances);
+    assert_eq!(generator.diagnose(&calldata), Ok(()));
+
+    let proof_cptr = 0x64;
+    let instance_cptr = proof_cptr + proof.len() + 0x20;
+    let corrupted = |offset: usize, f: fn(&mut u8)| {
+        let mut calldata = calldata.clone();
+        f(&mut calldata[offset]);
+        generator.diagnose(&calldata).unwrap_err()
+    };
+
+    assert_eq!(
+        corrupted(0, |byte| *byte ^= 1),
+        FailedCheck::FunctionSignature
+    );
+    assert_eq!(
+        corrupted(proof_cptr - 1, |byte| *byte ^= 1).offset(),
+        Some(proof_cptr - 0x20)
+    );
+    assert_eq!(
+        corrupted(instance_cptr + 0x20, |byte| *byte = 0xff),
+        FailedCheck::InvalidScalar {
+            name: "instances[1]".to_string(),
+            offset: instance_cptr + 0x20
+        }
+    );
+    assert!(matches!(
+        corrupted(proof_cptr + 0x3f, |byte| *byte ^= 1),
+        FailedCheck::InvalidEcPoint { offset, .. } if offset == proof_cptr
+    ));
+    assert_eq!(
+        corrupted(instance_cptr + 0x1f, |byte| *byte ^= 1),
+        FailedCheck::InvalidAccumulator {
+            name: "acc_lhs".to_string(),
+            offset: instance_cptr
+        }
+    );
+    assert_eq!(
+        corrupted(proof_cptr + proof.len() - 0x81, |byte| *byte ^= 1),
+        FailedCheck::Pairing
+    );
+}
+
+#[test]
+fn proof_layout() {
+    type C = halo2::maingate::MainGateWithRange<Bn256>;
+
+    for scheme in [Bdfg21, Gwc19] {
+        let (params, vk, instances, proof) =
+            halo2::create_testdata::<C>(C::min_k(), scheme, None, std_rng());
+        let generator = SolidityGenerator::new(&params, &vk, scheme, num_instances(&instances));
+        let layout = generator.proof_layout();
+        assert_eq!(layout.fields(), ProofLayout::new(&vk, scheme).fields());
+        assert_eq!(layout.len(), proof.len());
+
+        // Fields cover the whole proof back to back
+        let fields = layout.fields();
+        let end = fields.iter().fold(0, |offset, (_, field)| {
+            assert_eq!(field.offset(), offset);
+            match field {
+                ProofField::EcPoint(_) => offset + 0x40,
+                ProofField::Scalar(_) => offset + 0x20,
+            }
+        });
+        assert_eq!(end, proof.len());
+        assert_eq!(
+            fields[0],
+            ("advice_comms[0]".to_string(), ProofField::EcPoint(0))
+        );
+
+        let decoded = layout.decode(&proof).unwrap();
+        let mut transcript = Keccak256Transcript::<G1Affine, _>::new(proof.as_slice());
+        assert_eq!(decoded.advice_comms[0][0], transcript.read_point().unwrap());
+        assert_eq!(
+            decoded.evals.len(),
+            fields
+                .iter()
+                .filter(|(_, field)| matches!(field, ProofField::Scalar(_)))
+                .count()
+        );
+        let num_ws = match scheme {
+            Bdfg21 => 2,
+            Gwc19 => fields
+                .iter()
+                .filter(|(name, _)| name.starts_with("ws["))
+                .count(),
+        };
+        assert_eq!(decoded.ws.len(), num_ws);
+        let json = decoded.to_json();
+        assert!(json.contains(r#""advice_comms":[[{"x":"0x"#));
+        assert!(json.contains(r#""random_eval":"0x"#));
+        assert_eq!(
+            layout.to_json().matches(r#""type":"scalar""#).count(),
+            decoded.evals.len()
+        );
+
+        // Compressed proof of the same randomness decodes into the same points and evaluations
+        let compressed = halo2::Transcript::Keccak256 {
+            compressed_points: true,
+        };
+        let (_, _, _, compressed_proof) =
+            halo2::create_testdata_with_transcript::<C>(C::min_k(), scheme, compressed, std_rng());
+        let compressed_layout = generator.set_compressed_points(true).proof_layout();
+        assert_eq!(
+            compressed_layout.decode(&compressed_proof).unwrap(),
+            decoded
+        );
+
+        // Invalid field is reported by its name and offset
+        let mut tampered = proof.clone();
+        tampered[0x3f] ^= 1;
+        let err = layout.decode(&tampered).unwrap_err();
+        assert!(err.to_string().starts_with("advice_comms[0] at 0x0: "));
+        assert!(layout.decode(&proof[1..]).is_err());
+    }
+}
+
+#[test]
+fn render_custom_errors() {
+    type C = halo2::huge::HugeCircuit<Bn256>;
+
+    let acc_encoding = AccumulatorEncoding::new(0, 4, 68).into();
+    let (params, vk, instances, proof) =
+        halo2::create_testdata::<C>(C::min_k(), Bdfg21, acc_encoding, std_rng());
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, num_instances(&instances))
+        .set_acc_encoding(acc_encoding)
+        .set_custom_errors(true);
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(compile_solidity(generator.render().unwrap()));
+
+    let calldata = encode_calldata(None, &proof, &instances);
+    let (_, output) = evm.try_call(verifier_address, calldata.clone()).unwrap();
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    let proof_cptr = 0x64;
+    let instance_cptr = proof_cptr + proof.len() + 0x20;
+    let mut corrupted = |offset: usize, f: fn(&mut u8)| {
+        let mut calldata = calldata.clone();
+        f(&mut calldata[offset]);
+        let revert_data = evm.try_call(verifier_address, calldata).unwrap_err();
+        decode_revert_data(&revert_data).unwrap()
+    };
+
+    assert_eq!(
+        corrupted(proof_cptr - 1, |byte| *byte ^= 1),
+        VerifierError::InvalidProofLength
+    );
+    assert_eq!(
+        corrupted(instance_cptr - 1, |byte| *byte ^= 1),
+        VerifierError::InvalidInstancesLength
+    );
+    assert_eq!(
+        corrupted(instance_cptr + 0x20, |byte| *byte = 0xff),
+        VerifierError::InvalidScalar {
+            offset: instance_cptr + 0x20
+        }
+    );
+    assert_eq!(
+        corrupted(proof_cptr + 0x3f, |byte| *byte ^= 1),
+        VerifierError::InvalidEcPoint { offset: proof_cptr }
+    );
+    assert_eq!(
+        corrupted(instance_cptr + 0x1f, |byte| *byte ^= 1),
+        VerifierError::InvalidAccumulator
+    );
+    assert_eq!(
+        corrupted(proof_cptr + proof.len() - 0x81, |byte| *byte ^= 1),
+        VerifierError::PairingFailed
+    );
+}
+
+#[test]
+fn render_non_reverting() {
+    type C = halo2::maingate::MainGateWithRange<Bn256>;
+
+    let (params, vk, instances, proofs) =
+        halo2::create_testdata_batch::<C>(C::min_k(), Bdfg21, None, 2, std_rng());
+
+    let proof_cptr = 0x64;
+    let proof_len = proofs[0].len();
+    let calldata = encode_calldata(None, &proofs[0], &instances[0]);
+    let tampered = [
+        // Proof length
+        (proof_cptr - 1, 0x01),
+        // Instance
+        (proof_cptr + proof_len + 0x20, 0xff),
+        // Advice commitment
+        (proof_cptr + 0x3f, 0x01),
+        // Last evaluation
+        (proof_cptr + proof_len - 0x81, 0x01),
+    ]
+    .map(|(offset, mask)| {
+        let mut calldata = calldata.clone();
+        calldata[offset] ^= mask;
+        calldata
+    });
+    let tampered_batch = {
+        let mut instances = instances.clone();
+        instances[1][0][0] += Fr::from(1);
+        encode_calldata_batch(None, &proofs, &instances)
+    };
+
+    for non_reverting in [false, true] {
+        let generator = SolidityGenerator::new(&params, &vk, Bdfg21, num_instances(&instances[0]))
+            .set_non_reverting(non_reverting);
+        let mut evm = Evm::default();
+        let verifier_address = evm.create(compile_solidity(generator.render().unwrap()));
+
+        let (_, output) = evm.call(verifier_address, calldata.clone());
+        assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+        for calldata in chain![tampered.clone(), [tampered_batch.clone()]] {
+            let result = evm.try_call(verifier_address, calldata);
+            if non_reverting {
+                assert_eq!(result.unwrap().1, vec![0; 32]);
+            } else {
+                assert!(result.is_err());
+            }
+        }
+    }
+}
+
+#[test]
+fn render_interface() {
+    type C = halo2::maingate::MainGateWithRange<Bn256>;
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata::<C>(C::min_k(), Bdfg21, None, std_rng());
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, num_instances(&instances))
+        .set_custom_errors(true);
+
+    let abi = generator.render_abi();
+    assert_eq!(abi.matches(r#""type":"function""#).count(), 3);
+    assert_eq!(abi.matches(r#""type":"error""#).count(), 7);
+    assert_eq!(abi.matches(r#""stateMutability":"view""#).count(), 3);
+    assert!(!abi.contains(r#""name":"vk""#));
+    assert_eq!(
+        generator
+            .render_abi_separately()
+            .matches(r#""name":"vk","type":"address""#)
+            .count(),
+        3
+    );
+
+    // Functions of verifier are declared exactly as in interface
+    for (verifier, interface) in [
+        (
+            generator.render().unwrap(),
+            generator.render_interface().unwrap(),
+        ),
+        (
+            generator.render_separately().unwrap().0,
+            generator.render_interface_separately().unwrap(),
+        ),
+    ] {
+        let declarations = interface
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with("function "))
+            .collect::<Vec<_>>();
+        assert_eq!(declarations.len(), 3);
+        for declaration in declarations {
+            assert!(verifier.contains(&declaration.replace(';', " {")));
+        }
+    }
+
+    // Call verifier through `IHalo2Verifier` in a view function
+    let caller_solidity = format!(
+        "{}\n{}",
+        generator.render_interface().unwrap(),
+        r#"
+contract Caller {
+    function verifyProof(
+        address verifier,
+        bytes calldata proof,
+        uint256[] calldata instances
+    ) external view returns (bool) {
+        return IHalo2Verifier(verifier).verifyProof(proof, instances);
+    }
+}"#
+    );
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(compile_solidity(generator.render().unwrap()));
+    let caller_address = evm.create(compile_solidity(caller_solidity));
+    let (_, output) = evm.call(
+        caller_address,
+        encode_calldata(Some(verifier_address.into()), &proof, &instances),
+    );
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+}
+
+#[test]
+fn render_split() {
+    type C = halo2::huge::HugeCircuit<Bn256>;
+
+    let acc_encoding = AccumulatorEncoding::new(0, 4, 68).into();
+    let max_code_size = 0x2000;
+    for scheme in [Bdfg21, Gwc19] {
+        let (params, vk, instances, proof) =
+            halo2::create_testdata::<C>(C::min_k(), scheme, acc_encoding, std_rng());
+        let generator = || {
+            SolidityGenerator::new(&params, &vk, scheme, num_instances(&instances))
+                .set_acc_encoding(acc_encoding)
+        };
+
+        let (verifier_solidity, helper_solidities) = generator()
+            .set_max_code_size(usize::MAX)
+            .render_split()
+            .unwrap();
+        assert!(helper_solidities.is_empty());
+        assert_eq!(verifier_solidity, generator().render().unwrap());
+
+        assert!(matches!(
+            generator().set_max_code_size(0x100).render_split(),
+            Err(GeneratorError::CodeSizeTooLarge { max_code_size, .. }) if max_code_size == 0x100
+        ));
+
+        for separate in [false, true] {
+            let generator = generator().set_max_code_size(max_code_size);
+            let (verifier_solidity, helper_solidities, vk_solidity) = if separate {
+                let (verifier_solidity, helper_solidities, vk_solidity) =
+                    generator.render_split_separately().unwrap();
+                (verifier_solidity, helper_solidities, Some(vk_solidity))
+            } else {
+                let (verifier_solidity, helper_solidities) = generator.render_split().unwrap();
+                assert!(helper_solidities.len() >= 2);
+                assert!(helper_solidities
+                    .last()
+                    .unwrap()
+                    .contains("contract Halo2VerifierPcsHelper"));
+                (verifier_solidity, helper_solidities, None)
+            };
+            assert!(!helper_solidities.is_empty());
+
+            let mut evm = Evm::default();
+            let helper_addresses = helper_solidities
+                .iter()
+                .map(|helper_solidity| evm.create(compile_solidity(helper_solidity)))
+                .collect::<Vec<_>>();
+            for helper_address in helper_addresses.iter() {
+                assert!(evm.code_size(*helper_address) <= max_code_size);
+            }
+            let verifier_creation_code = helper_addresses.iter().fold(
+                compile_solidity(&verifier_solidity),
+                |mut code, address| {
+                    code.extend([0; 12]);
+                    code.extend(<[u8; 20]>::from(*address));
+                    code
+                },
+            );
+            let verifier_address = evm.create(verifier_creation_code);
+            assert!(evm.code_size(verifier_address) <= max_code_size);
+            let vk_address = vk_solidity
+                .map(|vk_solidity| <[u8; 20]>::from(evm.create(compile_solidity(vk_solidity))));
+
+            let calldata = encode_calldata(vk_address, &proof, &instances);
+            let (gas_cost, output) = evm.call(verifier_address, calldata.clone());
+            assert_eq!(output, [vec![0; 31], vec![1]].concat());
+            println!("Gas cost: {gas_cost}");
+
+            let mut tampered = calldata;
+            *tampered.last_mut().unwrap() ^= 1;
+            assert!(evm.try_call(verifier_address, tampered).is_err());
+        }
+    }
+}
+
+#[test]
+fn code_size_report() {
+    use crate::codegen::code_size::estimate_code_size;
+    type C = halo2::huge::HugeCircuit<Bn256>;
+
+    let acc_encoding = AccumulatorEncoding::new(0, 4, 68).into();
+    let (params, vk, instances, _) =
+        halo2::create_testdata::<C>(C::min_k(), Bdfg21, acc_encoding, std_rng());
+    let generator = || {
+        SolidityGenerator::new(&params, &vk, Bdfg21, num_instances(&instances))
+            .set_acc_encoding(acc_encoding)
+    };
+
+    let report = generator()
+        .set_max_code_size(usize::MAX)
+        .code_size_report()
+        .unwrap();
+    assert!(!report.estimated);
+    assert!(report.warnings.is_empty());
+    assert!(report.helpers.is_empty());
+    assert_eq!(report.split_verifier, report.verifier);
+    for contract in [&report.verifier, &report.separate_verifier, &report.vk] {
+        assert_eq!(contract.sections.total(), contract.runtime);
+    }
+    assert_eq!(report.vk.runtime, report.vk.sections.vk_loading);
+    assert!(report.verifier.sections.vk_loading > report.separate_verifier.sections.vk_loading);
+
+    let mut evm = Evm::default();
+    for (solidity, compiled) in chain![[(generator().render().unwrap(), report.verifier)], {
+        let (verifier_solidity, vk_solidity) = generator().render_separately().unwrap();
+        [
+            (verifier_solidity, report.separate_verifier),
+            (vk_solidity, report.vk),
+        ]
+    }] {
+        let creation_code = compile_solidity(solidity);
+        let creation_code_size = creation_code.len();
+        let address = evm.create(creation_code);
+        let runtime_code_size = evm.code_size(address);
+        assert_eq!(compiled.runtime, runtime_code_size);
+        assert_eq!(compiled.creation, creation_code_size);
+    }
+
+    // Estimate as fallback without `solc` is still close to the compiled size
+    for (solidity, compiled) in [
+        (generator().render().unwrap(), report.verifier),
+        (
+            generator().render_separately().unwrap().0,
+            report.separate_verifier,
+        ),
+    ] {
+        let estimated = estimate_code_size(&solidity);
+        assert!(estimated.abs_diff(compiled.runtime) * 2 < compiled.runtime);
+    }
+
+    let report = generator()
+        .set_max_code_size(0x1000)
+        .code_size_report()
+        .unwrap();
+    assert!(report
+        .warnings
+        .contains(&CodeSizeWarning::RuntimeCodeTooLarge {
+            contract: "verifier".to_string(),
+            size: report.verifier.runtime,
+            limit: 0x1000,
+        }));
+    assert!(!report.helpers.is_empty());
+    assert!(report.split_verifier.runtime < report.verifier.runtime);
+    for helper in report.helpers.iter() {
+        assert_eq!(helper.sections.total(), helper.runtime);
+        assert_eq!(helper.sections.transcript, 0);
+    }
+}
+
+#[test]
+fn simplify_expressions() {
+    use crate::codegen::evaluator::simplify;
+    use halo2::{
+        huge::HugeCircuit, maingate::MainGateWithRange, shuffle::ShuffleCircuit, TestCircuit,
+    };
+    use halo2_proofs::{
+        halo2curves::ff::Field,
+        plonk::{ConstraintSystem, Expression},
+    };
+    use std::{
+        collections::hash_map::DefaultHasher,
+        hash::{Hash, Hasher},
+    };
+
+    // Evaluate with pseudo-random value for each query, which is the same across expressions
+    fn evaluate(expression: &Expression<Fr>) -> Fr {
+        let random = |query: (&str, usize, i32)| {
+            let mut hasher = DefaultHasher::new();
+            query.hash(&mut hasher);
+            Fr::random(StdRng::seed_from_u64(hasher.finish()))
+        };
+        expression.evaluate(
+            &|constant| constant,
+            &|_| unreachable!(),
+            &|query| random(("fixed", query.column_index(), query.rotation().0)),
+            &|query| random(("advice", query.column_index(), query.rotation().0)),
+            &|query| random(("instance", query.column_index(), query.rotation().0)),
+            &|challenge| random(("challenge", challenge.index(), 0)),
+            &|value| -value,
+            &|lhs, rhs| lhs + rhs,
+            &|lhs, rhs| lhs * rhs,
+            &|value, scalar| value * scalar,
+        )
+    }
+
+    fn cs<C: TestCircuit<Fr>>() -> ConstraintSystem<Fr> {
+        let (_, vk, _, _) = halo2::create_testdata::<C>(C::min_k(), Bdfg21, None, std_rng());
+        vk.cs().clone()
+    }
+
+    for cs in [
+        cs::<HugeCircuit<Bn256>>(),
+        cs::<MainGateWithRange<Bn256>>(),
+        cs::<ShuffleCircuit<Bn256>>(),
+    ] {
+        let expressions = chain![
+            cs.gates().iter().flat_map(|gate| gate.polynomials()),
+            cs.lookups()
+                .iter()
+                .flat_map(|lookup| chain![lookup.input_expressions(), lookup.table_expressions()]),
+            cs.shuffles().iter().flat_map(|shuffle| chain![
+                shuffle.input_expressions(),
+                shuffle.shuffle_expressions()
+            ]),
+        ];
+        for expression in expressions {
+            assert_eq!(evaluate(&simplify(expression)), evaluate(expression));
+        }
+    }
+
+    let x = || Expression::Constant(Fr::from(3)) * Expression::Constant(Fr::from(5));
+    assert_eq!(evaluate(&simplify(&x())), Fr::from(15));
+    assert!(matches!(simplify(&x()), Expression::Constant(_)));
+    assert!(matches!(
+        simplify(&(x() * Expression::Constant(Fr::ZERO) + x())),
+        Expression::Constant(value) if value == Fr::from(15)
+    ));
+}
+
+#[test]
+fn render_shared_computations() {
+    type C = halo2::huge::HugeCircuit<Bn256>;
+
+    let (params, vk, instances, _) =
+        halo2::create_testdata::<C>(C::min_k(), Bdfg21, None, std_rng());
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, num_instances(&instances));
+    let verifier_solidity = generator.render().unwrap();
+
+    // Lookups of overlapping columns share `q * a`, which is computed once then loaded
+    assert!(verifier_solidity
+        .contains("// Compute values shared by gates, permutation and lookups once"));
+    assert!(verifier_solidity.contains(
+        "mstore(0x20, addmod(1, sub(r, addmod(mload(L_LAST_MPTR), mload(L_BLIND_MPTR), r)), r))"
+    ));
+    assert!(verifier_solidity.contains(":= mload(0x40)"));
+}
+
+#[test]
+fn evm_transcript_spec() {
+    fn run<H: EvmTranscriptSpec>(mut rng: impl RngCore) {
+        let scalars = (0..5).map(|_| Fr::random(&mut rng)).collect::<Vec<_>>();
+
+        let [challenge_0, challenge_1] = {
+            let mut transcript = EvmTranscript::<G1Affine, _, H>::new(Vec::new());
+            for scalar in &scalars {
+                Transcript::<G1Affine, ChallengeEvm<_>>::common_scalar(&mut transcript, *scalar)
+                    .unwrap();
+            }
+            [(); 2].map(|_| {
+                let challenge: ChallengeEvm<G1Affine> = transcript.squeeze_challenge();
+                fr_to_u256(challenge.get_scalar())
+            })
+        };
+
+        let yul_hash = |len| H::yul_hash("success", len).join("\n                ");
+        let solidity = format!(
+            r#"// SPDX-License-Identifier: MIT
+
+pragma solidity ^0.8.0;
+
+contract Transcript {{
+    fallback() external {{
+        assembly {{
+            let r := 21888242871839275222246405745257275088548364400416034343698204186575808495617
+            let success := true
+            calldatacopy(0x00, 0x00, calldatasize())
+            let challenge_0 := 0
+            {{
+                {}
+                challenge_0 := mod(hash, r)
+                mstore(0x00, hash)
+            }}
+            mstore8(0x20, 0x01)
+            let challenge_1 := 0
+            {{
+                {}
+                challenge_1 := mod(hash, r)
+            }}
+            if iszero(success) {{ revert(0, 0) }}
+            mstore(0x00, challenge_0)
+            mstore(0x20, challenge_1)
+            return(0x00, 0x40)
+        }}
+    }}
+}}
+"#,
+            yul_hash("calldatasize()"),
+            yul_hash("0x21"),
+        );
+
+        let mut evm = Evm::default();
+        let address = evm.create(compile_solidity(solidity));
+        let calldata = scalars
+            .iter()
+            .flat_map(|scalar| fr_to_u256(*scalar).to_be_bytes::<0x20>())
+            .collect();
+        let (_, output) = evm.call(address, calldata);
+        assert_eq!(
+            output,
+            [challenge_0, challenge_1]
+                .map(|challenge| challenge.to_be_bytes::<0x20>())
+                .concat()
+        );
+    }
+
+    run::<Keccak256Spec>(std_rng());
+    run::<Sha256Spec>(std_rng());
+    run::<halo2::DoubleKeccak256Spec>(std_rng());
+}
+
+#[test]
+fn render_transcript() {
+    use halo2::Transcript::{Blake2b, DoubleKeccak256, Keccak256, Sha256};
+    type C = halo2::maingate::MainGateWithRange<Bn256>;
+
+    // Each transcript with another one of the same encoding but different challenges if any
+    let mut transcripts = vec![
+        (halo2::KECCAK256, Some(Sha256)),
+        (
+            Keccak256 {
+                compressed_points: true,
+            },
+            None,
+        ),
+        (Sha256, Some(halo2::KECCAK256)),
+        (DoubleKeccak256, Some(halo2::KECCAK256)),
+    ];
+    #[cfg(feature = "poseidon")]
+    transcripts.extend([
+        (Blake2b, Some(halo2::Transcript::Poseidon)),
+        (halo2::Transcript::Poseidon, Some(Blake2b)),
+    ]);
+    #[cfg(not(feature = "poseidon"))]
+    transcripts.push((Blake2b, None));
+    for scheme in [Bdfg21, Gwc19] {
+        for (transcript, other) in transcripts.iter().copied() {
+            let (params, vk, instances, proof) = halo2::create_testdata_with_transcript::<C>(
+                C::min_k(),
+                scheme,
+                transcript,
+                std_rng(),
+            );
+            let generator = transcript.configure(
+                SolidityGenerator::new(&params, &vk, scheme, num_instances(&instances))
+                    .set_custom_errors(true),
+            );
+            let calldata = encode_calldata(None, &proof, &instances);
+            assert_eq!(generator.diagnose(&calldata), Ok(()));
+            if transcript.compressed_points() {
+                assert!(proof.len() < ProofLayout::new(&vk, scheme).len());
+            }
+
+            let mut evm = Evm::default();
+            let verifier_address = evm.create(compile_solidity(generator.render().unwrap()));
+            let (gas_cost, output) = evm.call(verifier_address, calldata.clone());
+            assert_eq!(output, [vec![0; 31], vec![1]].concat());
+            println!("Gas cost of {transcript:?}: {gas_cost}");
+
+            assert_gas_estimate(&generator, gas_cost);
+
+            let proof_cptr = 0x64;
+            let mut revert = |tampered: &[u8]| {
+                let revert_data = evm
+                    .try_call(verifier_address, tampered.to_vec())
+                    .unwrap_err();
+                decode_revert_data(&revert_data).unwrap()
+            };
+
+            // Proof created by another transcript derives different challenges
+            if let Some(other) = other {
+                let (_, _, _, other_proof) = halo2::create_testdata_with_transcript::<C>(
+                    C::min_k(),
+                    scheme,
+                    other,
+                    std_rng(),
+                );
+                let other_calldata = encode_calldata(None, &other_proof, &instances);
+                assert_eq!(revert(&other_calldata), VerifierError::PairingFailed);
+                assert_eq!(
+                    generator.diagnose(&other_calldata),
+                    Err(FailedCheck::Pairing)
+                );
+            }
+
+            if transcript.compressed_points() {
+                // Point with x not less than base field modulus, where the most significant byte
+                // is the last one in little-endian
+                let msb_cptr = proof_cptr + if transcript.little_endian() { 0x1f } else { 0 };
+                let mut tampered = calldata.clone();
+                tampered[msb_cptr] |= 0x7f;
+                assert_eq!(
+                    revert(&tampered),
+                    VerifierError::InvalidEcPoint { offset: proof_cptr }
+                );
+                assert!(matches!(
+                    generator.diagnose(&tampered),
+                    Err(FailedCheck::InvalidEcPoint { offset, .. }) if offset == proof_cptr
+                ));
+
+                // Flipping parity of y gives another valid point, but fails pairing
+                let mut tampered = calldata;
+                tampered[msb_cptr] ^= 0x80;
+                assert_eq!(revert(&tampered), VerifierError::PairingFailed);
+                assert_eq!(generator.diagnose(&tampered), Err(FailedCheck::Pairing));
+            }
+        }
+    }
+}
+
+#[test]
+fn render_blake2b_transcript_huge() {
+    type C = halo2::huge::HugeCircuit<Bn256>;
+
+    let transcript = halo2::Transcript::Blake2b;
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_with_transcript::<C>(C::min_k(), Bdfg21, transcript, std_rng());
+    let generator = transcript.configure(SolidityGenerator::new(
+        &params,
+        &vk,
+        Bdfg21,
+        num_instances(&instances),
+    ));
+    let calldata = encode_calldata(None, &proof, &instances);
+    assert_eq!(generator.diagnose(&calldata), Ok(()));
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(compile_solidity(generator.render().unwrap()));
+    let (gas_cost, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    println!("Gas cost: {gas_cost}");
+}
+
+#[test]
+#[cfg(feature = "poseidon")]
+fn poseidon_transcript() {
+    use crate::transcript::{poseidon::ChallengeScalar, PoseidonTranscript};
+    use halo2_proofs::halo2curves::{
+        bn256::Fq,
+        ff::{FromUniformBytes, PrimeField},
+        group::{prime::PrimeCurveAffine, Curve},
+        CurveAffine,
+    };
+    use poseidon::Poseidon;
+
+    // Coordinates are reduced into scalar field as `snark-verifier` does
+    let reduce = |coordinate: &Fq| {
+        let mut bytes = [0; 64];
+        bytes[..0x20].copy_from_slice(&coordinate.to_repr());
+        Fr::from_uniform_bytes(&bytes)
+    };
+
+    let mut rng = std_rng();
+    for num_scalars in 0..10 {
+        let scalars = (0..num_scalars)
+            .map(|_| Fr::random(&mut rng))
+            .collect::<Vec<_>>();
+        let ec_point = (G1Affine::generator() * Fr::random(&mut rng)).to_affine();
+
+        let mut transcript = PoseidonTranscript::<G1Affine, _>::new(Vec::new());
+        let mut reference = Poseidon::<Fr, 5, 4>::new(8, 60);
+
+        for scalar in &scalars {
+            Transcript::<G1Affine, ChallengeScalar<_>>::common_scalar(&mut transcript, *scalar)
+                .unwrap();
+        }
+        reference.update(&scalars);
+        let challenge: ChallengeScalar<G1Affine> = transcript.squeeze_challenge();
+        assert_eq!(challenge.get_scalar(), reference.squeeze());
+
+        Transcript::<G1Affine, ChallengeScalar<_>>::common_point(&mut transcript, ec_point)
+            .unwrap();
+        let coordinates = ec_point.coordinates().unwrap();
+        reference.update(&[reduce(coordinates.x()), reduce(coordinates.y())]);
+        let challenge: ChallengeScalar<G1Affine> = transcript.squeeze_challenge();
+        assert_eq!(challenge.get_scalar(), reference.squeeze());
+    }
+}
+
+#[test]
+fn render_identity_commitment() {
+    type C = halo2::identity::ZeroColumnCircuit<Bn256>;
+
+    let mut transcripts = vec![
+        halo2::KECCAK256,
+        halo2::Transcript::Keccak256 {
+            compressed_points: true,
+        },
+    ];
+    #[cfg(feature = "poseidon")]
+    transcripts.push(halo2::Transcript::Poseidon);
+    for scheme in [Bdfg21, Gwc19] {
+        for transcript in transcripts.iter().copied() {
+            let (params, vk, instances, proof) = halo2::create_testdata_with_transcript::<C>(
+                C::min_k(),
+                scheme,
+                transcript,
+                std_rng(),
+            );
+            let generator = transcript.configure(
+                SolidityGenerator::new(&params, &vk, scheme, num_instances(&instances))
+                    .set_custom_errors(true),
+            );
+            let compressed_points = transcript.compressed_points();
+
+            // Identity is encoded as zero, as (0, 0) when uncompressed
+            let ec_point_len = if compressed_points { 0x20 } else { 0x40 };
+            assert!(proof[..ec_point_len].iter().all(|byte| *byte == 0));
+
+            let calldata = encode_calldata(None, &proof, &instances);
+            assert_eq!(generator.diagnose(&calldata), Ok(()));
+
+            let mut evm = Evm::default();
+            let verifier_address = evm.create(compile_solidity(generator.render().unwrap()));
+            let (gas_cost, output) = evm.call(verifier_address, calldata.clone());
+            assert_eq!(output, [vec![0; 31], vec![1]].concat());
+            println!("Gas cost: {gas_cost}");
+
+            if compressed_points {
+                // Zero with parity of y set is not a point, since no point has x = 0
+                let proof_cptr = 0x64;
+                let mut tampered = calldata;
+                tampered[proof_cptr + if transcript.little_endian() { 0x1f } else { 0 }] ^= 0x80;
+                let revert_data = evm
+                    .try_call(verifier_address, tampered.clone())
+                    .unwrap_err();
+                assert_eq!(
+                    decode_revert_data(&revert_data),
+                    Some(VerifierError::InvalidEcPoint { offset: proof_cptr })
+                );
+                assert!(matches!(
+                    generator.diagnose(&tampered),
+                    Err(FailedCheck::InvalidEcPoint { offset, .. }) if offset == proof_cptr
+                ));
+            }
+        }
+    }
+}
+
+#[test]
+fn render_public_input_hash() {
+    let public_input_hash = PublicInputHash::new(3, 2, 128);
+    let public_data = (0..100).map(|idx| idx as u8).collect::<Vec<_>>();
+
+    for separate in [false, true] {
+        let (params, vk, instances, proof) = halo2::create_testdata_with_public_input_hash(
+            Bdfg21,
+            public_input_hash,
+            &public_data,
+            std_rng(),
+        );
+        let generator = SolidityGenerator::new(&params, &vk, Bdfg21, num_instances(&instances))
+            .set_public_input_hash(public_input_hash.into())
+            .set_custom_errors(true);
+
+        let mut evm = Evm::default();
+        let (verifier_address, vk_address) = if separate {
+            let (verifier_solidity, vk_solidity) = generator.render_separately().unwrap();
+            let verifier_address = evm.create(compile_solidity(verifier_solidity));
+            let vk_address = evm.create(compile_solidity(vk_solidity));
+            (verifier_address, Some(vk_address.into()))
+        } else {
+            (
+                evm.create(compile_solidity(generator.render().unwrap())),
+                None,
+            )
+        };
+
+        let calldata =
+            encode_calldata_with_public_data(vk_address, &proof, &instances, &public_data);
+        assert_eq!(generator.diagnose(&calldata), Ok(()));
+        let (gas_cost, output) = evm.call(verifier_address, calldata.clone());
+        assert_eq!(output, [vec![0; 31], vec![1]].concat());
+        println!("Gas cost: {gas_cost}");
+
+        let (gas_cost, output) = evm.call(
+            verifier_address,
+            encode_calldata_batch_with_public_data(
+                vk_address,
+                &[proof.clone(), proof.clone()],
+                &[instances.clone(), instances.clone()],
+                &[public_data.clone(), public_data.clone()],
+            ),
+        );
+        assert_eq!(output, [vec![0; 31], vec![1]].concat());
+        println!("Gas cost of verifying 2 proofs: {gas_cost}");
+
+        // Public data not matching the hash limbs in instances
+        let padding = (0x20 - public_data.len() % 0x20) % 0x20;
+        let mut tampered = calldata.clone();
+        tampered[calldata.len() - padding - 1] ^= 1;
+        let revert_data = evm
+            .try_call(verifier_address, tampered.clone())
+            .unwrap_err();
+        assert_eq!(
+            decode_revert_data(&revert_data),
+            Some(VerifierError::InvalidPublicInputHash)
+        );
+        let proof_cptr = if separate { 0xa4 } else { 0x84 };
+        let num_instance_cptr = proof_cptr + proof.len();
+        assert_eq!(
+            generator.diagnose(&tampered),
+            Err(FailedCheck::PublicInputHash {
+                offset: num_instance_cptr + 0x20 * (1 + public_input_hash.offset)
+            })
+        );
     }
 }
 
 #[test]
 fn render_huge() {
-    run_render::<halo2::huge::HugeCircuit<Bn256>>()
+    run_render::<halo2::huge::HugeCircuit<Bn256>>(Bdfg21)
 }
 
 #[test]
 fn render_maingate() {
-    run_render::<halo2::maingate::MainGateWithRange<Bn256>>()
+    run_render::<halo2::maingate::MainGateWithRange<Bn256>>(Bdfg21)
 }
 
 #[test]
 fn render_separately_huge() {
-    run_render_separately::<halo2::huge::HugeCircuit<Bn256>>()
+    run_render_separately::<halo2::huge::HugeCircuit<Bn256>>(Bdfg21)
 }
 
 #[test]
 fn render_separately_maingate() {
-    run_render_separately::<halo2::maingate::MainGateWithRange<Bn256>>()
+    run_render_separately::<halo2::maingate::MainGateWithRange<Bn256>>(Bdfg21)
+}
+
+#[test]
+fn render_huge_gwc19() {
+    run_render::<halo2::huge::HugeCircuit<Bn256>>(Gwc19)
+}
+
+#[test]
+fn render_maingate_gwc19() {
+    run_render::<halo2::maingate::MainGateWithRange<Bn256>>(Gwc19)
+}
+
+#[test]
+fn render_separately_huge_gwc19() {
+    run_render_separately::<halo2::huge::HugeCircuit<Bn256>>(Gwc19)
+}
+
+#[test]
+fn render_separately_maingate_gwc19() {
+    run_render_separately::<halo2::maingate::MainGateWithRange<Bn256>>(Gwc19)
+}
+
+#[test]
+fn render_shuffle() {
+    run_render::<halo2::shuffle::ShuffleCircuit<Bn256>>(Bdfg21)
+}
+
+#[test]
+fn render_shuffle_gwc19() {
+    run_render::<halo2::shuffle::ShuffleCircuit<Bn256>>(Gwc19)
+}
+
+#[test]
+fn render_separately_shuffle() {
+    run_render_separately::<halo2::shuffle::ShuffleCircuit<Bn256>>(Bdfg21)
+}
+
+#[test]
+fn render_lookup() {
+    run_render::<halo2::lookup::LookupCircuit<Bn256>>(Bdfg21)
+}
+
+#[test]
+fn render_lookup_gwc19() {
+    run_render::<halo2::lookup::LookupCircuit<Bn256>>(Gwc19)
+}
+
+#[test]
+fn render_separately_lookup() {
+    run_render_separately::<halo2::lookup::LookupCircuit<Bn256>>(Bdfg21)
+}
+
+#[test]
+fn render_batch_huge() {
+    run_render_batch::<halo2::huge::HugeCircuit<Bn256>>(Bdfg21, false)
+}
+
+#[test]
+fn render_batch_maingate_gwc19() {
+    run_render_batch::<halo2::maingate::MainGateWithRange<Bn256>>(Gwc19, false)
+}
+
+#[test]
+fn render_separately_batch_maingate() {
+    run_render_batch::<halo2::maingate::MainGateWithRange<Bn256>>(Bdfg21, true)
+}
+
+#[test]
+fn render_multi() {
+    use halo2::{huge::HugeCircuit, maingate::MainGateWithRange, TestCircuit};
+
+    let acc_encoding = AccumulatorEncoding::new(0, 4, 68).into();
+    let k = HugeCircuit::<Bn256>::min_k().max(MainGateWithRange::<Bn256>::min_k());
+    let params = ParamsKZG::<Bn256>::setup(k, std_rng());
+    let (huge_vk, huge_instances, huge_proof) = halo2::create_testdata_with_params::<
+        HugeCircuit<Bn256>,
+    >(&params, Bdfg21, acc_encoding, std_rng());
+    let (maingate_vk, maingate_instances, maingate_proof) = halo2::create_testdata_with_params::<
+        MainGateWithRange<Bn256>,
+    >(&params, Gwc19, None, std_rng());
+
+    let generator = SolidityMultiGenerator::new(vec![
+        SolidityGenerator::new(&params, &huge_vk, Bdfg21, num_instances(&huge_instances))
+            .set_acc_encoding(acc_encoding),
+        SolidityGenerator::new(
+            &params,
+            &maingate_vk,
+            Gwc19,
+            num_instances(&maingate_instances),
+        ),
+    ]);
+    let (verifier_solidities, multi_verifier_solidity) = generator.render().unwrap();
+
+    let mut evm = Evm::default();
+    let verifier_addresses = verifier_solidities
+        .iter()
+        .map(|verifier_solidity| evm.create(compile_solidity(verifier_solidity)))
+        .collect::<Vec<_>>();
+    let multi_verifier_creation_code = verifier_addresses.iter().fold(
+        compile_solidity(multi_verifier_solidity),
+        |mut code, address| {
+            code.extend([0; 12]);
+            code.extend(<[u8; 20]>::from(*address));
+            code
+        },
+    );
+    let multi_verifier_address = evm.create(multi_verifier_creation_code);
+
+    let proofs = [huge_proof, maingate_proof];
+    let instances = [huge_instances, maingate_instances];
+    let (gas_cost, output) = evm.call(
+        multi_verifier_address,
+        encode_calldata_batch(None, &proofs, &instances),
+    );
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    println!("Gas cost: {gas_cost}");
+
+    // Tampering either proof or its instances fails the combined pairing, where the tampered
+    // evaluation is still a valid scalar so each verifier still returns its pairing input
+    let layouts = [
+        ProofLayout::new(&huge_vk, Bdfg21),
+        ProofLayout::new(&maingate_vk, Gwc19),
+    ];
+    for (idx, layout) in layouts.iter().enumerate() {
+        let eval_offset = layout
+            .fields()
+            .into_iter()
+            .find_map(|(_, field)| match field {
+                ProofField::Scalar(offset) => Some(offset),
+                ProofField::EcPoint(_) => None,
+            })
+            .unwrap();
+        let mut tampered_proofs = proofs.clone();
+        tampered_proofs[idx][eval_offset + 0x1f] ^= 1;
+        let calldata = encode_calldata_batch(None, &tampered_proofs, &instances);
+        assert!(evm.try_call(multi_verifier_address, calldata).is_err());
+
+        let mut tampered_instances = instances.clone();
+        let instance = tampered_instances[idx]
+            .last_mut()
+            .unwrap()
+            .last_mut()
+            .unwrap();
+        *instance += Fr::ONE;
+        let calldata = encode_calldata_batch(None, &proofs, &tampered_instances);
+        assert!(evm.try_call(multi_verifier_address, calldata).is_err());
+    }
+
+    // Proofs given in the wrong order are rejected
+    let swapped_proofs = [proofs[1].clone(), proofs[0].clone()];
+    let swapped_instances = [instances[1].clone(), instances[0].clone()];
+    let calldata = encode_calldata_batch(None, &swapped_proofs, &swapped_instances);
+    assert!(evm.try_call(multi_verifier_address, calldata).is_err());
 }
 
-fn run_render<C: halo2::TestCircuit<Fr>>() {
+fn run_render<C: halo2::TestCircuit<Fr>>(scheme: BatchOpenScheme) {
     let acc_encoding = AccumulatorEncoding::new(0, 4, 68).into();
     let (params, vk, instances, proof) =
-        halo2::create_testdata_bdfg21::<C>(C::min_k(), acc_encoding, std_rng());
+        halo2::create_testdata::<C>(C::min_k(), scheme, acc_encoding, std_rng());
 
-    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+    let generator = SolidityGenerator::new(&params, &vk, scheme, num_instances(&instances))
         .set_acc_encoding(acc_encoding);
     let verifier_solidity = generator.render().unwrap();
     let verifier_creation_code = compile_solidity(verifier_solidity);
@@ -66,14 +1377,16 @@ fn run_render<C: halo2::TestCircuit<Fr>>() {
     let (gas_cost, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
     assert_eq!(output, [vec![0; 31], vec![1]].concat());
     println!("Gas cost: {gas_cost}");
+
+    assert_gas_estimate(&generator, gas_cost);
 }
 
-fn run_render_separately<C: halo2::TestCircuit<Fr>>() {
+fn run_render_separately<C: halo2::TestCircuit<Fr>>(scheme: BatchOpenScheme) {
     let acc_encoding = AccumulatorEncoding::new(0, 4, 68).into();
     let (params, vk, instances, _) =
-        halo2::create_testdata_bdfg21::<C>(C::min_k(), acc_encoding, std_rng());
+        halo2::create_testdata::<C>(C::min_k(), scheme, acc_encoding, std_rng());
 
-    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+    let generator = SolidityGenerator::new(&params, &vk, scheme, num_instances(&instances))
         .set_acc_encoding(acc_encoding);
     let (verifier_solidity, _vk_solidity) = generator.render_separately().unwrap();
     let verifier_creation_code = compile_solidity(&verifier_solidity);
@@ -90,8 +1403,8 @@ fn run_render_separately<C: halo2::TestCircuit<Fr>>() {
 
     for k in C::min_k()..C::min_k() + 4 {
         let (params, vk, instances, proof) =
-            halo2::create_testdata_bdfg21::<C>(k, acc_encoding, std_rng());
-        let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+            halo2::create_testdata::<C>(k, scheme, acc_encoding, std_rng());
+        let generator = SolidityGenerator::new(&params, &vk, scheme, num_instances(&instances))
             .set_acc_encoding(acc_encoding);
 
         let (verifier_solidity, vk_solidity) = generator.render_separately().unwrap();
@@ -109,6 +1422,59 @@ fn run_render_separately<C: halo2::TestCircuit<Fr>>() {
     }
 }
 
+fn run_render_batch<C: halo2::TestCircuit<Fr>>(scheme: BatchOpenScheme, separate: bool) {
+    let acc_encoding = AccumulatorEncoding::new(0, 4, 68).into();
+    let (params, vk, instances, proofs) =
+        halo2::create_testdata_batch::<C>(C::min_k(), scheme, acc_encoding, 3, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, scheme, num_instances(&instances[0]))
+        .set_acc_encoding(acc_encoding);
+
+    let mut evm = Evm::default();
+    let (verifier_address, vk_address) = if separate {
+        let (verifier_solidity, vk_solidity) = generator.render_separately().unwrap();
+        let verifier_address = evm.create(compile_solidity(verifier_solidity));
+        let vk_address = evm.create(compile_solidity(vk_solidity));
+        (verifier_address, Some(vk_address.into()))
+    } else {
+        let verifier_solidity = generator.render().unwrap();
+        (evm.create(compile_solidity(verifier_solidity)), None)
+    };
+
+    let (gas_cost, output) = evm.call(
+        verifier_address,
+        encode_calldata_batch(vk_address, &proofs, &instances),
+    );
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    println!("Gas cost of verifying {} proofs: {gas_cost}", proofs.len());
+
+    // Re-encoding each proof into a self staticcall of `pairingInput` should cost much less than
+    // the pairings saved, compared with calling `verifyProof` for each proof in a transaction
+    let separate_gas_cost = proofs
+        .iter()
+        .zip(&instances)
+        .map(|(proof, instances)| {
+            let calldata = encode_calldata(vk_address, proof, instances);
+            let (gas_cost, output) = evm.call(verifier_address, calldata);
+            assert_eq!(output, [vec![0; 31], vec![1]].concat());
+            gas_cost
+        })
+        .sum::<u64>()
+        - (proofs.len() as u64 - 1) * 21000;
+    println!("Gas cost of verifying each proof separately: {separate_gas_cost}");
+    assert!(gas_cost < separate_gas_cost);
+}
+
+fn assert_gas_estimate(generator: &SolidityGenerator, gas_cost: u64) {
+    let estimated_gas_cost = generator.estimate_gas().total();
+    println!("Estimated gas cost: {estimated_gas_cost}");
+    assert!(estimated_gas_cost.abs_diff(gas_cost) * 5 < gas_cost);
+}
+
+fn num_instances(instances: &[Vec<Fr>]) -> Vec<usize> {
+    instances.iter().map(Vec::len).collect()
+}
+
 fn std_rng() -> impl RngCore + Clone {
     StdRng::seed_from_u64(0)
 }
@@ -131,7 +1497,19 @@ fn save_generated(verifier: &str, vk: Option<&str>) {
 }
 
 mod halo2 {
-    use crate::{codegen::AccumulatorEncoding, transcript::Keccak256Transcript};
+    #[cfg(feature = "poseidon")]
+    use crate::transcript::PoseidonTranscript;
+    use crate::{
+        codegen::{
+            AccumulatorEncoding,
+            BatchOpenScheme::{self, Bdfg21, Gwc19},
+            PublicInputHash, SolidityGenerator, TranscriptHash,
+        },
+        transcript::{
+            EvmTranscript, EvmTranscriptSpec, Keccak256Spec, Keccak256Transcript, Sha256Spec,
+            Sha256Transcript,
+        },
+    };
     use halo2_proofs::{
         arithmetic::CurveAffine,
         halo2curves::{
@@ -140,72 +1518,403 @@ mod halo2 {
             group::{prime::PrimeCurveAffine, Curve, Group},
             pairing::{MillerLoopResult, MultiMillerLoop},
         },
-        plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, VerifyingKey},
-        poly::kzg::{
-            commitment::ParamsKZG,
-            multiopen::{ProverSHPLONK, VerifierSHPLONK},
-            strategy::SingleStrategy,
+        plonk::{
+            create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ProvingKey, VerifyingKey,
+        },
+        poly::{
+            commitment::{Prover, Verifier},
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                msm::DualMSM,
+                multiopen::{ProverGWC, ProverSHPLONK, VerifierGWC, VerifierSHPLONK},
+                strategy::{GuardKZG, SingleStrategy},
+            },
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
         },
-        transcript::TranscriptWriterBuffer,
     };
     use itertools::Itertools;
     use rand::RngCore;
     use ruint::aliases::U256;
-    use std::borrow::Borrow;
+    use std::{borrow::Borrow, iter};
 
     pub trait TestCircuit<F: Field>: Circuit<F> {
         fn min_k() -> u32;
 
         fn new(acc_encoding: Option<AccumulatorEncoding>, rng: impl RngCore) -> Self;
 
-        fn instances(&self) -> Vec<F>;
+        fn instances(&self) -> Vec<Vec<F>>;
+    }
+
+    /// Transcript proof is created with.
+    #[derive(Clone, Copy, Debug)]
+    pub enum Transcript {
+        Keccak256 {
+            compressed_points: bool,
+        },
+        Sha256,
+        DoubleKeccak256,
+        Blake2b,
+        #[cfg(feature = "poseidon")]
+        Poseidon,
+    }
+
+    pub const KECCAK256: Transcript = Transcript::Keccak256 {
+        compressed_points: false,
+    };
+
+    impl Transcript {
+        /// Return `generator` set to verify proof created with the transcript.
+        pub fn configure<'a>(self, generator: SolidityGenerator<'a>) -> SolidityGenerator<'a> {
+            match self {
+                Self::Keccak256 { compressed_points } => generator
+                    .set_transcript_hash::<Keccak256Spec>()
+                    .set_compressed_points(compressed_points),
+                Self::Sha256 => generator.set_transcript_hash::<Sha256Spec>(),
+                Self::DoubleKeccak256 => generator.set_transcript_hash::<DoubleKeccak256Spec>(),
+                Self::Blake2b => {
+                    generator.set_little_endian_transcript_hash(TranscriptHash::Blake2b)
+                }
+                #[cfg(feature = "poseidon")]
+                Self::Poseidon => {
+                    generator.set_little_endian_transcript_hash(TranscriptHash::Poseidon)
+                }
+            }
+        }
+
+        /// Return whether points in proof are compressed.
+        pub fn compressed_points(self) -> bool {
+            !matches!(
+                self,
+                Self::Keccak256 {
+                    compressed_points: false
+                } | Self::Sha256
+                    | Self::DoubleKeccak256
+            )
+        }
+
+        /// Return whether scalars in proof are little-endian.
+        pub fn little_endian(self) -> bool {
+            match self {
+                Self::Blake2b => true,
+                #[cfg(feature = "poseidon")]
+                Self::Poseidon => true,
+                _ => false,
+            }
+        }
+    }
+
+    /// Spec defined out of the crate, hashing twice by `keccak256`.
+    #[derive(Debug)]
+    pub struct DoubleKeccak256Spec;
+
+    impl EvmTranscriptSpec for DoubleKeccak256Spec {
+        fn hash(data: &[u8]) -> [u8; 0x20] {
+            Keccak256Spec::hash(&Keccak256Spec::hash(data))
+        }
+
+        fn yul_hash(_: &str, len: &str) -> Vec<String> {
+            vec![
+                format!("let hash := keccak256(0x00, {len})"),
+                "mstore(0x00, hash)".to_string(),
+                "hash := keccak256(0x00, 0x20)".to_string(),
+            ]
+        }
+
+        fn yul_hash_gas(num_words: u64) -> u64 {
+            Keccak256Spec::yul_hash_gas(num_words) + Keccak256Spec::yul_hash_gas(1)
+        }
     }
 
     #[allow(clippy::type_complexity)]
-    pub fn create_testdata_bdfg21<C: TestCircuit<bn256::Fr>>(
+    pub fn create_testdata<C: TestCircuit<bn256::Fr>>(
         k: u32,
+        scheme: BatchOpenScheme,
         acc_encoding: Option<AccumulatorEncoding>,
         mut rng: impl RngCore + Clone,
     ) -> (
         ParamsKZG<bn256::Bn256>,
         VerifyingKey<bn256::G1Affine>,
-        Vec<bn256::Fr>,
+        Vec<Vec<bn256::Fr>>,
         Vec<u8>,
     ) {
         let circuit = C::new(acc_encoding, rng.clone());
+        let params = ParamsKZG::<bn256::Bn256>::setup(k, &mut rng);
+        let (vk, instances, proof) =
+            create_testdata_with_circuit(&params, scheme, circuit, KECCAK256, rng);
+        (params, vk, instances, proof)
+    }
+
+    pub fn create_testdata_with_transcript<C: TestCircuit<bn256::Fr>>(
+        k: u32,
+        scheme: BatchOpenScheme,
+        transcript: Transcript,
+        mut rng: impl RngCore + Clone,
+    ) -> (
+        ParamsKZG<bn256::Bn256>,
+        VerifyingKey<bn256::G1Affine>,
+        Vec<Vec<bn256::Fr>>,
+        Vec<u8>,
+    ) {
+        let circuit = C::new(None, rng.clone());
+        let params = ParamsKZG::<bn256::Bn256>::setup(k, &mut rng);
+        let (vk, instances, proof) =
+            create_testdata_with_circuit(&params, scheme, circuit, transcript, rng);
+        (params, vk, instances, proof)
+    }
+
+    pub fn create_testdata_with_public_input_hash(
+        scheme: BatchOpenScheme,
+        public_input_hash: PublicInputHash,
+        public_data: &[u8],
+        mut rng: impl RngCore + Clone,
+    ) -> (
+        ParamsKZG<bn256::Bn256>,
+        VerifyingKey<bn256::G1Affine>,
+        Vec<Vec<bn256::Fr>>,
+        Vec<u8>,
+    ) {
+        type C = maingate::MainGateWithRange<bn256::Bn256>;
+
+        let mut instances = iter::repeat_with(|| bn256::Fr::random(&mut rng))
+            .take(10)
+            .collect_vec();
+        let offset = public_input_hash.offset;
+        instances.splice(
+            offset..offset + public_input_hash.num_limbs,
+            public_input_hash.limbs(public_data),
+        );
+        let circuit = C::with_instances(instances);
+        let params = ParamsKZG::<bn256::Bn256>::setup(C::min_k(), &mut rng);
+        let (vk, instances, proof) =
+            create_testdata_with_circuit(&params, scheme, circuit, KECCAK256, rng);
+        (params, vk, instances, proof)
+    }
+
+    pub fn create_testdata_with_params<C: TestCircuit<bn256::Fr>>(
+        params: &ParamsKZG<bn256::Bn256>,
+        scheme: BatchOpenScheme,
+        acc_encoding: Option<AccumulatorEncoding>,
+        mut rng: impl RngCore,
+    ) -> (VerifyingKey<bn256::G1Affine>, Vec<Vec<bn256::Fr>>, Vec<u8>) {
+        let circuit = C::new(acc_encoding, &mut rng);
+        create_testdata_with_circuit(params, scheme, circuit, KECCAK256, rng)
+    }
+
+    fn create_testdata_with_circuit<C: TestCircuit<bn256::Fr>>(
+        params: &ParamsKZG<bn256::Bn256>,
+        scheme: BatchOpenScheme,
+        circuit: C,
+        transcript: Transcript,
+        mut rng: impl RngCore,
+    ) -> (VerifyingKey<bn256::G1Affine>, Vec<Vec<bn256::Fr>>, Vec<u8>) {
         let instances = circuit.instances();
 
-        let params = ParamsKZG::<bn256::Bn256>::setup(k, &mut rng);
-        let vk = keygen_vk(&params, &circuit).unwrap();
-        let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
-
-        let proof = {
-            let mut transcript = Keccak256Transcript::new(Vec::new());
-            create_proof::<_, ProverSHPLONK<_>, _, _, _, _>(
-                &params,
-                &pk,
-                &[circuit],
-                &[&[&instances]],
-                &mut rng,
-                &mut transcript,
-            )
-            .unwrap();
-            transcript.finalize()
+        let vk = keygen_vk(params, &circuit).unwrap();
+        let pk = keygen_pk(params, vk.clone(), &circuit).unwrap();
+
+        let proof = match scheme {
+            Bdfg21 => create_proof_checked::<_, ProverSHPLONK<_>, VerifierSHPLONK<_>>(
+                params, &pk, circuit, &instances, transcript, &mut rng,
+            ),
+            Gwc19 => create_proof_checked::<_, ProverGWC<_>, VerifierGWC<_>>(
+                params, &pk, circuit, &instances, transcript, &mut rng,
+            ),
+        };
+
+        (vk, instances, proof)
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn create_testdata_batch<C: TestCircuit<bn256::Fr>>(
+        k: u32,
+        scheme: BatchOpenScheme,
+        acc_encoding: Option<AccumulatorEncoding>,
+        num_proofs: usize,
+        mut rng: impl RngCore + Clone,
+    ) -> (
+        ParamsKZG<bn256::Bn256>,
+        VerifyingKey<bn256::G1Affine>,
+        Vec<Vec<Vec<bn256::Fr>>>,
+        Vec<Vec<u8>>,
+    ) {
+        let circuits = iter::repeat_with(|| C::new(acc_encoding, &mut rng))
+            .take(num_proofs)
+            .collect_vec();
+
+        let params = ParamsKZG::<bn256::Bn256>::setup(k, &mut rng);
+        let vk = keygen_vk(&params, &circuits[0]).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &circuits[0]).unwrap();
+
+        let (instances, proofs) = circuits
+            .into_iter()
+            .map(|circuit| {
+                let instances = circuit.instances();
+                let proof = match scheme {
+                    Bdfg21 => create_proof_checked::<_, ProverSHPLONK<_>, VerifierSHPLONK<_>>(
+                        &params, &pk, circuit, &instances, KECCAK256, &mut rng,
+                    ),
+                    Gwc19 => create_proof_checked::<_, ProverGWC<_>, VerifierGWC<_>>(
+                        &params, &pk, circuit, &instances, KECCAK256, &mut rng,
+                    ),
+                };
+                (instances, proof)
+            })
+            .unzip();
+
+        (params, vk, instances, proofs)
+    }
+
+    fn create_proof_checked<'a, C, P, V>(
+        params: &'a ParamsKZG<bn256::Bn256>,
+        pk: &ProvingKey<bn256::G1Affine>,
+        circuit: C,
+        instances: &[Vec<bn256::Fr>],
+        transcript: Transcript,
+        mut rng: impl RngCore,
+    ) -> Vec<u8>
+    where
+        C: Circuit<bn256::Fr>,
+        P: Prover<'a, KZGCommitmentScheme<bn256::Bn256>>,
+        V: Verifier<
+            'a,
+            KZGCommitmentScheme<bn256::Bn256>,
+            Guard = GuardKZG<'a, bn256::Bn256>,
+            MSMAccumulator = DualMSM<'a, bn256::Bn256>,
+        >,
+    {
+        let instances = instances.iter().map(Vec::as_slice).collect_vec();
+        let instances = &[instances.as_slice()];
+        let proof = match transcript {
+            Transcript::Keccak256 { compressed_points } => {
+                let mut transcript =
+                    Keccak256Transcript::new(Vec::new()).set_compressed_points(compressed_points);
+                create_proof::<_, P, _, _, _, _>(
+                    params,
+                    pk,
+                    &[circuit],
+                    instances,
+                    &mut rng,
+                    &mut transcript,
+                )
+                .unwrap();
+                transcript.finalize()
+            }
+            Transcript::Sha256 => {
+                let mut transcript = Sha256Transcript::new(Vec::new());
+                create_proof::<_, P, _, _, _, _>(
+                    params,
+                    pk,
+                    &[circuit],
+                    instances,
+                    &mut rng,
+                    &mut transcript,
+                )
+                .unwrap();
+                transcript.finalize()
+            }
+            Transcript::DoubleKeccak256 => {
+                let mut transcript = EvmTranscript::<_, _, DoubleKeccak256Spec>::new(Vec::new());
+                create_proof::<_, P, _, _, _, _>(
+                    params,
+                    pk,
+                    &[circuit],
+                    instances,
+                    &mut rng,
+                    &mut transcript,
+                )
+                .unwrap();
+                transcript.finalize()
+            }
+            Transcript::Blake2b => {
+                let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(Vec::new());
+                create_proof::<_, P, _, _, _, _>(
+                    params,
+                    pk,
+                    &[circuit],
+                    instances,
+                    &mut rng,
+                    &mut transcript,
+                )
+                .unwrap();
+                transcript.finalize()
+            }
+            #[cfg(feature = "poseidon")]
+            Transcript::Poseidon => {
+                let mut transcript = PoseidonTranscript::new(Vec::new());
+                create_proof::<_, P, _, _, _, _>(
+                    params,
+                    pk,
+                    &[circuit],
+                    instances,
+                    &mut rng,
+                    &mut transcript,
+                )
+                .unwrap();
+                transcript.finalize()
+            }
         };
 
-        let result = {
-            let mut transcript = Keccak256Transcript::new(proof.as_slice());
-            verify_proof::<_, VerifierSHPLONK<_>, _, _, SingleStrategy<_>>(
-                &params,
-                pk.get_vk(),
-                SingleStrategy::new(&params),
-                &[&[&instances]],
-                &mut transcript,
-            )
+        let strategy = SingleStrategy::new(params);
+        let result = match transcript {
+            Transcript::Keccak256 { compressed_points } => {
+                let mut transcript = Keccak256Transcript::new(proof.as_slice())
+                    .set_compressed_points(compressed_points);
+                verify_proof::<_, V, _, _, SingleStrategy<_>>(
+                    params,
+                    pk.get_vk(),
+                    strategy,
+                    instances,
+                    &mut transcript,
+                )
+            }
+            Transcript::Sha256 => {
+                let mut transcript = Sha256Transcript::new(proof.as_slice());
+                verify_proof::<_, V, _, _, SingleStrategy<_>>(
+                    params,
+                    pk.get_vk(),
+                    strategy,
+                    instances,
+                    &mut transcript,
+                )
+            }
+            Transcript::DoubleKeccak256 => {
+                let mut transcript =
+                    EvmTranscript::<_, _, DoubleKeccak256Spec>::new(proof.as_slice());
+                verify_proof::<_, V, _, _, SingleStrategy<_>>(
+                    params,
+                    pk.get_vk(),
+                    strategy,
+                    instances,
+                    &mut transcript,
+                )
+            }
+            Transcript::Blake2b => {
+                let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof.as_slice());
+                verify_proof::<_, V, _, _, SingleStrategy<_>>(
+                    params,
+                    pk.get_vk(),
+                    strategy,
+                    instances,
+                    &mut transcript,
+                )
+            }
+            #[cfg(feature = "poseidon")]
+            Transcript::Poseidon => {
+                let mut transcript = PoseidonTranscript::new(proof.as_slice());
+                verify_proof::<_, V, _, _, SingleStrategy<_>>(
+                    params,
+                    pk.get_vk(),
+                    strategy,
+                    instances,
+                    &mut transcript,
+                )
+            }
         };
         assert!(result.is_ok());
 
-        (params, vk, instances, proof)
+        proof
     }
 
     fn random_accumulator_limbs<M>(
@@ -318,8 +2027,8 @@ mod halo2 {
                 Self(instances)
             }
 
-            fn instances(&self) -> Vec<M::Scalar> {
-                self.0.clone()
+            fn instances(&self) -> Vec<Vec<M::Scalar>> {
+                vec![self.0.clone(), self.0[..5].iter().rev().cloned().collect()]
             }
         }
 
@@ -332,7 +2041,8 @@ mod halo2 {
                 [Selector; 10],
                 [Column<Fixed>; 10],
                 [Column<Advice>; 10],
-                Column<Instance>,
+                [Column<Instance>; 2],
+                Selector,
             );
             type FloorPlanner = SimpleFloorPlanner;
             #[cfg(feature = "halo2_circuit_params")]
@@ -365,7 +2075,8 @@ mod halo2 {
                     .unzip::<_, _, Vec<_>, Vec<_>>();
                 let advices: [_; 10] = advices.try_into().unwrap();
                 let challenges: [_; 10] = challenges.try_into().unwrap();
-                let instance = meta.instance_column();
+                let instances = [(); 2].map(|_| meta.instance_column());
+                let instance_selector = meta.selector();
 
                 meta.create_gate("", |meta| {
                     let selectors = selectors.map(|selector| meta.query_selector(selector));
@@ -405,21 +2116,43 @@ mod halo2 {
                     });
                 }
 
+                // Second instance column is reversed first 5 instances of first instance column,
+                // so at 3rd row they should match with opposite rotation.
+                meta.create_gate("", |meta| {
+                    let q = meta.query_selector(instance_selector);
+                    [(-1, 1), (2, -2)]
+                        .map(|(lhs, rhs)| {
+                            let lhs = meta.query_instance(instances[1], Rotation(lhs));
+                            let rhs = meta.query_instance(instances[0], Rotation(rhs));
+                            q.clone() * (lhs - rhs)
+                        })
+                        .to_vec()
+                });
+
                 fixeds.map(|column| meta.enable_equality(column));
                 advices.map(|column| meta.enable_equality(column));
-                meta.enable_equality(instance);
-
-                (selectors, complex_selectors, fixeds, advices, instance)
+                instances.map(|column| meta.enable_equality(column));
+
+                (
+                    selectors,
+                    complex_selectors,
+                    fixeds,
+                    advices,
+                    instances,
+                    instance_selector,
+                )
             }
 
             fn synthesize(
                 &self,
-                (selectors, complex_selectors, fixeds, advices, instance): Self::Config,
+                (selectors, complex_selectors, fixeds, advices, instances, instance_selector): Self::Config,
                 mut layouter: impl Layouter<M::Scalar>,
             ) -> Result<(), plonk::Error> {
                 let assigneds = layouter.assign_region(
                     || "",
                     |mut region| {
+                        instance_selector.enable(&mut region, 2)?;
+
                         let offset = &mut 10;
                         let mut next_offset = || mem::replace(offset, *offset + 1);
 
@@ -441,7 +2174,131 @@ mod halo2 {
                             .try_collect::<_, Vec<_>, _>()
                     },
                 )?;
-                for (idx, assigned) in izip!(0.., assigneds) {
+                for (idx, assigned) in izip!(0.., &assigneds) {
+                    layouter.constrain_instance(assigned.cell(), instances[0], idx)?;
+                }
+                for (idx, assigned) in izip!(0.., assigneds.iter().take(5).rev()) {
+                    layouter.constrain_instance(assigned.cell(), instances[1], idx)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub mod shuffle {
+        use crate::{
+            codegen::AccumulatorEncoding,
+            test::halo2::{random_accumulator_limbs, TestCircuit},
+        };
+        use halo2_proofs::{
+            arithmetic::CurveAffine,
+            circuit::{Layouter, SimpleFloorPlanner, Value},
+            halo2curves::{
+                ff::{Field, PrimeField},
+                pairing::MultiMillerLoop,
+            },
+            plonk::{self, Advice, Circuit, Column, ConstraintSystem, Instance, Selector},
+            poly::Rotation,
+        };
+        use itertools::{izip, Itertools};
+        use rand::RngCore;
+        use std::iter;
+
+        /// Circuit with instances copied into the first advice column, and their reverse assigned
+        /// to the second one, which is checked by shuffles instead of copy constraints.
+        #[derive(Clone, Debug, Default)]
+        pub struct ShuffleCircuit<M: MultiMillerLoop>(Vec<M::Scalar>);
+
+        impl<M: MultiMillerLoop> TestCircuit<M::Scalar> for ShuffleCircuit<M>
+        where
+            M: MultiMillerLoop,
+            <M::G1Affine as CurveAffine>::Base: PrimeField<Repr = [u8; 0x20]>,
+            M::Scalar: PrimeField<Repr = [u8; 0x20]>,
+        {
+            fn min_k() -> u32 {
+                5
+            }
+
+            fn new(acc_encoding: Option<AccumulatorEncoding>, mut rng: impl RngCore) -> Self {
+                let instances = if let Some(acc_encoding) = acc_encoding {
+                    random_accumulator_limbs::<M>(acc_encoding, rng)
+                } else {
+                    iter::repeat_with(|| M::Scalar::random(&mut rng))
+                        .take(10)
+                        .collect()
+                };
+                Self(instances)
+            }
+
+            fn instances(&self) -> Vec<Vec<M::Scalar>> {
+                vec![self.0.clone()]
+            }
+        }
+
+        impl<M: MultiMillerLoop> Circuit<M::Scalar> for ShuffleCircuit<M>
+        where
+            M::Scalar: PrimeField,
+        {
+            type Config = (Selector, [Column<Advice>; 2], Column<Instance>);
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "halo2_circuit_params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                unimplemented!()
+            }
+
+            fn configure(meta: &mut ConstraintSystem<M::Scalar>) -> Self::Config {
+                let selector = meta.complex_selector();
+                let advices = [(); 2].map(|_| meta.advice_column());
+                let instance = meta.instance_column();
+
+                // Single expression
+                meta.shuffle("", |meta| {
+                    let q = meta.query_selector(selector);
+                    let [a, b] = advices.map(|column| meta.query_advice(column, Rotation::cur()));
+                    vec![(q.clone() * a, q * b)]
+                });
+                // Tuple of expressions compressed by theta, where `(a_i, b_i)` is `(b_j, a_j)`
+                // with `j = n - 1 - i`
+                meta.shuffle("", |meta| {
+                    let q = meta.query_selector(selector);
+                    let [a, b] = advices.map(|column| meta.query_advice(column, Rotation::cur()));
+                    vec![
+                        (q.clone() * a.clone(), q.clone() * b.clone()),
+                        (q.clone() * b, q * a),
+                    ]
+                });
+
+                meta.enable_equality(advices[0]);
+                meta.enable_equality(instance);
+
+                (selector, advices, instance)
+            }
+
+            fn synthesize(
+                &self,
+                (selector, advices, instance): Self::Config,
+                mut layouter: impl Layouter<M::Scalar>,
+            ) -> Result<(), plonk::Error> {
+                let assigneds = layouter.assign_region(
+                    || "",
+                    |mut region| {
+                        izip!(0.., &self.0, self.0.iter().rev())
+                            .map(|(offset, a, b)| {
+                                selector.enable(&mut region, offset)?;
+                                region.assign_advice(
+                                    || "",
+                                    advices[1],
+                                    offset,
+                                    || Value::known(*b),
+                                )?;
+                                region.assign_advice(|| "", advices[0], offset, || Value::known(*a))
+                            })
+                            .try_collect::<_, Vec<_>, _>()
+                    },
+                )?;
+                for (idx, assigned) in izip!(0.., &assigneds) {
                     layouter.constrain_instance(assigned.cell(), instance, idx)?;
                 }
                 Ok(())
@@ -449,6 +2306,210 @@ mod halo2 {
         }
     }
 
+    pub mod lookup {
+        use crate::{
+            codegen::AccumulatorEncoding,
+            test::halo2::{random_accumulator_limbs, TestCircuit},
+        };
+        use halo2_proofs::{
+            arithmetic::CurveAffine,
+            circuit::{Layouter, SimpleFloorPlanner, Value},
+            halo2curves::{
+                ff::{Field, PrimeField},
+                pairing::MultiMillerLoop,
+            },
+            plonk::{self, Advice, Circuit, Column, ConstraintSystem, Selector},
+            poly::Rotation,
+        };
+        use itertools::izip;
+        use rand::RngCore;
+        use std::iter;
+
+        /// Circuit without any copy constraint, where instances are checked by a gate against the
+        /// first advice column, and their reverse assigned to the second one is looked up in the
+        /// instance column, so it has no permutation column at all.
+        #[derive(Clone, Debug, Default)]
+        pub struct LookupCircuit<M: MultiMillerLoop>(Vec<M::Scalar>);
+
+        impl<M: MultiMillerLoop> TestCircuit<M::Scalar> for LookupCircuit<M>
+        where
+            M: MultiMillerLoop,
+            <M::G1Affine as CurveAffine>::Base: PrimeField<Repr = [u8; 0x20]>,
+            M::Scalar: PrimeField<Repr = [u8; 0x20]>,
+        {
+            fn min_k() -> u32 {
+                5
+            }
+
+            fn new(acc_encoding: Option<AccumulatorEncoding>, mut rng: impl RngCore) -> Self {
+                let instances = if let Some(acc_encoding) = acc_encoding {
+                    random_accumulator_limbs::<M>(acc_encoding, rng)
+                } else {
+                    iter::repeat_with(|| M::Scalar::random(&mut rng))
+                        .take(10)
+                        .collect()
+                };
+                Self(instances)
+            }
+
+            fn instances(&self) -> Vec<Vec<M::Scalar>> {
+                vec![self.0.clone()]
+            }
+        }
+
+        impl<M: MultiMillerLoop> Circuit<M::Scalar> for LookupCircuit<M>
+        where
+            M::Scalar: PrimeField,
+        {
+            type Config = (Selector, [Column<Advice>; 2]);
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "halo2_circuit_params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                unimplemented!()
+            }
+
+            fn configure(meta: &mut ConstraintSystem<M::Scalar>) -> Self::Config {
+                let selector = meta.complex_selector();
+                let advices = [(); 2].map(|_| meta.advice_column());
+                let instance = meta.instance_column();
+
+                meta.create_gate("", |meta| {
+                    let q = meta.query_selector(selector);
+                    let a = meta.query_advice(advices[0], Rotation::cur());
+                    let instance = meta.query_instance(instance, Rotation::cur());
+                    Some(q * (a - instance))
+                });
+                meta.lookup_any("", |meta| {
+                    let q = meta.query_selector(selector);
+                    let b = meta.query_advice(advices[1], Rotation::cur());
+                    let instance = meta.query_instance(instance, Rotation::cur());
+                    vec![(q * b, instance)]
+                });
+
+                (selector, advices)
+            }
+
+            fn synthesize(
+                &self,
+                (selector, advices): Self::Config,
+                mut layouter: impl Layouter<M::Scalar>,
+            ) -> Result<(), plonk::Error> {
+                layouter.assign_region(
+                    || "",
+                    |mut region| {
+                        for (offset, a, b) in izip!(0.., &self.0, self.0.iter().rev()) {
+                            selector.enable(&mut region, offset)?;
+                            region.assign_advice(|| "", advices[0], offset, || Value::known(*a))?;
+                            region.assign_advice(|| "", advices[1], offset, || Value::known(*b))?;
+                        }
+                        Ok(())
+                    },
+                )
+            }
+        }
+    }
+
+    pub mod identity {
+        use crate::{
+            codegen::AccumulatorEncoding,
+            test::halo2::{random_accumulator_limbs, TestCircuit},
+        };
+        use halo2_proofs::{
+            arithmetic::CurveAffine,
+            circuit::{Layouter, SimpleFloorPlanner, Value},
+            halo2curves::{
+                ff::{Field, PrimeField},
+                pairing::MultiMillerLoop,
+            },
+            plonk::{self, Advice, Circuit, Column, ConstraintSystem, Selector},
+            poly::Rotation,
+        };
+        use rand::RngCore;
+        use std::iter;
+
+        /// Circuit with an unblinded advice column left all zero, so its commitment is identity
+        /// and is the first point of proof, and instances checked by a gate against the other
+        /// advice column.
+        #[derive(Clone, Debug, Default)]
+        pub struct ZeroColumnCircuit<M: MultiMillerLoop>(Vec<M::Scalar>);
+
+        impl<M: MultiMillerLoop> TestCircuit<M::Scalar> for ZeroColumnCircuit<M>
+        where
+            M: MultiMillerLoop,
+            <M::G1Affine as CurveAffine>::Base: PrimeField<Repr = [u8; 0x20]>,
+            M::Scalar: PrimeField<Repr = [u8; 0x20]>,
+        {
+            fn min_k() -> u32 {
+                5
+            }
+
+            fn new(acc_encoding: Option<AccumulatorEncoding>, mut rng: impl RngCore) -> Self {
+                let instances = if let Some(acc_encoding) = acc_encoding {
+                    random_accumulator_limbs::<M>(acc_encoding, rng)
+                } else {
+                    iter::repeat_with(|| M::Scalar::random(&mut rng))
+                        .take(10)
+                        .collect()
+                };
+                Self(instances)
+            }
+
+            fn instances(&self) -> Vec<Vec<M::Scalar>> {
+                vec![self.0.clone()]
+            }
+        }
+
+        impl<M: MultiMillerLoop> Circuit<M::Scalar> for ZeroColumnCircuit<M>
+        where
+            M::Scalar: PrimeField,
+        {
+            type Config = (Selector, [Column<Advice>; 2]);
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "halo2_circuit_params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                unimplemented!()
+            }
+
+            fn configure(meta: &mut ConstraintSystem<M::Scalar>) -> Self::Config {
+                let selector = meta.selector();
+                let zero = meta.unblinded_advice_column();
+                let advice = meta.advice_column();
+                let instance = meta.instance_column();
+
+                meta.create_gate("", |meta| {
+                    let q = meta.query_selector(selector);
+                    let zero = meta.query_advice(zero, Rotation::cur());
+                    let advice = meta.query_advice(advice, Rotation::cur());
+                    let instance = meta.query_instance(instance, Rotation::cur());
+                    [q.clone() * zero, q * (advice - instance)]
+                });
+
+                (selector, [zero, advice])
+            }
+
+            fn synthesize(
+                &self,
+                (selector, [_, advice]): Self::Config,
+                mut layouter: impl Layouter<M::Scalar>,
+            ) -> Result<(), plonk::Error> {
+                layouter.assign_region(
+                    || "",
+                    |mut region| {
+                        for (offset, value) in self.0.iter().enumerate() {
+                            selector.enable(&mut region, offset)?;
+                            region.assign_advice(|| "", advice, offset, || Value::known(*value))?;
+                        }
+                        Ok(())
+                    },
+                )
+            }
+        }
+    }
+
     pub mod maingate {
         use crate::{
             codegen::AccumulatorEncoding,
@@ -510,6 +2571,12 @@ mod halo2 {
             instances: Vec<M::Scalar>,
         }
 
+        impl<M: MultiMillerLoop> MainGateWithRange<M> {
+            pub fn with_instances(instances: Vec<M::Scalar>) -> Self {
+                Self { instances }
+            }
+        }
+
         impl<M> TestCircuit<M::Scalar> for MainGateWithRange<M>
         where
             M: MultiMillerLoop,
@@ -531,8 +2598,8 @@ mod halo2 {
                 Self { instances }
             }
 
-            fn instances(&self) -> Vec<M::Scalar> {
-                self.instances.clone()
+            fn instances(&self) -> Vec<Vec<M::Scalar>> {
+                vec![self.instances.clone()]
             }
         }
 
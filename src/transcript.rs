@@ -1,5 +1,9 @@
 use halo2_proofs::{
-    halo2curves::{ff::PrimeField, Coordinates, CurveAffine},
+    halo2curves::{
+        ff::{Field, PrimeField},
+        group::prime::PrimeCurveAffine,
+        Coordinates, CurveAffine,
+    },
     transcript::{
         EncodedChallenge, Transcript, TranscriptRead, TranscriptReadBuffer, TranscriptWrite,
         TranscriptWriterBuffer,
@@ -7,30 +11,127 @@ use halo2_proofs::{
 };
 use itertools::{chain, Itertools};
 use ruint::aliases::U256;
+use sha2::Sha256;
 use sha3::{Digest, Keccak256};
 use std::{
+    fmt::Debug,
     io::{self, Read, Write},
     marker::PhantomData,
     mem,
 };
 
-/// Transcript using Keccak256 as hash function in Fiat-Shamir transformation.
+#[cfg(feature = "poseidon")]
+pub(crate) mod poseidon;
+
+#[cfg(feature = "poseidon")]
+pub use self::poseidon::PoseidonTranscript;
+
+/// Hash function of [`EvmTranscript`], which defines both how challenges are squeezed in Rust and
+/// how the verifier rendered with
+/// [`SolidityGenerator::set_transcript_hash`](crate::SolidityGenerator::set_transcript_hash)
+/// squeezes them in Yul.
+///
+/// Each squeeze hashes the last hash if any followed by data absorbed since then, with an extra byte
+/// `0x01` when it's only 32 bytes, and the hash interpreted as big-endian integer modulo scalar field
+/// is the challenge.
+pub trait EvmTranscriptSpec: Debug {
+    /// Return hash of `data`.
+    fn hash(data: &[u8]) -> [u8; 0x20];
+
+    /// Return Yul statements that hash `memory[0x00..len]` into a new variable `hash`, and update
+    /// `success` if it calls a precompile.
+    fn yul_hash(success: &str, len: &str) -> Vec<String>;
+
+    /// Return address of precompile called by [`EvmTranscriptSpec::yul_hash`] if any.
+    fn precompile() -> Option<u8> {
+        None
+    }
+
+    /// Return gas of [`EvmTranscriptSpec::yul_hash`] hashing `num_words` words, used by
+    /// [`SolidityGenerator::estimate_gas`](crate::SolidityGenerator::estimate_gas).
+    fn yul_hash_gas(num_words: u64) -> u64;
+}
+
+/// [`EvmTranscriptSpec`] of Keccak256 by opcode `keccak256`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Keccak256Spec;
+
+impl EvmTranscriptSpec for Keccak256Spec {
+    fn hash(data: &[u8]) -> [u8; 0x20] {
+        Keccak256::digest(data).into()
+    }
+
+    fn yul_hash(_: &str, len: &str) -> Vec<String> {
+        vec![format!("let hash := keccak256(0x00, {len})")]
+    }
+
+    fn yul_hash_gas(num_words: u64) -> u64 {
+        30 + 6 * num_words
+    }
+}
+
+/// [`EvmTranscriptSpec`] of SHA-256 by precompile `0x02`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sha256Spec;
+
+impl EvmTranscriptSpec for Sha256Spec {
+    fn hash(data: &[u8]) -> [u8; 0x20] {
+        Sha256::digest(data).into()
+    }
+
+    fn yul_hash(success: &str, len: &str) -> Vec<String> {
+        vec![
+            format!(
+                "{success} := and({success}, staticcall(gas(), 0x02, 0x00, {len}, 0x00, 0x20))"
+            ),
+            "let hash := mload(0x00)".to_string(),
+        ]
+    }
+
+    fn precompile() -> Option<u8> {
+        Some(0x02)
+    }
+
+    fn yul_hash_gas(num_words: u64) -> u64 {
+        // `staticcall` to warm precompile, and 60 plus 12 per word of input
+        100 + 60 + 12 * num_words
+    }
+}
+
+/// Transcript using hash function of `H` in Fiat-Shamir transformation.
 #[derive(Debug, Default)]
-pub struct Keccak256Transcript<C, S> {
+pub struct EvmTranscript<C, S, H> {
     stream: S,
     buf: Vec<u8>,
-    _marker: PhantomData<C>,
+    compressed_points: bool,
+    _marker: PhantomData<(C, H)>,
 }
 
-impl<C, S> Keccak256Transcript<C, S> {
-    /// Return a `Keccak256Transcript` with empty buffer.
+/// Transcript using Keccak256 as hash function in Fiat-Shamir transformation.
+pub type Keccak256Transcript<C, S> = EvmTranscript<C, S, Keccak256Spec>;
+
+/// Transcript using SHA-256 as hash function in Fiat-Shamir transformation.
+pub type Sha256Transcript<C, S> = EvmTranscript<C, S, Sha256Spec>;
+
+impl<C, S, H> EvmTranscript<C, S, H> {
+    /// Return an `EvmTranscript` with empty buffer.
     pub fn new(stream: S) -> Self {
         Self {
             stream,
             buf: Vec::new(),
+            compressed_points: false,
             _marker: PhantomData,
         }
     }
+
+    /// Set whether points are written and read as x coordinate with parity of y coordinate in the
+    /// most significant bit, instead of both coordinates. Default is `false`.
+    ///
+    /// Points are always hashed with both coordinates, so challenges are the same either way.
+    pub fn set_compressed_points(mut self, compressed_points: bool) -> Self {
+        self.compressed_points = compressed_points;
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -55,10 +156,11 @@ where
     }
 }
 
-impl<C, S> Transcript<C, ChallengeEvm<C>> for Keccak256Transcript<C, S>
+impl<C, S, H> Transcript<C, ChallengeEvm<C>> for EvmTranscript<C, S, H>
 where
     C: CurveAffine,
     C::Scalar: PrimeField<Repr = [u8; 0x20]>,
+    H: EvmTranscriptSpec,
 {
     fn squeeze_challenge(&mut self) -> ChallengeEvm<C> {
         let buf_len = self.buf.len();
@@ -67,19 +169,13 @@ where
             if buf_len == 0x20 { Some(1) } else { None }
         ]
         .collect_vec();
-        let hash: [u8; 0x20] = Keccak256::digest(data).into();
+        let hash = H::hash(&data);
         self.buf = hash.to_vec();
         ChallengeEvm::new(&hash)
     }
 
     fn common_point(&mut self, ec_point: C) -> io::Result<()> {
-        let coords: Coordinates<C> = Option::from(ec_point.coordinates()).ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                "Invalid elliptic curve point".to_string(),
-            )
-        })?;
-        [coords.x(), coords.y()].map(|coordinate| {
+        ec_point_coordinates(ec_point).map(|coordinate| {
             self.buf
                 .extend(coordinate.to_repr().as_ref().iter().rev().cloned());
         });
@@ -92,27 +188,55 @@ where
     }
 }
 
-impl<C, R: Read> TranscriptRead<C, ChallengeEvm<C>> for Keccak256Transcript<C, R>
+impl<C, R: Read, H> TranscriptRead<C, ChallengeEvm<C>> for EvmTranscript<C, R, H>
 where
     C: CurveAffine,
     C::Scalar: PrimeField<Repr = [u8; 0x20]>,
+    H: EvmTranscriptSpec,
 {
     fn read_point(&mut self) -> io::Result<C> {
-        let mut reprs = [<C::Base as PrimeField>::Repr::default(); 2];
-        for repr in &mut reprs {
+        let ec_point = if self.compressed_points {
+            let mut repr = <C::Base as PrimeField>::Repr::default();
             self.stream.read_exact(repr.as_mut())?;
+            let is_y_odd = repr.as_ref()[0] & 0x80 != 0;
+            repr.as_mut()[0] &= 0x7f;
             repr.as_mut().reverse();
-        }
-        let [x, y] = reprs.map(|repr| Option::from(C::Base::from_repr(repr)));
-        let ec_point = x
-            .zip(y)
-            .and_then(|(x, y)| Option::from(C::from_xy(x, y)))
-            .ok_or_else(|| {
-                io::Error::new(
-                    io::ErrorKind::Other,
-                    "Invalid elliptic curve point".to_string(),
-                )
-            })?;
+            Option::from(C::Base::from_repr(repr)).and_then(|x: C::Base| {
+                if bool::from(x.is_zero()) && !is_y_odd {
+                    return Some(C::identity());
+                }
+                let y: C::Base = Option::from((x.square() * x + C::a() * x + C::b()).sqrt())?;
+                let y = if bool::from(y.is_odd()) == is_y_odd {
+                    y
+                } else {
+                    -y
+                };
+                // Reject y = 0 with parity set, which would otherwise be read as valid
+                if bool::from(y.is_odd()) != is_y_odd {
+                    return None;
+                }
+                Option::from(C::from_xy(x, y))
+            })
+        } else {
+            let mut reprs = [<C::Base as PrimeField>::Repr::default(); 2];
+            for repr in &mut reprs {
+                self.stream.read_exact(repr.as_mut())?;
+                repr.as_mut().reverse();
+            }
+            let [x, y] = reprs.map(|repr| Option::<C::Base>::from(C::Base::from_repr(repr)));
+            x.zip(y).and_then(|(x, y)| {
+                if bool::from(x.is_zero() & y.is_zero()) {
+                    return Some(C::identity());
+                }
+                Option::from(C::from_xy(x, y))
+            })
+        };
+        let ec_point = ec_point.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "Invalid elliptic curve point".to_string(),
+            )
+        })?;
         self.common_point(ec_point)?;
         Ok(ec_point)
     }
@@ -128,25 +252,35 @@ where
     }
 }
 
-impl<C, R: Read> TranscriptReadBuffer<R, C, ChallengeEvm<C>> for Keccak256Transcript<C, R>
+impl<C, R: Read, H> TranscriptReadBuffer<R, C, ChallengeEvm<C>> for EvmTranscript<C, R, H>
 where
     C: CurveAffine,
     C::Scalar: PrimeField<Repr = [u8; 0x20]>,
+    H: EvmTranscriptSpec,
 {
     fn init(reader: R) -> Self {
-        Keccak256Transcript::new(reader)
+        EvmTranscript::new(reader)
     }
 }
 
-impl<C, W: Write> TranscriptWrite<C, ChallengeEvm<C>> for Keccak256Transcript<C, W>
+impl<C, W: Write, H> TranscriptWrite<C, ChallengeEvm<C>> for EvmTranscript<C, W, H>
 where
     C: CurveAffine,
     C::Scalar: PrimeField<Repr = [u8; 0x20]>,
+    H: EvmTranscriptSpec,
 {
     fn write_point(&mut self, ec_point: C) -> io::Result<()> {
         self.common_point(ec_point)?;
-        let coords = ec_point.coordinates().unwrap();
-        for coord in [coords.x(), coords.y()] {
+        let [x, y] = ec_point_coordinates(ec_point);
+        if self.compressed_points {
+            let mut repr = x.to_repr();
+            repr.as_mut().reverse();
+            if bool::from(y.is_odd()) {
+                repr.as_mut()[0] |= 0x80;
+            }
+            return self.stream.write_all(repr.as_ref());
+        }
+        for coord in [x, y] {
             let mut repr = coord.to_repr();
             repr.as_mut().reverse();
             self.stream.write_all(repr.as_ref())?;
@@ -162,13 +296,14 @@ where
     }
 }
 
-impl<C, W: Write> TranscriptWriterBuffer<W, C, ChallengeEvm<C>> for Keccak256Transcript<C, W>
+impl<C, W: Write, H> TranscriptWriterBuffer<W, C, ChallengeEvm<C>> for EvmTranscript<C, W, H>
 where
     C: CurveAffine,
     C::Scalar: PrimeField<Repr = [u8; 0x20]>,
+    H: EvmTranscriptSpec,
 {
     fn init(writer: W) -> Self {
-        Keccak256Transcript::new(writer)
+        EvmTranscript::new(writer)
     }
 
     fn finalize(self) -> W {
@@ -176,6 +311,17 @@ where
     }
 }
 
+/// Return coordinates of `ec_point`, or `(0, 0)` for identity, which is how identity is written in
+/// proof and absorbed by all transcripts, and also how EVM precompiles `ecAdd` and `ecMul` take it.
+///
+/// No point on BN254 has `x = 0` since `3` is not a quadratic residue, so compressed identity as zero
+/// is never ambiguous.
+fn ec_point_coordinates<C: CurveAffine>(ec_point: C) -> [C::Base; 2] {
+    Option::<Coordinates<C>>::from(ec_point.coordinates())
+        .map(|coords| [*coords.x(), *coords.y()])
+        .unwrap_or([C::Base::ZERO; 2])
+}
+
 fn u256_to_fe<F>(value: U256) -> F
 where
     F: PrimeField<Repr = [u8; 0x20]>,
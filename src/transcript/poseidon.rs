@@ -0,0 +1,254 @@
+use crate::transcript::{ec_point_coordinates, u256_to_fe};
+use ::poseidon::{Poseidon, Spec};
+use halo2_proofs::{
+    halo2curves::{
+        ff::{FromUniformBytes, PrimeField},
+        group::GroupEncoding,
+        CurveAffine,
+    },
+    transcript::{
+        EncodedChallenge, Transcript, TranscriptRead, TranscriptReadBuffer, TranscriptWrite,
+        TranscriptWriterBuffer,
+    },
+};
+use itertools::chain;
+use ruint::aliases::U256;
+use std::io::{self, Read, Write};
+
+const T: usize = 5;
+const RATE: usize = 4;
+const R_F: usize = 8;
+const R_P: usize = 60;
+
+/// Transcript using Poseidon as hash function in Fiat-Shamir transformation, which is the same as
+/// `PoseidonTranscript` of `snark-verifier` with `NativeLoader` and `T = 5`, `RATE = 4`, `R_F = 8`
+/// and `R_P = 60`, so proof for aggregation circuit can also be verified on EVM directly.
+///
+/// Points are written compressed and scalars are written in little-endian as `halo2`'s
+/// `Blake2bWrite` does, and points are absorbed as both coordinates reduced into scalar field.
+#[derive(Debug)]
+pub struct PoseidonTranscript<C: CurveAffine, S> {
+    stream: S,
+    sponge: Poseidon<C::Scalar, T, RATE>,
+}
+
+impl<C, S> PoseidonTranscript<C, S>
+where
+    C: CurveAffine,
+    C::Scalar: FromUniformBytes<64>,
+{
+    /// Return a `PoseidonTranscript` with initial state.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            sponge: Poseidon::new(R_F, R_P),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ChallengeScalar<C: CurveAffine>(C::Scalar);
+
+impl<C: CurveAffine> EncodedChallenge<C> for ChallengeScalar<C> {
+    type Input = C::Scalar;
+
+    fn new(challenge_input: &C::Scalar) -> Self {
+        ChallengeScalar(*challenge_input)
+    }
+
+    fn get_scalar(&self) -> C::Scalar {
+        self.0
+    }
+}
+
+impl<C, S> Transcript<C, ChallengeScalar<C>> for PoseidonTranscript<C, S>
+where
+    C: CurveAffine,
+    C::Base: PrimeField<Repr = [u8; 0x20]>,
+    C::Scalar: PrimeField<Repr = [u8; 0x20]> + FromUniformBytes<64>,
+{
+    fn squeeze_challenge(&mut self) -> ChallengeScalar<C> {
+        ChallengeScalar::new(&self.sponge.squeeze())
+    }
+
+    fn common_point(&mut self, ec_point: C) -> io::Result<()> {
+        self.sponge.update(
+            &ec_point_coordinates(ec_point)
+                .map(|coordinate| u256_to_fe(U256::from_le_bytes(coordinate.to_repr()))),
+        );
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.sponge.update(&[scalar]);
+        Ok(())
+    }
+}
+
+impl<C, R: Read> TranscriptRead<C, ChallengeScalar<C>> for PoseidonTranscript<C, R>
+where
+    C: CurveAffine,
+    C::Base: PrimeField<Repr = [u8; 0x20]>,
+    C::Scalar: PrimeField<Repr = [u8; 0x20]> + FromUniformBytes<64>,
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let mut repr = C::Repr::default();
+        self.stream.read_exact(repr.as_mut())?;
+        let ec_point = Option::from(C::from_bytes(&repr)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "Invalid elliptic curve point".to_string(),
+            )
+        })?;
+        self.common_point(ec_point)?;
+        Ok(ec_point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let mut data = [0; 0x20];
+        self.stream.read_exact(data.as_mut())?;
+        let scalar = C::Scalar::from_repr_vartime(data)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "Invalid scalar".to_string()))?;
+        Transcript::<C, ChallengeScalar<C>>::common_scalar(self, scalar)?;
+        Ok(scalar)
+    }
+}
+
+impl<C, R: Read> TranscriptReadBuffer<R, C, ChallengeScalar<C>> for PoseidonTranscript<C, R>
+where
+    C: CurveAffine,
+    C::Base: PrimeField<Repr = [u8; 0x20]>,
+    C::Scalar: PrimeField<Repr = [u8; 0x20]> + FromUniformBytes<64>,
+{
+    fn init(reader: R) -> Self {
+        PoseidonTranscript::new(reader)
+    }
+}
+
+impl<C, W: Write> TranscriptWrite<C, ChallengeScalar<C>> for PoseidonTranscript<C, W>
+where
+    C: CurveAffine,
+    C::Base: PrimeField<Repr = [u8; 0x20]>,
+    C::Scalar: PrimeField<Repr = [u8; 0x20]> + FromUniformBytes<64>,
+{
+    fn write_point(&mut self, ec_point: C) -> io::Result<()> {
+        self.common_point(ec_point)?;
+        self.stream.write_all(ec_point.to_bytes().as_ref())
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        Transcript::<C, ChallengeScalar<C>>::common_scalar(self, scalar)?;
+        self.stream.write_all(scalar.to_repr().as_ref())
+    }
+}
+
+impl<C, W: Write> TranscriptWriterBuffer<W, C, ChallengeScalar<C>> for PoseidonTranscript<C, W>
+where
+    C: CurveAffine,
+    C::Base: PrimeField<Repr = [u8; 0x20]>,
+    C::Scalar: PrimeField<Repr = [u8; 0x20]> + FromUniformBytes<64>,
+{
+    fn init(writer: W) -> Self {
+        PoseidonTranscript::new(writer)
+    }
+
+    fn finalize(self) -> W {
+        self.stream
+    }
+}
+
+/// `Spec` of `poseidon` crate used by [`PoseidonTranscript`], rendering the same permutation in
+/// Yul with its round constants and MDS matrix inlined.
+#[derive(Debug)]
+pub(crate) struct PoseidonSpec<F: PrimeField>(Spec<F, T, RATE>);
+
+impl<F> PoseidonSpec<F>
+where
+    F: PrimeField<Repr = [u8; 0x20]> + FromUniformBytes<64>,
+{
+    pub(crate) fn new() -> Self {
+        Self(Spec::new(R_F, R_P))
+    }
+
+    /// Return Yul statements multiplying state in `memory[0x00..0xa0]` by MDS matrix, with state
+    /// loaded as `s0..s4` and scalar field modulus as `r`.
+    pub(crate) fn yul_mds(&self) -> Vec<String> {
+        self.0
+            .mds_matrices()
+            .mds()
+            .rows()
+            .iter()
+            .enumerate()
+            .map(|(idx, row)| {
+                let sum = row
+                    .iter()
+                    .enumerate()
+                    .map(|(column, m)| format!("mulmod({}, s{column}, r)", fe_to_hex(m)))
+                    .reduce(|acc, term| format!("add({acc}, {term})"))
+                    .unwrap();
+                format!("mstore({:#04x}, mod({sum}, r))", idx * 0x20)
+            })
+            .collect()
+    }
+
+    /// Return Yul statements permuting state in `memory[0x00..0xa0]`, with scalar field modulus as
+    /// `r` and functions `poseidon_sbox` and `poseidon_mds` in scope.
+    ///
+    /// Round constants are optimized by `poseidon` crate to be added after S-box, where those of
+    /// partial rounds only remain in the first element. The crate then factors MDS matrix of
+    /// partial rounds into sparse ones, which is equivalent to multiplying by MDS matrix as is
+    /// since constants are only added to the first element.
+    pub(crate) fn yul_permutation(&self) -> Vec<String> {
+        let constants = self.0.constants();
+        let (first, start) = constants.start().split_first().unwrap();
+        let sbox = |idx: usize| format!("poseidon_sbox(mload({:#04x}), r)", idx * 0x20);
+        let round = |constants: &[F]| {
+            chain![
+                constants.iter().enumerate().map(move |(idx, constant)| {
+                    let sbox = sbox(idx);
+                    format!(
+                        "mstore({:#04x}, addmod({sbox}, {}, r))",
+                        idx * 0x20,
+                        fe_to_hex(constant)
+                    )
+                }),
+                ["poseidon_mds(r)".to_string()],
+            ]
+            .collect::<Vec<_>>()
+        };
+        let full_sbox_round = || {
+            chain![
+                (0..T).map(move |idx| format!("mstore({:#04x}, {})", idx * 0x20, sbox(idx))),
+                ["poseidon_mds(r)".to_string()],
+            ]
+        };
+        chain![
+            first.iter().enumerate().map(|(idx, constant)| {
+                let mptr = format!("{:#04x}", idx * 0x20);
+                format!(
+                    "mstore({mptr}, addmod(mload({mptr}), {}, r))",
+                    fe_to_hex(constant)
+                )
+            }),
+            start.iter().flat_map(|constants| round(constants)),
+            full_sbox_round(),
+            constants
+                .partial()
+                .iter()
+                .flat_map(|constant| round(&[*constant])),
+            constants
+                .end()
+                .iter()
+                .flat_map(|constants| round(constants)),
+            full_sbox_round(),
+        ]
+        .collect()
+    }
+}
+
+fn fe_to_hex<F>(fe: &F) -> String
+where
+    F: PrimeField<Repr = [u8; 0x20]>,
+{
+    format!("0x{:064x}", U256::from_le_bytes(fe.to_repr()))
+}
@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: MIT
+
+pragma solidity ^0.8.0;
+
+contract Halo2MultiVerifier {
+    bytes4 internal constant PAIRING_INPUT_SELECTOR = {{ fn_sig_pairing_input|hex_padded(8) }};
+
+    {%- for idx in 0..num_verifiers %}
+    address internal immutable VERIFIER_{{ idx }};
+    {%- endfor %}
+
+    constructor(address[{{ num_verifiers }}] memory verifiers) {
+        {%- for idx in 0..num_verifiers %}
+        VERIFIER_{{ idx }} = verifiers[{{ idx }}];
+        {%- endfor %}
+    }
+
+    function verifyProofs(
+        bytes[] calldata proofs,
+        uint256[][] calldata instances
+    ) external view returns (bool) {
+        require(proofs.length == {{ num_verifiers }} && instances.length == {{ num_verifiers }});
+
+        address[{{ num_verifiers }}] memory verifiers = [
+            {%- for idx in 0..num_verifiers %}
+            VERIFIER_{{ idx }}{% if !loop.last %},{% endif %}
+            {%- endfor %}
+        ];
+
+        // Compute pairing input of each proof by its verifier
+        uint256[{{ 4 * num_verifiers }}] memory inputs;
+        for (uint256 idx = 0; idx < {{ num_verifiers }}; idx++) {
+            (bool success, bytes memory output) = verifiers[idx].staticcall(
+                abi.encodeWithSelector(PAIRING_INPUT_SELECTOR, proofs[idx], instances[idx])
+            );
+            require(success && output.length == 0x80);
+            uint256[4] memory input = abi.decode(output, (uint256[4]));
+            for (uint256 offset = 0; offset < 4; offset++) {
+                inputs[4 * idx + offset] = input[offset];
+            }
+        }
+
+        assembly {
+            // Return updated (success, x, y) of [scalar] * (x, y) + (rhs_x, rhs_y).
+            function ec_mul_add(success, x, y, scalar, rhs_x, rhs_y) -> ret0, ret1, ret2 {
+                mstore(0x00, x)
+                mstore(0x20, y)
+                mstore(0x40, scalar)
+                ret0 := and(success, staticcall(gas(), 0x07, 0x00, 0x60, 0x00, 0x40))
+                mstore(0x40, rhs_x)
+                mstore(0x60, rhs_y)
+                ret0 := and(ret0, staticcall(gas(), 0x06, 0x00, 0x80, 0x00, 0x40))
+                ret1 := mload(0x00)
+                ret2 := mload(0x20)
+            }
+
+            // Modulus
+            let r := 21888242871839275222246405745257275088548364400416034343698204186575808495617 // BN254 scalar field
+
+            // Initialize success as true
+            let success := true
+
+            // Random linear combine pairing inputs
+            let challenge := mod(keccak256(inputs, {{ (128 * num_verifiers)|hex() }}), r)
+            let lhs_x := mload(inputs)
+            let lhs_y := mload(add(inputs, 0x20))
+            let rhs_x := mload(add(inputs, 0x40))
+            let rhs_y := mload(add(inputs, 0x60))
+            for
+                {
+                    let mptr := add(inputs, 0x80)
+                    let mptr_end := add(inputs, {{ (128 * num_verifiers)|hex() }})
+                }
+                lt(mptr, mptr_end)
+                { mptr := add(mptr, 0x80) }
+            {
+                success, lhs_x, lhs_y := ec_mul_add(success, lhs_x, lhs_y, challenge, mload(mptr), mload(add(mptr, 0x20)))
+                success, rhs_x, rhs_y := ec_mul_add(success, rhs_x, rhs_y, challenge, mload(add(mptr, 0x40)), mload(add(mptr, 0x60)))
+            }
+
+            // Perform pairing
+            mstore(0x00, lhs_x)
+            mstore(0x20, lhs_y)
+            mstore(0x40, {{ g2[0]|hex_padded(64) }}) // g2_x_1
+            mstore(0x60, {{ g2[1]|hex_padded(64) }}) // g2_x_2
+            mstore(0x80, {{ g2[2]|hex_padded(64) }}) // g2_y_1
+            mstore(0xa0, {{ g2[3]|hex_padded(64) }}) // g2_y_2
+            mstore(0xc0, rhs_x)
+            mstore(0xe0, rhs_y)
+            mstore(0x100, {{ neg_s_g2[0]|hex_padded(64) }}) // neg_s_g2_x_1
+            mstore(0x120, {{ neg_s_g2[1]|hex_padded(64) }}) // neg_s_g2_x_2
+            mstore(0x140, {{ neg_s_g2[2]|hex_padded(64) }}) // neg_s_g2_y_1
+            mstore(0x160, {{ neg_s_g2[3]|hex_padded(64) }}) // neg_s_g2_y_2
+            success := and(success, staticcall(gas(), 0x08, 0x00, 0x180, 0x00, 0x20))
+            success := and(success, mload(0x00))
+
+            // Revert if anything fails
+            if iszero(success) {
+                revert(0x00, 0x00)
+            }
+
+            // Return 1 as result if everything succeeds
+            mstore(0x00, 1)
+            return(0x00, 0x20)
+        }
+    }
+}
@@ -3,84 +3,337 @@
 pragma solidity ^0.8.0;
 
 contract Halo2Verifier {
-    uint256 internal constant    PROOF_LEN_CPTR = {{ proof_cptr - 1 }};
-    uint256 internal constant        PROOF_CPTR = {{ proof_cptr }};
-    uint256 internal constant NUM_INSTANCE_CPTR = {{ proof_cptr + (proof_len / 32) }};
-    uint256 internal constant     INSTANCE_CPTR = {{ proof_cptr + (proof_len / 32) + 1 }};
-
-    uint256 internal constant FIRST_QUOTIENT_X_CPTR = {{ quotient_comm_cptr }};
-    uint256 internal constant  LAST_QUOTIENT_X_CPTR = {{ quotient_comm_cptr + 2 * (num_quotients - 1) }};
-
-    uint256 internal constant                VK_MPTR = {{ vk_mptr }};
-    uint256 internal constant         VK_DIGEST_MPTR = {{ vk_mptr }};
-    uint256 internal constant                 K_MPTR = {{ vk_mptr + 1 }};
-    uint256 internal constant             N_INV_MPTR = {{ vk_mptr + 2 }};
-    uint256 internal constant             OMEGA_MPTR = {{ vk_mptr + 3 }};
-    uint256 internal constant         OMEGA_INV_MPTR = {{ vk_mptr + 4 }};
-    uint256 internal constant    OMEGA_INV_TO_L_MPTR = {{ vk_mptr + 5 }};
-    uint256 internal constant     NUM_INSTANCES_MPTR = {{ vk_mptr + 6 }};
-    uint256 internal constant   HAS_ACCUMULATOR_MPTR = {{ vk_mptr + 7 }};
-    uint256 internal constant        ACC_OFFSET_MPTR = {{ vk_mptr + 8 }};
-    uint256 internal constant     NUM_ACC_LIMBS_MPTR = {{ vk_mptr + 9 }};
-    uint256 internal constant NUM_ACC_LIMB_BITS_MPTR = {{ vk_mptr + 10 }};
-    uint256 internal constant              G1_X_MPTR = {{ vk_mptr + 11 }};
-    uint256 internal constant              G1_Y_MPTR = {{ vk_mptr + 12 }};
-    uint256 internal constant            G2_X_1_MPTR = {{ vk_mptr + 13 }};
-    uint256 internal constant            G2_X_2_MPTR = {{ vk_mptr + 14 }};
-    uint256 internal constant            G2_Y_1_MPTR = {{ vk_mptr + 15 }};
-    uint256 internal constant            G2_Y_2_MPTR = {{ vk_mptr + 16 }};
-    uint256 internal constant      NEG_S_G2_X_1_MPTR = {{ vk_mptr + 17 }};
-    uint256 internal constant      NEG_S_G2_X_2_MPTR = {{ vk_mptr + 18 }};
-    uint256 internal constant      NEG_S_G2_Y_1_MPTR = {{ vk_mptr + 19 }};
-    uint256 internal constant      NEG_S_G2_Y_2_MPTR = {{ vk_mptr + 20 }};
-
-    uint256 internal constant CHALLENGE_MPTR = {{ challenge_mptr }};
-
-    uint256 internal constant THETA_MPTR = {{ theta_mptr }};
-    uint256 internal constant  BETA_MPTR = {{ theta_mptr + 1 }};
-    uint256 internal constant GAMMA_MPTR = {{ theta_mptr + 2 }};
-    uint256 internal constant     Y_MPTR = {{ theta_mptr + 3 }};
-    uint256 internal constant     X_MPTR = {{ theta_mptr + 4 }};
-    {%- match scheme %}
-    {%- when Bdfg21 %}
-    uint256 internal constant  ZETA_MPTR = {{ theta_mptr + 5 }};
-    uint256 internal constant    NU_MPTR = {{ theta_mptr + 6 }};
-    uint256 internal constant    MU_MPTR = {{ theta_mptr + 7 }};
-    {%- when Gwc19 %}
-    // TODO
-    {%- endmatch %}
-
-    uint256 internal constant       ACC_LHS_X_MPTR = {{ theta_mptr + 8 }};
-    uint256 internal constant       ACC_LHS_Y_MPTR = {{ theta_mptr + 9 }};
-    uint256 internal constant       ACC_RHS_X_MPTR = {{ theta_mptr + 10 }};
-    uint256 internal constant       ACC_RHS_Y_MPTR = {{ theta_mptr + 11 }};
-    uint256 internal constant             X_N_MPTR = {{ theta_mptr + 12 }};
-    uint256 internal constant X_N_MINUS_1_INV_MPTR = {{ theta_mptr + 13 }};
-    uint256 internal constant          L_LAST_MPTR = {{ theta_mptr + 14 }};
-    uint256 internal constant         L_BLIND_MPTR = {{ theta_mptr + 15 }};
-    uint256 internal constant             L_0_MPTR = {{ theta_mptr + 16 }};
-    uint256 internal constant   INSTANCE_EVAL_MPTR = {{ theta_mptr + 17 }};
-    uint256 internal constant   QUOTIENT_EVAL_MPTR = {{ theta_mptr + 18 }};
-    uint256 internal constant      QUOTIENT_X_MPTR = {{ theta_mptr + 19 }};
-    uint256 internal constant      QUOTIENT_Y_MPTR = {{ theta_mptr + 20 }};
-    uint256 internal constant          R_EVAL_MPTR = {{ theta_mptr + 21 }};
-    uint256 internal constant   PAIRING_LHS_X_MPTR = {{ theta_mptr + 22 }};
-    uint256 internal constant   PAIRING_LHS_Y_MPTR = {{ theta_mptr + 23 }};
-    uint256 internal constant   PAIRING_RHS_X_MPTR = {{ theta_mptr + 24 }};
-    uint256 internal constant   PAIRING_RHS_Y_MPTR = {{ theta_mptr + 25 }};
-
-    function verifyProof(
+{% include "Halo2VerifierConstants.sol" %}
+    {%- if custom_errors %}
+{# #}
+    {%- for error in errors %}
+    {{ error.declaration() }}
+    {%- endfor %}
+{# #}
+    {%- for error in errors %}
+    uint256 internal constant {{ "{:>29}"|format(error.sig_name()) }} = {{ error.sig()|hex_padded(64) }};
+    {%- endfor %}
+    {%- endif %}
+    {%- if public_input_hash.is_some() %}
+
+    uint256 internal constant PUBLIC_DATA_OFFSET_CPTR = {{ proof_cptr - 2 }};
+    {%- endif %}
+    {%- if self.num_helpers() > 0 %}
+
+    uint256 internal constant HANDOFF_CPTR = {{ proof_cptr + (proof_len / 32) + 1 + num_instances }};
+    uint256 internal constant HANDOFF_MPTR = {{ theta_mptr + num_instance_evals + 25 }};
+{# #}
+    {%- for idx in 0..num_quotient_helpers %}
+    address internal immutable QUOTIENT_HELPER_{{ idx }};
+    {%- endfor %}
+    {%- if pcs_helper %}
+    address internal immutable PCS_HELPER;
+    {%- endif %}
+
+    constructor(address[{{ self.num_helpers() }}] memory helpers) {
+        {%- for idx in 0..num_quotient_helpers %}
+        QUOTIENT_HELPER_{{ idx }} = helpers[{{ idx }}];
+        {%- endfor %}
+        {%- if pcs_helper %}
+        PCS_HELPER = helpers[{{ num_quotient_helpers }}];
+        {%- endif %}
+    }
+    {%- endif %}
+
+    {%- let functions = self.functions() %}
+
+    {{ functions[0].header() }} {
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        verify(false);
+        {%- when None %}
+        verify(vk, false);
+        {%- endmatch %}
+    }
+
+    {{ functions[1].header() }} {
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        verify(true);
+        {%- when None %}
+        verify(vk, true);
+        {%- endmatch %}
+    }
+
+    {{ functions[2].header() }} {
+        bytes4 selector = this.pairingInput.selector;
+        assembly {
+            {%- if public_input_hash.is_some() %}
+            // Call pairingInput with proof, instances and public data at (proof_cptr, instances_cptr, public_data_cptr),
+            {%- else %}
+            // Call pairingInput with proof and instances at (proof_cptr, instances_cptr),
+            {%- endif %}
+            // by encoding calldata in memory[calldata_mptr..] where function selector is already stored,
+            // and store returned pairing input in memory[input_mptr..input_mptr + 0x80].
+            // Return updated (success).
+            {%- if public_input_hash.is_some() %}
+            function pairing_input(success, calldata_mptr, proof_cptr, instances_cptr, public_data_cptr, input_mptr) -> ret {
+            {%- else %}
+            function pairing_input(success, calldata_mptr, proof_cptr, instances_cptr, input_mptr) -> ret {
+            {%- endif %}
+                let proof_size := add(0x20, calldataload(proof_cptr))
+                let instances_size := add(0x20, mul(0x20, calldataload(instances_cptr)))
+                {%- if public_input_hash.is_some() %}
+                let public_data_size := add(0x20, calldataload(public_data_cptr))
+                {%- endif %}
+                {%- if non_reverting %}
+                {%- if public_input_hash.is_some() %}
+                // Skip proof, instances or public data larger than calldata to avoid huge memory expansion
+                if or(or(gt(proof_size, calldatasize()), gt(instances_size, calldatasize())), gt(public_data_size, calldatasize())) {
+                    leave
+                }
+                {%- else %}
+                // Skip proof or instances larger than calldata to avoid huge memory expansion
+                if or(gt(proof_size, calldatasize()), gt(instances_size, calldatasize())) {
+                    leave
+                }
+                {%- endif %}
+                {%- endif %}
+                {%- match vk %}
+                {%- when Some with (vk) %}
+                let head_size := {% if public_input_hash.is_some() %}0x60{% else %}0x40{% endif %}
+                {%- when None %}
+                let head_size := {% if public_input_hash.is_some() %}0x80{% else %}0x60{% endif %}
+                {%- endmatch %}
+                let args_mptr := add(calldata_mptr, 0x04)
+                {%- if public_input_hash.is_some() %}
+                mstore(add(args_mptr, sub(head_size, 0x60)), head_size)
+                mstore(add(args_mptr, sub(head_size, 0x40)), add(head_size, proof_size))
+                mstore(add(args_mptr, sub(head_size, 0x20)), add(head_size, add(proof_size, instances_size)))
+                {%- else %}
+                mstore(add(args_mptr, sub(head_size, 0x40)), head_size)
+                mstore(add(args_mptr, sub(head_size, 0x20)), add(head_size, proof_size))
+                {%- endif %}
+                calldatacopy(add(args_mptr, head_size), proof_cptr, proof_size)
+                calldatacopy(add(args_mptr, add(head_size, proof_size)), instances_cptr, instances_size)
+                {%- if public_input_hash.is_some() %}
+                calldatacopy(add(args_mptr, add(head_size, add(proof_size, instances_size))), public_data_cptr, public_data_size)
+                let calldata_size := add(0x04, add(head_size, add(proof_size, add(instances_size, public_data_size))))
+                {%- else %}
+                let calldata_size := add(0x04, add(head_size, add(proof_size, instances_size)))
+                {%- endif %}
+                ret := and(success, staticcall(gas(), address(), calldata_mptr, calldata_size, input_mptr, 0x80))
+                {%- if custom_errors %}
+                if iszero(ret) {
+                    returndatacopy(0x00, 0x00, returndatasize())
+                    revert(0x00, returndatasize())
+                }
+                {%- endif %}
+            }
+
+            // Return updated (success, x, y) of [scalar] * (x, y) + (rhs_x, rhs_y).
+            function ec_mul_add(success, x, y, scalar, rhs_x, rhs_y) -> ret0, ret1, ret2 {
+                mstore(0x00, x)
+                mstore(0x20, y)
+                mstore(0x40, scalar)
+                ret0 := and(success, staticcall(gas(), 0x07, 0x00, 0x60, 0x00, 0x40))
+                {%- if custom_errors %}
+                if iszero(ret0) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x07) }
+                {%- endif %}
+                mstore(0x40, rhs_x)
+                mstore(0x60, rhs_y)
+                ret0 := and(ret0, staticcall(gas(), 0x06, 0x00, 0x80, 0x00, 0x40))
+                {%- if custom_errors %}
+                if iszero(ret0) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x06) }
+                {%- endif %}
+                ret1 := mload(0x00)
+                ret2 := mload(0x20)
+            }
+            {%- if custom_errors %}
+
+            // Revert with custom error of given signature.
+            function revert_with(sig) {
+                mstore(0x00, sig)
+                revert(0x00, 0x04)
+            }
+
+            // Revert with custom error of given signature and argument.
+            function revert_with_arg(sig, arg) {
+                mstore(0x00, sig)
+                mstore(0x04, arg)
+                revert(0x00, 0x24)
+            }
+            {%- endif %}
+
+            {%- if public_input_hash.is_some() %}
+            // Check valid length of proofs, instances and public data
+            let success := and(gt(proofs.length, 0), eq(proofs.length, instances.length))
+            success := and(success, eq(proofs.length, publicData.length))
+            {%- else %}
+            // Check valid length of proofs and instances
+            let success := and(gt(proofs.length, 0), eq(proofs.length, instances.length))
+            {%- endif %}
+            {%- if custom_errors %}
+            if iszero(success) { revert_with(INVALID_INSTANCES_LENGTH_SIG) }
+            {%- endif %}
+
+            // Compute pairing input of each proof, and store them in memory[0x180..calldata_mptr]
+            let calldata_mptr := add(0x180, mul(proofs.length, 0x80))
+            mstore(calldata_mptr, selector)
+            {%- match vk %}
+            {%- when Some with (vk) %}
+            {%- when None %}
+            mstore(add(calldata_mptr, 0x04), vk)
+            {%- endmatch %}
+            for
+                { let idx := 0 }
+                lt(idx, proofs.length)
+                { idx := add(idx, 1) }
+            {
+                success := pairing_input(
+                    success,
+                    calldata_mptr,
+                    add(proofs.offset, calldataload(add(proofs.offset, mul(idx, 0x20)))),
+                    add(instances.offset, calldataload(add(instances.offset, mul(idx, 0x20)))),
+                    {%- if public_input_hash.is_some() %}
+                    add(publicData.offset, calldataload(add(publicData.offset, mul(idx, 0x20)))),
+                    {%- endif %}
+                    add(0x180, mul(idx, 0x80))
+                )
+            }
+
+            // Random linear combine pairing inputs
+            let r := 21888242871839275222246405745257275088548364400416034343698204186575808495617 // BN254 scalar field
+            let challenge := mod(keccak256(0x180, sub(calldata_mptr, 0x180)), r)
+            let lhs_x := mload(0x180)
+            let lhs_y := mload(0x1a0)
+            let rhs_x := mload(0x1c0)
+            let rhs_y := mload(0x1e0)
+            for
+                { let mptr := 0x200 }
+                lt(mptr, calldata_mptr)
+                { mptr := add(mptr, 0x80) }
+            {
+                success, lhs_x, lhs_y := ec_mul_add(success, lhs_x, lhs_y, challenge, mload(mptr), mload(add(mptr, 0x20)))
+                success, rhs_x, rhs_y := ec_mul_add(success, rhs_x, rhs_y, challenge, mload(add(mptr, 0x40)), mload(add(mptr, 0x60)))
+            }
+
+            // Perform pairing
+            mstore(0x00, lhs_x)
+            mstore(0x20, lhs_y)
+            mstore(0xc0, rhs_x)
+            mstore(0xe0, rhs_y)
+            {%- match vk %}
+            {%- when Some with (vk) %}
+            mstore(0x40, {{ vk.constant("g2_x_1")|hex_padded(64) }}) // g2_x_1
+            mstore(0x60, {{ vk.constant("g2_x_2")|hex_padded(64) }}) // g2_x_2
+            mstore(0x80, {{ vk.constant("g2_y_1")|hex_padded(64) }}) // g2_y_1
+            mstore(0xa0, {{ vk.constant("g2_y_2")|hex_padded(64) }}) // g2_y_2
+            mstore(0x100, {{ vk.constant("neg_s_g2_x_1")|hex_padded(64) }}) // neg_s_g2_x_1
+            mstore(0x120, {{ vk.constant("neg_s_g2_x_2")|hex_padded(64) }}) // neg_s_g2_x_2
+            mstore(0x140, {{ vk.constant("neg_s_g2_y_1")|hex_padded(64) }}) // neg_s_g2_y_1
+            mstore(0x160, {{ vk.constant("neg_s_g2_y_2")|hex_padded(64) }}) // neg_s_g2_y_2
+            {%- when None %}
+            extcodecopy(vk, 0x40, sub(G2_X_1_MPTR, VK_MPTR), 0x80)
+            extcodecopy(vk, 0x100, sub(NEG_S_G2_X_1_MPTR, VK_MPTR), 0x80)
+            {%- endmatch %}
+            success := and(success, staticcall(gas(), 0x08, 0x00, 0x180, 0x00, 0x20))
+            {%- if custom_errors %}
+            if iszero(success) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x08) }
+            {%- endif %}
+            success := and(success, mload(0x00))
+            {%- if custom_errors %}
+            if iszero(success) { revert_with(PAIRING_FAILED_SIG) }
+            {%- endif %}
+
+            {%- if non_reverting %}
+
+            // Return 0 as result if anything fails
+            if iszero(success) {
+                mstore(0x00, 0)
+                return(0x00, 0x20)
+            }
+            {%- else %}
+
+            // Revert if anything fails
+            if iszero(success) {
+                revert(0x00, 0x00)
+            }
+            {%- endif %}
+
+            // Return 1 as result if everything succeeds
+            mstore(0x00, 1)
+            return(0x00, 0x20)
+        }
+    }
+
+    // Verify proof in calldata, and return 1 if `pairing_input_only` is false, otherwise return
+    // pairing input (lhs, rhs) without performing pairing.
+    function verify(
         {%- match vk %}
         {%- when Some with (vk) %}
         {%- when None %}
         address vk,
         {%- endmatch %}
-        bytes calldata proof,
-        uint256[] calldata instances
-    ) public returns (bool) {
+        bool pairing_input_only
+    ) internal view {
+        {%- for idx in 0..num_quotient_helpers %}
+        address quotient_helper_{{ idx }} = QUOTIENT_HELPER_{{ idx }};
+        {%- endfor %}
+        {%- if pcs_helper %}
+        address pcs_helper = PCS_HELPER;
+        {%- endif %}
         assembly {
+            {%- if self.little_endian() %}
+            // Read compressed EC point at proof_cptr in little-endian, which is x with parity of y in the most
+            // significant bit, and decompress it by computing y = (x^3 + 3)^((q + 1) / 4) with modexp,
+            // and check if the point is on affine plane or is identity encoded as zero,
+            // and store (x, y) in (ec_point_mptr, ec_point_mptr + 0x20) and absorb it.
+            // Return updated (success, proof_cptr, hash_mptr).
+            function read_ec_point(success, proof_cptr, hash_mptr, ec_point_mptr, q) -> ret0, ret1, ret2 {
+                let x := reverse_bytes(calldataload(proof_cptr))
+            {%- else if compressed_points %}
+            // Read compressed EC point at proof_cptr, which is x with parity of y in the most significant bit,
+            // and decompress it by computing y = (x^3 + 3)^((q + 1) / 4) with modexp,
+            // and check if the point is on affine plane or is identity encoded as zero,
+            // and store (x, y) in (hash_mptr, hash_mptr + 0x20) and (ec_point_mptr, ec_point_mptr + 0x20).
+            // Return updated (success, proof_cptr, hash_mptr).
+            function read_ec_point(success, proof_cptr, hash_mptr, ec_point_mptr, q) -> ret0, ret1, ret2 {
+                let x := calldataload(proof_cptr)
+            {%- endif %}
+            {%- if compressed_points %}
+                let is_identity := iszero(x)
+                let y_parity := shr(255, x)
+                x := and(x, 0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff)
+                let y_square := addmod(mulmod(x, mulmod(x, x, q), q), 3, q)
+                let mptr := add(PAIRING_RHS_Y_MPTR, 0x20)
+                mstore(mptr, 0x20)
+                mstore(add(mptr, 0x20), 0x20)
+                mstore(add(mptr, 0x40), 0x20)
+                mstore(add(mptr, 0x60), y_square)
+                mstore(add(mptr, 0x80), 0x0c19139cb84c680a6e14116da060561765e05aa45a1c72a34f082305b61f3f52)
+                mstore(add(mptr, 0xa0), q)
+                ret0 := and(success, staticcall(gas(), 0x05, mptr, 0xc0, mptr, 0x20))
+                let y := mload(mptr)
+                ret0 := and(ret0, lt(x, q))
+                ret0 := and(ret0, or(is_identity, eq(mulmod(y, y, q), y_square)))
+                {%- if custom_errors %}
+                if iszero(ret0) { revert_with_arg(INVALID_EC_POINT_SIG, proof_cptr) }
+                {%- endif %}
+                if xor(y_parity, and(y, 1)) { y := sub(q, y) }
+                if is_identity { y := 0 }
+                {%- if self.little_endian() %}
+                mstore(ec_point_mptr, x)
+                mstore(add(ec_point_mptr, 0x20), y)
+                ret0, ret2 := absorb_ec_point(ret0, hash_mptr, x, y)
+                ret1 := add(proof_cptr, 0x20)
+                {%- else %}
+                mstore(hash_mptr, x)
+                mstore(add(hash_mptr, 0x20), y)
+                mstore(ec_point_mptr, x)
+                mstore(add(ec_point_mptr, 0x20), y)
+                ret1 := add(proof_cptr, 0x20)
+                ret2 := add(hash_mptr, 0x40)
+                {%- endif %}
+            }
+            {%- else %}
             // Read EC point (x, y) at (proof_cptr, proof_cptr + 0x20),
-            // and check if the point is on affine plane,
+            // and check if the point is on affine plane or is identity encoded as (0, 0),
             // and store them in (hash_mptr, hash_mptr + 0x20).
             // Return updated (success, proof_cptr, hash_mptr).
             function read_ec_point(success, proof_cptr, hash_mptr, q) -> ret0, ret1, ret2 {
@@ -88,37 +341,202 @@ contract Halo2Verifier {
                 let y := calldataload(add(proof_cptr, 0x20))
                 ret0 := and(success, lt(x, q))
                 ret0 := and(ret0, lt(y, q))
-                ret0 := and(ret0, eq(mulmod(y, y, q), addmod(mulmod(x, mulmod(x, x, q), q), 3, q)))
+                ret0 := and(ret0, or(iszero(or(x, y)), eq(mulmod(y, y, q), addmod(mulmod(x, mulmod(x, x, q), q), 3, q))))
+                {%- if custom_errors %}
+                if iszero(ret0) { revert_with_arg(INVALID_EC_POINT_SIG, proof_cptr) }
+                {%- endif %}
                 mstore(hash_mptr, x)
                 mstore(add(hash_mptr, 0x20), y)
                 ret1 := add(proof_cptr, 0x40)
                 ret2 := add(hash_mptr, 0x40)
             }
+            {%- endif %}
+            {%- if self.little_endian() %}
+
+            // Reverse byte order of value, to convert between little-endian and big-endian.
+            function reverse_bytes(value) -> ret {
+                ret := or(shr(8, and(value, 0xff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00)), shl(8, and(value, 0x00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff)))
+                ret := or(shr(16, and(ret, 0xffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000)), shl(16, and(ret, 0x0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff)))
+                ret := or(shr(32, and(ret, 0xffffffff00000000ffffffff00000000ffffffff00000000ffffffff00000000)), shl(32, and(ret, 0x00000000ffffffff00000000ffffffff00000000ffffffff00000000ffffffff)))
+                ret := or(shr(64, and(ret, 0xffffffffffffffff0000000000000000ffffffffffffffff0000000000000000)), shl(64, and(ret, 0x0000000000000000ffffffffffffffff0000000000000000ffffffffffffffff)))
+                ret := or(shr(128, ret), shl(128, ret))
+            }
+            {%- endif %}
+            {%- if self.blake2b() %}
+
+            // Blake2b is kept in memory[0x00..0x240] during transcript, where memory[0x1c..0xf1] is input
+            // of blake2f precompile (rounds, state, block, counter and final flag), memory[0x100..0x140] is
+            // output of the last squeeze, memory[0x140] is number of bytes compressed into state, and
+            // memory[0x160..hash_mptr] is pending input not compressed yet.
+
+            // Compress the first block of pending input in memory[0x160..0x1e0] with counter t and final flag f
+            // by blake2f precompile, and store the output state in memory[output_mptr..output_mptr + 0x40].
+            // Return updated (success).
+            function blake2b_compress(success, t, f, output_mptr) -> ret {
+                mstore(0x00, 12)
+                mstore(0x60, mload(0x160))
+                mstore(0x80, mload(0x180))
+                mstore(0xa0, mload(0x1a0))
+                mstore(0xc0, mload(0x1c0))
+                mstore(0xe0, reverse_bytes(t))
+                mstore8(0xf0, f)
+                ret := and(success, staticcall(gas(), 0x09, 0x1c, 0xd5, output_mptr, 0x40))
+                {%- if custom_errors %}
+                if iszero(ret) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x09) }
+                {%- endif %}
+            }
+
+            // Compress the first block of pending input into state if there is more than a block, since
+            // the last block is only compressed with final flag when squeezing, and move the rest to front.
+            // Return updated (success, hash_mptr).
+            function blake2b_update(success, hash_mptr) -> ret0, ret1 {
+                ret0 := success
+                ret1 := hash_mptr
+                if gt(hash_mptr, 0x1e0) {
+                    let t := add(mload(0x140), 0x80)
+                    mstore(0x140, t)
+                    ret0 := blake2b_compress(success, t, 0, 0x20)
+                    mstore(0x160, mload(0x1e0))
+                    mstore(0x180, mload(0x200))
+                    mstore(0x1a0, mload(0x220))
+                    ret1 := sub(hash_mptr, 0x80)
+                }
+            }
+
+            // Absorb scalar with prefix 0x02 in little-endian into pending input at hash_mptr.
+            // Return updated (success, hash_mptr).
+            function absorb_scalar(success, hash_mptr, scalar) -> ret0, ret1 {
+                mstore8(hash_mptr, 0x02)
+                mstore(add(hash_mptr, 0x01), reverse_bytes(scalar))
+                ret0, ret1 := blake2b_update(success, add(hash_mptr, 0x21))
+            }
+
+            // Absorb EC point with prefix 0x01 and coordinates in little-endian into pending input at hash_mptr.
+            // Return updated (success, hash_mptr).
+            function absorb_ec_point(success, hash_mptr, x, y) -> ret0, ret1 {
+                mstore8(hash_mptr, 0x01)
+                mstore(add(hash_mptr, 0x01), reverse_bytes(x))
+                mstore(add(hash_mptr, 0x21), reverse_bytes(y))
+                ret0, ret1 := blake2b_update(success, add(hash_mptr, 0x41))
+            }
+
+            // Squeeze challenge by absorbing prefix 0x00 and compressing pending input padded with zeros as
+            // the last block without updating state, and store 64 bytes output interpreted as little-endian
+            // mod r as challenge in challenge_mptr.
+            // Return updated (success, challenge_mptr, hash_mptr).
+            function squeeze_challenge(success, challenge_mptr, hash_mptr, r) -> ret0, ret1, ret2 {
+                mstore8(hash_mptr, 0x00)
+                ret0, ret2 := blake2b_update(success, add(hash_mptr, 0x01))
+                calldatacopy(ret2, calldatasize(), sub(0x1e0, ret2))
+                ret0 := blake2b_compress(ret0, add(mload(0x140), sub(ret2, 0x160)), 1, 0x100)
+                let lo := reverse_bytes(mload(0x100))
+                let hi := reverse_bytes(mload(0x120))
+                mstore(challenge_mptr, addmod(mulmod(hi, 0x0e0a77c19a07df2f666ea36f7879462e36fc76959f60cd29ac96341c4ffffffb, r), lo, r)) // 2^256 mod r
+                ret1 := add(challenge_mptr, 0x20)
+            }
+            {%- else if self.poseidon() %}
+
+            // Poseidon state is kept in memory[0x00..0xa0] during transcript, where hash_mptr points to the
+            // element of rate to absorb the next input into, and 0xa0 means the rate is full.
+
+            // Apply S-box x^5 of Poseidon.
+            function poseidon_sbox(x, r) -> ret {
+                ret := mulmod(x, x, r)
+                ret := mulmod(mulmod(ret, ret, r), x, r)
+            }
+
+            // Multiply state in memory[0x00..0xa0] by MDS matrix of Poseidon,
+            // where sum of 5 products doesn't overflow since 5 * r < 2^256.
+            function poseidon_mds(r) {
+                let s0 := mload(0x00)
+                let s1 := mload(0x20)
+                let s2 := mload(0x40)
+                let s3 := mload(0x60)
+                let s4 := mload(0x80)
+                {%- for line in self.poseidon_mds() %}
+                {{ line }}
+                {%- endfor %}
+            }
+
+            // Permute state in memory[0x00..0xa0] by Poseidon with round constants optimized by `poseidon`
+            // crate, where those of partial rounds are moved into the first element.
+            function poseidon_permutation(r) {
+                {%- for line in self.poseidon_permutation() %}
+                {{ line }}
+                {%- endfor %}
+            }
+
+            // Absorb scalar into state at hash_mptr, and permute state first if the rate is full.
+            // Return updated (success, hash_mptr).
+            function absorb_scalar(success, hash_mptr, scalar) -> ret0, ret1 {
+                let r := 21888242871839275222246405745257275088548364400416034343698204186575808495617
+                ret0 := success
+                ret1 := hash_mptr
+                if eq(ret1, 0xa0) {
+                    poseidon_permutation(r)
+                    ret1 := 0x20
+                }
+                mstore(ret1, addmod(mload(ret1), scalar, r))
+                ret1 := add(ret1, 0x20)
+            }
+
+            // Absorb EC point as its coordinates reduced into scalar field.
+            // Return updated (success, hash_mptr).
+            function absorb_ec_point(success, hash_mptr, x, y) -> ret0, ret1 {
+                let r := 21888242871839275222246405745257275088548364400416034343698204186575808495617
+                ret0, ret1 := absorb_scalar(success, hash_mptr, mod(x, r))
+                ret0, ret1 := absorb_scalar(ret0, ret1, mod(y, r))
+            }
+
+            // Squeeze challenge by absorbing padding 1 after inputs and permuting state,
+            // and store the first element of rate as challenge in challenge_mptr.
+            // Return updated (success, challenge_mptr, hash_mptr).
+            function squeeze_challenge(success, challenge_mptr, hash_mptr, r) -> ret0, ret1, ret2 {
+                ret0 := success
+                ret2 := hash_mptr
+                if eq(ret2, 0xa0) {
+                    poseidon_permutation(r)
+                    ret2 := 0x20
+                }
+                mstore(ret2, addmod(mload(ret2), 1, r))
+                poseidon_permutation(r)
+                mstore(challenge_mptr, mload(0x20))
+                ret1 := add(challenge_mptr, 0x20)
+                ret2 := 0x20
+            }
+            {%- else %}
 
-            // Squeeze challenge by keccak256(memory[0..hash_mptr]),
+            // Squeeze challenge by hash of memory[0..hash_mptr],
             // and store hash mod r as challenge in challenge_mptr,
             // and push back hash in 0x00 as the first input for next squeeze.
-            // Return updated (challenge_mptr, hash_mptr).
-            function squeeze_challenge(challenge_mptr, hash_mptr, r) -> ret0, ret1 {
-                let hash := keccak256(0x00, hash_mptr)
+            // Return updated (success, challenge_mptr, hash_mptr).
+            function squeeze_challenge(success, challenge_mptr, hash_mptr, r) -> ret0, ret1, ret2 {
+                ret0 := success
+                {%- for line in self.transcript_hash_yul("ret0", "hash_mptr") %}
+                {{ line }}
+                {%- endfor %}
                 mstore(challenge_mptr, mod(hash, r))
                 mstore(0x00, hash)
-                ret0 := add(challenge_mptr, 0x20)
-                ret1 := 0x20
+                ret1 := add(challenge_mptr, 0x20)
+                ret2 := 0x20
             }
 
             // Squeeze challenge without absorbing new input from calldata,
-            // by putting an extra 0x01 in memory[0x20] and squeeze by keccak256(memory[0..21]),
+            // by putting an extra 0x01 in memory[0x20] and squeeze by hash of memory[0..21],
             // and store hash mod r as challenge in challenge_mptr,
             // and push back hash in 0x00 as the first input for next squeeze.
-            // Return updated (challenge_mptr).
-            function squeeze_challenge_cont(challenge_mptr, r) -> ret {
+            // Return updated (success, challenge_mptr).
+            function squeeze_challenge_cont(success, challenge_mptr, r) -> ret0, ret1 {
                 mstore8(0x20, 0x01)
-                let hash := keccak256(0x00, 0x21)
+                ret0 := success
+                {%- for line in self.transcript_hash_yul("ret0", "0x21") %}
+                {{ line }}
+                {%- endfor %}
                 mstore(challenge_mptr, mod(hash, r))
                 mstore(0x00, hash)
-                ret := add(challenge_mptr, 0x20)
+                ret1 := add(challenge_mptr, 0x20)
             }
+            {%- endif %}
 
             // Batch invert values in memory[mptr_start..mptr_end] in place.
             // Return updated (success).
@@ -145,6 +563,9 @@ contract Halo2Verifier {
                 mstore(add(gp_mptr, 0x80), sub(r, 2))
                 mstore(add(gp_mptr, 0xa0), r)
                 ret := and(success, staticcall(gas(), 0x05, gp_mptr, 0xc0, gp_mptr, 0x20))
+                {%- if custom_errors %}
+                if iszero(ret) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x05) }
+                {%- endif %}
                 let all_inv := mload(gp_mptr)
 
                 let first_mptr := mptr_start
@@ -173,12 +594,18 @@ contract Halo2Verifier {
                 mstore(0x40, x)
                 mstore(0x60, y)
                 ret := and(success, staticcall(gas(), 0x06, 0x00, 0x80, 0x00, 0x40))
+                {%- if custom_errors %}
+                if iszero(ret) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x06) }
+                {%- endif %}
             }
 
             // Scale point at (0x00, 0x20) by scalar.
             function ec_mul_acc(success, scalar) -> ret {
                 mstore(0x40, scalar)
                 ret := and(success, staticcall(gas(), 0x07, 0x00, 0x60, 0x00, 0x40))
+                {%- if custom_errors %}
+                if iszero(ret) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x07) }
+                {%- endif %}
             }
 
             // Add (x, y) into point at (0x80, 0xa0).
@@ -187,6 +614,9 @@ contract Halo2Verifier {
                 mstore(0xc0, x)
                 mstore(0xe0, y)
                 ret := and(success, staticcall(gas(), 0x06, 0x80, 0x80, 0x80, 0x40))
+                {%- if custom_errors %}
+                if iszero(ret) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x06) }
+                {%- endif %}
             }
 
             // Scale point at (0x80, 0xa0) by scalar.
@@ -194,6 +624,9 @@ contract Halo2Verifier {
             function ec_mul_tmp(success, scalar) -> ret {
                 mstore(0xc0, scalar)
                 ret := and(success, staticcall(gas(), 0x07, 0x80, 0x60, 0x80, 0x40))
+                {%- if custom_errors %}
+                if iszero(ret) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x07) }
+                {%- endif %}
             }
 
             // Perform pairing check.
@@ -212,8 +645,66 @@ contract Halo2Verifier {
                 mstore(0x140, mload(NEG_S_G2_Y_1_MPTR))
                 mstore(0x160, mload(NEG_S_G2_Y_2_MPTR))
                 ret := and(success, staticcall(gas(), 0x08, 0x00, 0x180, 0x00, 0x20))
+                {%- if custom_errors %}
+                if iszero(ret) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x08) }
+                {%- endif %}
                 ret := and(ret, mload(0x00))
+                {%- if custom_errors %}
+                if iszero(ret) { revert_with(PAIRING_FAILED_SIG) }
+                {%- endif %}
             }
+            {%- if custom_errors %}
+
+            // Revert with custom error of given signature.
+            function revert_with(sig) {
+                mstore(0x00, sig)
+                revert(0x00, 0x04)
+            }
+
+            // Revert with custom error of given signature and argument.
+            function revert_with_arg(sig, arg) {
+                mstore(0x00, sig)
+                mstore(0x04, arg)
+                revert(0x00, 0x24)
+            }
+            {%- endif %}
+            {%- if self.num_helpers() > 0 %}
+
+            // Call helper with calldata of verifier followed by memory[VK_MPTR..PAIRING_LHS_X_MPTR],
+            // and store its return data of output_len bytes in memory[output_mptr..].
+            // Return updated (success).
+            function call_helper(success, helper, output_mptr, output_len) -> ret {
+                calldatacopy(HANDOFF_MPTR, 0x00, HANDOFF_CPTR)
+                let mptr := add(HANDOFF_MPTR, HANDOFF_CPTR)
+                for
+                    { let src_mptr := VK_MPTR }
+                    lt(src_mptr, PAIRING_LHS_X_MPTR)
+                    { src_mptr := add(src_mptr, 0x20) }
+                {
+                    mstore(mptr, mload(src_mptr))
+                    mptr := add(mptr, 0x20)
+                }
+                ret := and(success, staticcall(gas(), helper, HANDOFF_MPTR, sub(mptr, HANDOFF_MPTR), output_mptr, output_len))
+                {%- if custom_errors %}
+                if iszero(ret) {
+                    returndatacopy(0x00, 0x00, returndatasize())
+                    revert(0x00, returndatasize())
+                }
+                {%- endif %}
+                ret := and(ret, eq(returndatasize(), output_len))
+            }
+            {%- endif %}
+            {%- if non_reverting %}
+
+            // Return 0 as result, or revert if only pairing input is requested.
+            function return_false(pairing_input_only) {
+                if pairing_input_only {
+                    revert(0x00, 0x00)
+                }
+                mstore(0x00, 0)
+                return(0x00, 0x20)
+            }
+            {%- endif %}
 
             // Modulus
             let q := 21888242871839275222246405745257275088696311157297823662689037894645226208583 // BN254 base field
@@ -246,16 +737,73 @@ contract Halo2Verifier {
 
                 // Check valid length of proof
                 success := and(success, eq({{ proof_len|hex() }}, calldataload(PROOF_LEN_CPTR)))
+                {%- if custom_errors %}
+                if iszero(success) { revert_with(INVALID_PROOF_LENGTH_SIG) }
+                {%- endif %}
 
                 // Check valid length of instances
                 let num_instances := mload(NUM_INSTANCES_MPTR)
                 success := and(success, eq(num_instances, calldataload(NUM_INSTANCE_CPTR)))
+                {%- if custom_errors %}
+                if iszero(success) { revert_with(INVALID_INSTANCES_LENGTH_SIG) }
+                {%- endif %}
+                {%- if non_reverting %}
+
+                {%- if public_input_hash.is_some() %}
+                // Check offsets of proof, instances and public data and size of calldata, and return
+                // earlier if anything is invalid to avoid reading calldata out of bounds
+                success := and(success, eq(calldataload(sub(PROOF_LEN_CPTR, 0x60)), sub(PROOF_LEN_CPTR, 0x04)))
+                success := and(success, eq(calldataload(sub(PROOF_LEN_CPTR, 0x40)), sub(NUM_INSTANCE_CPTR, 0x04)))
+                success := and(success, iszero(lt(calldatasize(), add(INSTANCE_CPTR, mul(0x20, num_instances)))))
+                {
+                    let public_data_cptr := add(INSTANCE_CPTR, mul(0x20, num_instances))
+                    let public_data_len := calldataload(public_data_cptr)
+                    success := and(success, eq(calldataload(PUBLIC_DATA_OFFSET_CPTR), sub(public_data_cptr, 0x04)))
+                    success := and(success, lt(public_data_len, calldatasize()))
+                    success := and(success, iszero(lt(calldatasize(), add(add(public_data_cptr, 0x20), public_data_len))))
+                }
+                {%- else %}
+                // Check offsets of proof and instances and size of calldata, and return earlier if
+                // anything is invalid to avoid reading calldata out of bounds
+                success := and(success, eq(calldataload(sub(PROOF_LEN_CPTR, 0x40)), sub(PROOF_LEN_CPTR, 0x04)))
+                success := and(success, eq(calldataload(sub(PROOF_LEN_CPTR, 0x20)), sub(NUM_INSTANCE_CPTR, 0x04)))
+                success := and(success, iszero(lt(calldatasize(), add(INSTANCE_CPTR, mul(0x20, num_instances)))))
+                {%- endif %}
+                if iszero(success) {
+                    return_false(pairing_input_only)
+                }
+                {%- endif %}
+
+                {%- if self.blake2b() %}
+
+                // Initialize blake2b with personalization "Halo2-Transcript", and absorb vk digest
+                mstore(0x20, 0x48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5)
+                mstore(0x40, 0xd182e6ad7f520e511f6c3e2b8c68059b23dc2d9499f4d76d184f0d706ba4902f)
+                mstore(0x140, 0)
+                let hash_mptr := 0x160
+                success, hash_mptr := absorb_scalar(success, hash_mptr, mload(VK_DIGEST_MPTR))
+
+                // Read instances and witness commitments and generate challenges
+                {%- else if self.poseidon() %}
+
+                // Initialize poseidon with capacity 2^64, and absorb vk digest
+                mstore(0x00, 0x10000000000000000)
+                mstore(0x20, 0)
+                mstore(0x40, 0)
+                mstore(0x60, 0)
+                mstore(0x80, 0)
+                let hash_mptr := 0x20
+                success, hash_mptr := absorb_scalar(success, hash_mptr, mload(VK_DIGEST_MPTR))
+
+                // Read instances and witness commitments and generate challenges
+                {%- else %}
 
                 // Absorb vk diegst
                 mstore(0x00, mload(VK_DIGEST_MPTR))
 
                 // Read instances and witness commitments and generate challenges
                 let hash_mptr := 0x20
+                {%- endif %}
                 let instance_cptr := INSTANCE_CPTR
                 for
                     { let instance_cptr_end := add(instance_cptr, mul(0x20, num_instances)) }
@@ -264,10 +812,43 @@ contract Halo2Verifier {
                 {
                     let instance := calldataload(instance_cptr)
                     success := and(success, lt(instance, r))
+                    {%- if custom_errors %}
+                    if iszero(success) { revert_with_arg(INVALID_SCALAR_SIG, instance_cptr) }
+                    {%- endif %}
+                    {%- if self.little_endian() %}
+                    success, hash_mptr := absorb_scalar(success, hash_mptr, instance)
+                    instance_cptr := add(instance_cptr, 0x20)
+                    {%- else %}
                     mstore(hash_mptr, instance)
                     instance_cptr := add(instance_cptr, 0x20)
                     hash_mptr := add(hash_mptr, 0x20)
+                    {%- endif %}
+                }
+                {%- match public_input_hash %}
+                {%- when Some with (public_input_hash) %}
+
+                // Check keccak256 of public data matches its limbs in instances, least significant limb first
+                {
+                    let public_data_cptr := add(0x04, calldataload(PUBLIC_DATA_OFFSET_CPTR))
+                    let public_data_len := calldataload(public_data_cptr)
+                    let mptr := add(PAIRING_RHS_Y_MPTR, 0x20)
+                    calldatacopy(mptr, add(public_data_cptr, 0x20), public_data_len)
+                    let public_data_hash := keccak256(mptr, public_data_len)
+                    {%- for idx in 0..public_input_hash.num_limbs %}
+                    {%- let offset = public_input_hash.offset + idx %}
+                    {%- let shift = public_input_hash.num_limb_bits * idx %}
+                    {%- if shift == 0 %}
+                    success := and(success, eq(calldataload(add(INSTANCE_CPTR, {{ (32 * offset)|hex() }})), and(public_data_hash, {{ public_input_hash.mask()|hex() }})))
+                    {%- else %}
+                    success := and(success, eq(calldataload(add(INSTANCE_CPTR, {{ (32 * offset)|hex() }})), and(shr({{ shift }}, public_data_hash), {{ public_input_hash.mask()|hex() }})))
+                    {%- endif %}
+                    {%- endfor %}
+                    {%- if custom_errors %}
+                    if iszero(success) { revert_with(INVALID_PUBLIC_INPUT_HASH_SIG) }
+                    {%- endif %}
                 }
+                {%- when None %}
+                {%- endmatch %}
 
                 let proof_cptr := PROOF_CPTR
                 let challenge_mptr := CHALLENGE_MPTR
@@ -276,18 +857,50 @@ contract Halo2Verifier {
 
                 // Phase {{ loop.index }}
                 for
-                    { let proof_cptr_end := add(proof_cptr, {{ (2 * 32 * num_advices)|hex() }}) }
+                    { let proof_cptr_end := add(proof_cptr, {{ (self.ec_point_len() * num_advices)|hex() }}) }
                     lt(proof_cptr, proof_cptr_end)
                     {}
                 {
+                    {%- if compressed_points %}
+                    // Decompressed points are stored in the same order as proof
+                    let ec_point_mptr := add(EC_POINT_MPTR, shl(1, sub(proof_cptr, PROOF_CPTR)))
+                    success, proof_cptr, hash_mptr := read_ec_point(success, proof_cptr, hash_mptr, ec_point_mptr, q)
+                    {%- else %}
                     success, proof_cptr, hash_mptr := read_ec_point(success, proof_cptr, hash_mptr, q)
+                    {%- endif %}
                 }
 
-                challenge_mptr, hash_mptr := squeeze_challenge(challenge_mptr, hash_mptr, r)
+                success, challenge_mptr, hash_mptr := squeeze_challenge(success, challenge_mptr, hash_mptr, r)
                 {%- for _ in 0..num_challenges - 1 %}
-                challenge_mptr := squeeze_challenge_cont(challenge_mptr, r)
+                {%- if self.little_endian() %}
+                success, challenge_mptr, hash_mptr := squeeze_challenge(success, challenge_mptr, hash_mptr, r)
+                {%- else %}
+                success, challenge_mptr := squeeze_challenge_cont(success, challenge_mptr, r)
+                {%- endif %}
                 {%- endfor %}
                 {%- endfor %}
+                {%- if self.little_endian() %}
+
+                // Read evaluations in little-endian, and store them in memory in the same order as proof
+                for
+                    {
+                        let proof_cptr_end := add(proof_cptr, {{ (32 * num_evals)|hex() }})
+                        let eval_mptr := {{ eval_cptr }}
+                    }
+                    lt(proof_cptr, proof_cptr_end)
+                    {}
+                {
+                    let eval := reverse_bytes(calldataload(proof_cptr))
+                    success := and(success, lt(eval, r))
+                    {%- if custom_errors %}
+                    if iszero(success) { revert_with_arg(INVALID_SCALAR_SIG, proof_cptr) }
+                    {%- endif %}
+                    mstore(eval_mptr, eval)
+                    success, hash_mptr := absorb_scalar(success, hash_mptr, eval)
+                    proof_cptr := add(proof_cptr, 0x20)
+                    eval_mptr := add(eval_mptr, 0x20)
+                }
+                {%- else %}
 
                 // Read evaluations
                 for
@@ -297,24 +910,57 @@ contract Halo2Verifier {
                 {
                     let eval := calldataload(proof_cptr)
                     success := and(success, lt(eval, r))
+                    {%- if custom_errors %}
+                    if iszero(success) { revert_with_arg(INVALID_SCALAR_SIG, proof_cptr) }
+                    {%- endif %}
                     mstore(hash_mptr, eval)
                     proof_cptr := add(proof_cptr, 0x20)
                     hash_mptr := add(hash_mptr, 0x20)
                 }
+                {%- endif %}
 
                 // Read batch opening proof and generate challenges
                 {%- match scheme %}
                 {%- when Bdfg21 %}
-                challenge_mptr, hash_mptr := squeeze_challenge(challenge_mptr, hash_mptr, r)       // zeta
-                challenge_mptr := squeeze_challenge_cont(challenge_mptr, r)                        // nu
+                success, challenge_mptr, hash_mptr := squeeze_challenge(success, challenge_mptr, hash_mptr, r) // zeta
+                {%- if self.little_endian() %}
+                success, challenge_mptr, hash_mptr := squeeze_challenge(success, challenge_mptr, hash_mptr, r) // nu
+                {%- else %}
+                success, challenge_mptr := squeeze_challenge_cont(success, challenge_mptr, r)                  // nu
+                {%- endif %}
+                {%- if compressed_points %}
+
+                success, proof_cptr, hash_mptr := read_ec_point(success, proof_cptr, hash_mptr, {{ quotient_comm_cptr + 2 * num_quotients }}, q) // W
+                {%- else %}
 
                 success, proof_cptr, hash_mptr := read_ec_point(success, proof_cptr, hash_mptr, q) // W
+                {%- endif %}
 
-                challenge_mptr, hash_mptr := squeeze_challenge(challenge_mptr, hash_mptr, r)       // mu
+                success, challenge_mptr, hash_mptr := squeeze_challenge(success, challenge_mptr, hash_mptr, r) // mu
+                {%- if compressed_points %}
+
+                success, proof_cptr, hash_mptr := read_ec_point(success, proof_cptr, hash_mptr, {{ quotient_comm_cptr + 2 * num_quotients + 2 }}, q) // W'
+                {%- else %}
 
                 success, proof_cptr, hash_mptr := read_ec_point(success, proof_cptr, hash_mptr, q) // W'
+                {%- endif %}
                 {%- when Gwc19 %}
-                // TODO
+                success, challenge_mptr, hash_mptr := squeeze_challenge(success, challenge_mptr, hash_mptr, r) // v
+
+                for
+                    { let proof_cptr_end := add(proof_cptr, {{ (self.ec_point_len() * num_rotations)|hex() }}) }
+                    lt(proof_cptr, proof_cptr_end)
+                    {}
+                {
+                    {%- if compressed_points %}
+                    let ec_point_mptr := add({{ quotient_comm_cptr + 2 * num_quotients }}, shl(1, sub(proof_cptr, {{ proof_cptr + (proof_len / 32) - num_rotations }})))
+                    success, proof_cptr, hash_mptr := read_ec_point(success, proof_cptr, hash_mptr, ec_point_mptr, q) // W's
+                    {%- else %}
+                    success, proof_cptr, hash_mptr := read_ec_point(success, proof_cptr, hash_mptr, q) // W's
+                    {%- endif %}
+                }
+
+                success, challenge_mptr, hash_mptr := squeeze_challenge(success, challenge_mptr, hash_mptr, r) // u
                 {%- endmatch %}
 
                 // Read accumulator from instances
@@ -348,6 +994,9 @@ contract Halo2Verifier {
 
                     success := and(success, eq(mulmod(lhs_y, lhs_y, q), addmod(mulmod(lhs_x, mulmod(lhs_x, lhs_x, q), q), 3, q)))
                     success := and(success, eq(mulmod(rhs_y, rhs_y, q), addmod(mulmod(rhs_x, mulmod(rhs_x, rhs_x, q), q), 3, q)))
+                    {%- if custom_errors %}
+                    if iszero(success) { revert_with(INVALID_ACCUMULATOR_SIG) }
+                    {%- endif %}
 
                     mstore(ACC_LHS_X_MPTR, lhs_x)
                     mstore(ACC_LHS_Y_MPTR, lhs_y)
@@ -357,13 +1006,21 @@ contract Halo2Verifier {
 
                 pop(q)
             }
+            {%- if non_reverting %}
+
+            // Return earlier if anything from calldata is invalid
+            if iszero(success) {
+                return_false(pairing_input_only)
+            }
+            {%- else %}
 
             // Revert earlier if anything from calldata is invalid
             if iszero(success) {
                 revert(0, 0)
             }
+            {%- endif %}
 
-            // Compute lagrange evaluations and instance evaluation
+            // Compute lagrange evaluations and instance evaluations
             {
                 let k := mload(K_MPTR)
                 let x := mload(X_MPTR)
@@ -378,8 +1035,27 @@ contract Halo2Verifier {
 
                 let omega := mload(OMEGA_MPTR)
 
+                // Number of positive lagrange evaluations to compute, at least 1 for L_0, and extra
+                // ones for instance queries with negative rotation
+                let num_lagranges := 1
+                for
+                    {
+                        let num_instances_mptr := NUM_COLUMN_INSTANCES_MPTR
+                        let num_instances_mptr_end := add(num_instances_mptr, {{ (32 * num_instance_columns)|hex() }})
+                    }
+                    lt(num_instances_mptr, num_instances_mptr_end)
+                    { num_instances_mptr := add(num_instances_mptr, 0x20) }
+                {
+                    let num_instances := mload(num_instances_mptr)
+                    if gt(num_instances, num_lagranges) {
+                        num_lagranges := num_instances
+                    }
+                }
+
+                num_lagranges := add(num_lagranges, {{ num_trailing_lagranges }})
+
                 let mptr := X_N_MPTR
-                let mptr_end := add(mptr, mul(0x20, add(mload(NUM_INSTANCES_MPTR), {{ num_neg_lagranges }})))
+                let mptr_end := add(mptr, mul(0x20, add(num_lagranges, {{ num_neg_lagranges }})))
                 for
                     { let pow_of_omega := mload(OMEGA_INV_TO_L_MPTR) }
                     lt(mptr, mptr_end)
@@ -403,8 +1079,8 @@ contract Halo2Verifier {
                     pow_of_omega := mulmod(pow_of_omega, omega, r)
                 }
 
-                let l_blind := mload(add(X_N_MPTR, 0x20))
-                let l_i_cptr := add(X_N_MPTR, 0x40)
+                let l_blind := mload(add(X_N_MPTR, {{ ((num_neg_lagranges - num_blinding_factors) * 32)|hex() }}))
+                let l_i_cptr := add(X_N_MPTR, {{ ((num_neg_lagranges - num_blinding_factors + 1) * 32)|hex() }})
                 for
                     { let l_i_cptr_end := add(X_N_MPTR, {{ (num_neg_lagranges * 32)|hex() }}) }
                     lt(l_i_cptr, l_i_cptr_end)
@@ -413,22 +1089,13 @@ contract Halo2Verifier {
                     l_blind := addmod(l_blind, mload(l_i_cptr), r)
                 }
 
-                let instance_eval := mulmod(mload(l_i_cptr), calldataload(INSTANCE_CPTR), r)
-                let instance_cptr := add(INSTANCE_CPTR, 0x20)
-                l_i_cptr := add(l_i_cptr, 0x20)
-                for
-                    { let instance_cptr_end := add(INSTANCE_CPTR, mul(0x20, mload(NUM_INSTANCES_MPTR))) }
-                    lt(instance_cptr, instance_cptr_end)
-                    {
-                        instance_cptr := add(instance_cptr, 0x20)
-                        l_i_cptr := add(l_i_cptr, 0x20)
-                    }
-                {
-                    instance_eval := addmod(instance_eval, mulmod(mload(l_i_cptr), calldataload(instance_cptr), r), r)
-                }
+                // Compute instance evaluation of each instance query
+                {%- for line in instance_computations %}
+                {{ line }}
+                {%- endfor %}
 
                 let x_n_minus_1_inv := mload(mptr_end)
-                let l_last := mload(X_N_MPTR)
+                let l_last := mload(add(X_N_MPTR, {{ ((num_neg_lagranges - num_blinding_factors - 1) * 32)|hex() }}))
                 let l_0 := mload(add(X_N_MPTR, {{ (num_neg_lagranges * 32)|hex() }}))
 
                 mstore(X_N_MPTR, x_n)
@@ -436,14 +1103,32 @@ contract Halo2Verifier {
                 mstore(L_LAST_MPTR, l_last)
                 mstore(L_BLIND_MPTR, l_blind)
                 mstore(L_0_MPTR, l_0)
-                mstore(INSTANCE_EVAL_MPTR, instance_eval)
             }
 
             // Compute quotient evavluation
             {
+                {%- if num_quotient_helpers > 0 %}
+                // Accumulate quotient evaluation numerator in QUOTIENT_EVAL_MPTR by helpers
+                mstore(QUOTIENT_EVAL_MPTR, 0)
+                {%- for idx in 0..num_quotient_helpers %}
+                success := call_helper(success, quotient_helper_{{ idx }}, QUOTIENT_EVAL_MPTR, 0x20)
+                {%- endfor %}
+                let quotient_eval_numer := mload(QUOTIENT_EVAL_MPTR)
+                {%- else %}
                 let quotient_eval_numer
                 let delta := 4131629893567559867359510883348571134090853742863529169391034518566172092834
                 let y := mload(Y_MPTR)
+                {%- if !quotient_eval_shared_computations.is_empty() %}
+
+                // Compute values shared by gates, permutation and lookups once
+                {%- for code_block in quotient_eval_shared_computations %}
+                {
+                    {%- for line in code_block %}
+                    {{ line }}
+                    {%- endfor %}
+                }
+                {%- endfor %}
+                {%- endif %}
 
                 {%- for code_block in quotient_eval_numer_computations %}
                 {
@@ -455,6 +1140,7 @@ contract Halo2Verifier {
 
                 pop(y)
                 pop(delta)
+                {%- endif %}
 
                 let quotient_eval := mulmod(quotient_eval_numer, mload(X_N_MINUS_1_INV_MPTR), r)
                 mstore(QUOTIENT_EVAL_MPTR, quotient_eval)
@@ -462,6 +1148,23 @@ contract Halo2Verifier {
 
             // Compute quotient commitment
             {
+                {%- if compressed_points %}
+                mstore(0x00, mload(LAST_QUOTIENT_X_MPTR))
+                mstore(0x20, mload(add(LAST_QUOTIENT_X_MPTR, 0x20)))
+                let x_n := mload(X_N_MPTR)
+                for
+                    {
+                        let mptr := sub(LAST_QUOTIENT_X_MPTR, 0x40)
+                        let mptr_end := sub(FIRST_QUOTIENT_X_MPTR, 0x40)
+                    }
+                    lt(mptr_end, mptr)
+                    {}
+                {
+                    success := ec_mul_acc(success, x_n)
+                    success := ec_add_acc(success, mload(mptr), mload(add(mptr, 0x20)))
+                    mptr := sub(mptr, 0x40)
+                }
+                {%- else %}
                 mstore(0x00, calldataload(LAST_QUOTIENT_X_CPTR))
                 mstore(0x20, calldataload(add(LAST_QUOTIENT_X_CPTR, 0x20)))
                 let x_n := mload(X_N_MPTR)
@@ -477,12 +1180,16 @@ contract Halo2Verifier {
                     success := ec_add_acc(success, calldataload(cptr), calldataload(add(cptr, 0x20)))
                     cptr := sub(cptr, 0x40)
                 }
+                {%- endif %}
                 mstore(QUOTIENT_X_MPTR, mload(0x00))
                 mstore(QUOTIENT_Y_MPTR, mload(0x20))
             }
 
             // Compute pairing lhs and rhs
             {
+                {%- if pcs_helper %}
+                success := call_helper(success, pcs_helper, PAIRING_LHS_X_MPTR, 0x80)
+                {%- else %}
                 {%- for code_block in pcs_computations %}
                 {
                     {%- for line in code_block %}
@@ -490,6 +1197,7 @@ contract Halo2Verifier {
                     {%- endfor %}
                 }
                 {%- endfor %}
+                {%- endif %}
             }
 
             // Random linear combine with accumulator
@@ -519,6 +1227,18 @@ contract Halo2Verifier {
                 mstore(PAIRING_RHS_Y_MPTR, mload(0x20))
             }
 
+            // Return pairing input if only pairing input is requested
+            if pairing_input_only {
+                if iszero(success) {
+                    revert(0x00, 0x00)
+                }
+                mstore(0x00, mload(PAIRING_LHS_X_MPTR))
+                mstore(0x20, mload(PAIRING_LHS_Y_MPTR))
+                mstore(0x40, mload(PAIRING_RHS_X_MPTR))
+                mstore(0x60, mload(PAIRING_RHS_Y_MPTR))
+                return(0x00, 0x80)
+            }
+
             // Perform pairing
             success := ec_pairing(
                 success,
@@ -528,10 +1248,19 @@ contract Halo2Verifier {
                 mload(PAIRING_RHS_Y_MPTR)
             )
 
+            {%- if non_reverting %}
+
+            // Return 0 as result if anything fails
+            if iszero(success) {
+                return_false(pairing_input_only)
+            }
+            {%- else %}
+
             // Revert if anything fails
             if iszero(success) {
                 revert(0x00, 0x00)
             }
+            {%- endif %}
 
             // Return 1 as result if everything succeeds
             mstore(0x00, 1)
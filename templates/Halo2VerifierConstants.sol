@@ -0,0 +1,77 @@
+    uint256 internal constant    PROOF_LEN_CPTR = {{ proof_cptr - 1 }};
+    uint256 internal constant        PROOF_CPTR = {{ proof_cptr }};
+    uint256 internal constant NUM_INSTANCE_CPTR = {{ proof_cptr + (proof_len / 32) }};
+    uint256 internal constant     INSTANCE_CPTR = {{ proof_cptr + (proof_len / 32) + 1 }};
+    {%- if compressed_points %}
+
+    uint256 internal constant FIRST_QUOTIENT_X_MPTR = {{ quotient_comm_cptr }};
+    uint256 internal constant  LAST_QUOTIENT_X_MPTR = {{ quotient_comm_cptr + 2 * (num_quotients - 1) }};
+    {%- else %}
+
+    uint256 internal constant FIRST_QUOTIENT_X_CPTR = {{ quotient_comm_cptr }};
+    uint256 internal constant  LAST_QUOTIENT_X_CPTR = {{ quotient_comm_cptr + 2 * (num_quotients - 1) }};
+    {%- endif %}
+
+    uint256 internal constant                VK_MPTR = {{ vk_mptr }};
+    uint256 internal constant         VK_DIGEST_MPTR = {{ vk_mptr }};
+    uint256 internal constant                 K_MPTR = {{ vk_mptr + 1 }};
+    uint256 internal constant             N_INV_MPTR = {{ vk_mptr + 2 }};
+    uint256 internal constant             OMEGA_MPTR = {{ vk_mptr + 3 }};
+    uint256 internal constant         OMEGA_INV_MPTR = {{ vk_mptr + 4 }};
+    uint256 internal constant    OMEGA_INV_TO_L_MPTR = {{ vk_mptr + 5 }};
+    uint256 internal constant     NUM_INSTANCES_MPTR = {{ vk_mptr + 6 }};
+    uint256 internal constant   HAS_ACCUMULATOR_MPTR = {{ vk_mptr + 7 }};
+    uint256 internal constant        ACC_OFFSET_MPTR = {{ vk_mptr + 8 }};
+    uint256 internal constant     NUM_ACC_LIMBS_MPTR = {{ vk_mptr + 9 }};
+    uint256 internal constant NUM_ACC_LIMB_BITS_MPTR = {{ vk_mptr + 10 }};
+    uint256 internal constant              G1_X_MPTR = {{ vk_mptr + 11 }};
+    uint256 internal constant              G1_Y_MPTR = {{ vk_mptr + 12 }};
+    uint256 internal constant            G2_X_1_MPTR = {{ vk_mptr + 13 }};
+    uint256 internal constant            G2_X_2_MPTR = {{ vk_mptr + 14 }};
+    uint256 internal constant            G2_Y_1_MPTR = {{ vk_mptr + 15 }};
+    uint256 internal constant            G2_Y_2_MPTR = {{ vk_mptr + 16 }};
+    uint256 internal constant      NEG_S_G2_X_1_MPTR = {{ vk_mptr + 17 }};
+    uint256 internal constant      NEG_S_G2_X_2_MPTR = {{ vk_mptr + 18 }};
+    uint256 internal constant      NEG_S_G2_Y_1_MPTR = {{ vk_mptr + 19 }};
+    uint256 internal constant      NEG_S_G2_Y_2_MPTR = {{ vk_mptr + 20 }};
+    uint256 internal constant NUM_COLUMN_INSTANCES_MPTR = {{ vk_mptr + 21 }};
+
+    uint256 internal constant CHALLENGE_MPTR = {{ challenge_mptr }};
+    {%- if compressed_points %}
+
+    uint256 internal constant EC_POINT_MPTR = {{ ec_point_mptr }};
+    {%- endif %}
+
+    uint256 internal constant THETA_MPTR = {{ theta_mptr }};
+    uint256 internal constant  BETA_MPTR = {{ theta_mptr + 1 }};
+    uint256 internal constant GAMMA_MPTR = {{ theta_mptr + 2 }};
+    uint256 internal constant     Y_MPTR = {{ theta_mptr + 3 }};
+    uint256 internal constant     X_MPTR = {{ theta_mptr + 4 }};
+    {%- match scheme %}
+    {%- when Bdfg21 %}
+    uint256 internal constant  ZETA_MPTR = {{ theta_mptr + 5 }};
+    uint256 internal constant    NU_MPTR = {{ theta_mptr + 6 }};
+    uint256 internal constant    MU_MPTR = {{ theta_mptr + 7 }};
+    {%- when Gwc19 %}
+    uint256 internal constant     V_MPTR = {{ theta_mptr + 5 }};
+    uint256 internal constant     U_MPTR = {{ theta_mptr + 6 }};
+    {%- endmatch %}
+
+    uint256 internal constant       ACC_LHS_X_MPTR = {{ theta_mptr + 8 }};
+    uint256 internal constant       ACC_LHS_Y_MPTR = {{ theta_mptr + 9 }};
+    uint256 internal constant       ACC_RHS_X_MPTR = {{ theta_mptr + 10 }};
+    uint256 internal constant       ACC_RHS_Y_MPTR = {{ theta_mptr + 11 }};
+    uint256 internal constant   INSTANCE_EVAL_MPTR = {{ theta_mptr + 12 }};
+    uint256 internal constant             X_N_MPTR = {{ theta_mptr + num_instance_evals + 12 }};
+    uint256 internal constant X_N_MINUS_1_INV_MPTR = {{ theta_mptr + num_instance_evals + 13 }};
+    uint256 internal constant          L_LAST_MPTR = {{ theta_mptr + num_instance_evals + 14 }};
+    uint256 internal constant         L_BLIND_MPTR = {{ theta_mptr + num_instance_evals + 15 }};
+    uint256 internal constant             L_0_MPTR = {{ theta_mptr + num_instance_evals + 16 }};
+    uint256 internal constant   QUOTIENT_EVAL_MPTR = {{ theta_mptr + num_instance_evals + 17 }};
+    uint256 internal constant      QUOTIENT_X_MPTR = {{ theta_mptr + num_instance_evals + 18 }};
+    uint256 internal constant      QUOTIENT_Y_MPTR = {{ theta_mptr + num_instance_evals + 19 }};
+    uint256 internal constant          R_EVAL_MPTR = {{ theta_mptr + num_instance_evals + 20 }};
+    uint256 internal constant   PAIRING_LHS_X_MPTR = {{ theta_mptr + num_instance_evals + 21 }};
+    uint256 internal constant   PAIRING_LHS_Y_MPTR = {{ theta_mptr + num_instance_evals + 22 }};
+    uint256 internal constant   PAIRING_RHS_X_MPTR = {{ theta_mptr + num_instance_evals + 23 }};
+    uint256 internal constant   PAIRING_RHS_Y_MPTR = {{ theta_mptr + num_instance_evals + 24 }};
@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: MIT
+
+pragma solidity ^0.8.0;
+
+contract {{ name }} {
+{% include "Halo2VerifierConstants.sol" %}
+
+    uint256 internal constant HANDOFF_CPTR = {{ proof_cptr + (proof_len / 32) + 1 + num_instances }};
+    {%- if custom_errors %}
+{# #}
+    {%- for error in errors %}
+    uint256 internal constant {{ "{:>29}"|format(error.sig_name()) }} = {{ error.sig()|hex_padded(64) }};
+    {%- endfor %}
+    {%- endif %}
+
+    // Called by `Halo2Verifier` with its calldata followed by its memory[VK_MPTR..PAIRING_LHS_X_MPTR].
+    fallback() external {
+        assembly {
+            {%- match kind %}
+            {%- when Quotient %}
+            {%- when Pcs %}
+            // Batch invert values in memory[mptr_start..mptr_end] in place.
+            // Return updated (success).
+            function batch_invert(success, mptr_start, mptr_end, r) -> ret {
+                let gp_mptr := mptr_end
+                let gp := mload(mptr_start)
+                let mptr := add(mptr_start, 0x20)
+                for
+                    {}
+                    lt(mptr, sub(mptr_end, 0x20))
+                    {}
+                {
+                    gp := mulmod(gp, mload(mptr), r)
+                    mstore(gp_mptr, gp)
+                    mptr := add(mptr, 0x20)
+                    gp_mptr := add(gp_mptr, 0x20)
+                }
+                gp := mulmod(gp, mload(mptr), r)
+
+                mstore(gp_mptr, 0x20)
+                mstore(add(gp_mptr, 0x20), 0x20)
+                mstore(add(gp_mptr, 0x40), 0x20)
+                mstore(add(gp_mptr, 0x60), gp)
+                mstore(add(gp_mptr, 0x80), sub(r, 2))
+                mstore(add(gp_mptr, 0xa0), r)
+                ret := and(success, staticcall(gas(), 0x05, gp_mptr, 0xc0, gp_mptr, 0x20))
+                {%- if custom_errors %}
+                if iszero(ret) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x05) }
+                {%- endif %}
+                let all_inv := mload(gp_mptr)
+
+                let first_mptr := mptr_start
+                let second_mptr := add(first_mptr, 0x20)
+                gp_mptr := sub(gp_mptr, 0x20)
+                for
+                    {}
+                    lt(second_mptr, mptr)
+                    {}
+                {
+                    let inv := mulmod(all_inv, mload(gp_mptr), r)
+                    all_inv := mulmod(all_inv, mload(mptr), r)
+                    mstore(mptr, inv)
+                    mptr := sub(mptr, 0x20)
+                    gp_mptr := sub(gp_mptr, 0x20)
+                }
+                let inv_first := mulmod(all_inv, mload(second_mptr), r)
+                let inv_second := mulmod(all_inv, mload(first_mptr), r)
+                mstore(first_mptr, inv_first)
+                mstore(second_mptr, inv_second)
+            }
+
+            // Add (x, y) into point at (0x00, 0x20).
+            // Return updated (success).
+            function ec_add_acc(success, x, y) -> ret {
+                mstore(0x40, x)
+                mstore(0x60, y)
+                ret := and(success, staticcall(gas(), 0x06, 0x00, 0x80, 0x00, 0x40))
+                {%- if custom_errors %}
+                if iszero(ret) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x06) }
+                {%- endif %}
+            }
+
+            // Scale point at (0x00, 0x20) by scalar.
+            function ec_mul_acc(success, scalar) -> ret {
+                mstore(0x40, scalar)
+                ret := and(success, staticcall(gas(), 0x07, 0x00, 0x60, 0x00, 0x40))
+                {%- if custom_errors %}
+                if iszero(ret) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x07) }
+                {%- endif %}
+            }
+
+            // Add (x, y) into point at (0x80, 0xa0).
+            // Return updated (success).
+            function ec_add_tmp(success, x, y) -> ret {
+                mstore(0xc0, x)
+                mstore(0xe0, y)
+                ret := and(success, staticcall(gas(), 0x06, 0x80, 0x80, 0x80, 0x40))
+                {%- if custom_errors %}
+                if iszero(ret) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x06) }
+                {%- endif %}
+            }
+
+            // Scale point at (0x80, 0xa0) by scalar.
+            // Return updated (success).
+            function ec_mul_tmp(success, scalar) -> ret {
+                mstore(0xc0, scalar)
+                ret := and(success, staticcall(gas(), 0x07, 0x80, 0x60, 0x80, 0x40))
+                {%- if custom_errors %}
+                if iszero(ret) { revert_with_arg(PRECOMPILE_FAILED_SIG, 0x07) }
+                {%- endif %}
+            }
+            {%- if custom_errors %}
+
+            // Revert with custom error of given signature and argument.
+            function revert_with_arg(sig, arg) {
+                mstore(0x00, sig)
+                mstore(0x04, arg)
+                revert(0x00, 0x24)
+            }
+            {%- endif %}
+{# #}
+            {%- endmatch %}
+            // Modulus
+            let r := 21888242871839275222246405745257275088548364400416034343698204186575808495617 // BN254 scalar field
+
+            // Restore memory handed off by verifier
+            calldatacopy(VK_MPTR, HANDOFF_CPTR, sub(PAIRING_LHS_X_MPTR, VK_MPTR))
+            {%- match kind %}
+            {%- when Quotient %}
+
+            // Continue accumulating quotient evaluation numerator
+            {
+                let quotient_eval_numer := mload(QUOTIENT_EVAL_MPTR)
+                let delta := 4131629893567559867359510883348571134090853742863529169391034518566172092834
+                let y := mload(Y_MPTR)
+                {%- if !shared_computations.is_empty() %}
+
+                // Compute values shared by gates, permutation and lookups once
+                {%- for code_block in shared_computations %}
+                {
+                    {%- for line in code_block %}
+                    {{ line }}
+                    {%- endfor %}
+                }
+                {%- endfor %}
+                {%- endif %}
+
+                {%- for code_block in computations %}
+                {
+                    {%- for line in code_block %}
+                    {{ line }}
+                    {%- endfor %}
+                }
+                {%- endfor %}
+
+                pop(y)
+                pop(delta)
+
+                mstore(0x00, quotient_eval_numer)
+                return(0x00, 0x20)
+            }
+            {%- when Pcs %}
+
+            // Initialize success as true
+            let success := true
+
+            // Compute pairing lhs and rhs
+            {
+                {%- for code_block in computations %}
+                {
+                    {%- for line in code_block %}
+                    {{ line }}
+                    {%- endfor %}
+                }
+                {%- endfor %}
+            }
+
+            // Revert if anything fails
+            if iszero(success) {
+                revert(0x00, 0x00)
+            }
+
+            // Return pairing lhs and rhs
+            mstore(0x00, mload(PAIRING_LHS_X_MPTR))
+            mstore(0x20, mload(PAIRING_LHS_Y_MPTR))
+            mstore(0x40, mload(PAIRING_RHS_X_MPTR))
+            mstore(0x60, mload(PAIRING_RHS_Y_MPTR))
+            return(0x00, 0x80)
+            {%- endmatch %}
+        }
+    }
+}
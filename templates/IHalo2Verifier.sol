@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: MIT
+
+pragma solidity ^0.8.0;
+
+interface IHalo2Verifier {
+    {%- for error in errors %}
+    {{ error.declaration() }}
+    {%- endfor %}
+    {%- for function in functions %}
+    {{ function.declaration() }}
+    {%- endfor %}
+}